@@ -0,0 +1,9 @@
+-- Durable processing job queue (I-FR-03: Asynchronous execution, I-FR-08: Scalability)
+-- Workers claim QUEUED jobs with FOR UPDATE SKIP LOCKED and hold a renewable lease
+
+ALTER TABLE processing_jobs ADD COLUMN IF NOT EXISTS locked_by VARCHAR(255);
+ALTER TABLE processing_jobs ADD COLUMN IF NOT EXISTS heartbeat_at TIMESTAMPTZ;
+ALTER TABLE processing_jobs ADD COLUMN IF NOT EXISTS lease_expires_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_jobs_claimable ON processing_jobs(created_at) WHERE status = 'QUEUED';
+CREATE INDEX IF NOT EXISTS idx_jobs_lease ON processing_jobs(lease_expires_at) WHERE status = 'PROCESSING';
@@ -0,0 +1,12 @@
+-- Automatic job retries with exponential backoff (I-FR-16: User-configurable retry mechanisms)
+
+ALTER TABLE processing_jobs ADD COLUMN IF NOT EXISTS next_attempt_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_jobs_retry_due ON processing_jobs(next_attempt_at) WHERE status = 'RETRYING';
+
+-- Platform-wide settings editable at runtime (e.g. global retry policy)
+CREATE TABLE IF NOT EXISTS platform_settings (
+    setting_key VARCHAR(100) PRIMARY KEY,
+    value JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
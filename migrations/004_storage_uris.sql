@@ -0,0 +1,6 @@
+-- Asset file locations are stored as storage URIs (file://, s3://)
+-- Bare paths written before this migration belong to local disk storage
+
+UPDATE assets
+SET file_path = 'file://' || file_path
+WHERE file_path NOT LIKE '%://%';
@@ -0,0 +1,17 @@
+-- Resumable (tus-style) upload sessions for large media (I-FR-31)
+-- Bytes are staged on disk until the upload is complete, then stored and registered as an asset
+
+CREATE TABLE IF NOT EXISTS upload_sessions (
+    upload_id UUID PRIMARY KEY,
+    filename VARCHAR(500) NOT NULL,
+    upload_length BIGINT NOT NULL,
+    upload_offset BIGINT NOT NULL DEFAULT 0,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    staging_path TEXT NOT NULL,
+    created_by VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_upload_sessions_expires ON upload_sessions(expires_at);
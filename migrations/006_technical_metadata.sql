@@ -0,0 +1,3 @@
+-- Technical metadata probed from the media container (duration, codecs, resolution, audio layout)
+
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS technical_metadata JSONB;
@@ -0,0 +1,3 @@
+-- MIME type sniffed from the file content at ingest; served as Content-Type on download
+
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS mime_type VARCHAR(255);
@@ -0,0 +1,3 @@
+-- Who last edited or rolled back an asset's metadata (I-FR-18, I-FR-13)
+
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS updated_by UUID REFERENCES users(id);
@@ -0,0 +1,14 @@
+-- Pending OAuth/OIDC sign-ins (I-FR-21)
+-- The state sent to the provider is recorded with its PKCE verifier and nonce; the callback
+-- must present a state that is still here, and consumes it so it cannot be replayed.
+
+CREATE TABLE IF NOT EXISTS oauth_states (
+    state VARCHAR(255) PRIMARY KEY,
+    provider VARCHAR(100) NOT NULL,
+    pkce_verifier TEXT NOT NULL,
+    nonce TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_oauth_states_expires ON oauth_states(expires_at);
@@ -0,0 +1,12 @@
+-- Account lifecycle managed by admins (I-FR-23)
+-- A disabled user keeps their data and API keys, but every credential is rejected until re-enabled.
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS disabled_at TIMESTAMPTZ;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS disabled_reason VARCHAR(500);
+
+CREATE INDEX IF NOT EXISTS idx_users_role ON users(role);
+
+-- Admin changes to accounts are written to action_records (I-FR-05)
+ALTER TYPE action_type ADD VALUE IF NOT EXISTS 'USER_ROLE_CHANGED';
+ALTER TYPE action_type ADD VALUE IF NOT EXISTS 'USER_DISABLED';
+ALTER TYPE action_type ADD VALUE IF NOT EXISTS 'USER_ENABLED';
@@ -0,0 +1,8 @@
+-- API key expiry, rotation and quotas (I-FR-23, I-FR-25)
+-- key_prefix is the non-secret start of the key so owners can tell keys apart.
+-- A rotated key points at the key it replaced; the old key stays valid until its shortened expires_at.
+
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS key_prefix VARCHAR(32);
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS rate_limit_per_minute INTEGER CHECK (rate_limit_per_minute > 0);
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS rotated_from UUID REFERENCES api_keys(id) ON DELETE SET NULL;
@@ -0,0 +1,12 @@
+-- Shared rate-limit counters (I-FR-25)
+-- Used when RATE_LIMIT_BACKEND=postgres so every instance draws from one budget per caller.
+-- One row per caller per one-minute window; old windows are purged by the rate limiter.
+
+CREATE TABLE IF NOT EXISTS rate_limit_counters (
+    identifier VARCHAR(255) NOT NULL,
+    window_start TIMESTAMPTZ NOT NULL,
+    request_count INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (identifier, window_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_rate_limit_counters_window ON rate_limit_counters(window_start);
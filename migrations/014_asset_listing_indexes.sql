@@ -0,0 +1,15 @@
+-- Indexes for GET /api/media (asset listing)
+-- Each sort key is paired with uuid, the tie-breaker used for cursor pagination.
+-- Both directions are served by scanning the same index backwards.
+
+CREATE INDEX IF NOT EXISTS idx_assets_created_uuid ON assets(created_at, uuid);
+CREATE INDEX IF NOT EXISTS idx_assets_updated_uuid ON assets((COALESCE(updated_at, created_at)), uuid);
+CREATE INDEX IF NOT EXISTS idx_assets_name_uuid ON assets(asset_name, uuid);
+CREATE INDEX IF NOT EXISTS idx_assets_size_uuid ON assets(file_size, uuid);
+
+CREATE INDEX IF NOT EXISTS idx_assets_type ON assets(asset_type);
+CREATE INDEX IF NOT EXISTS idx_assets_uploaded_by ON assets(uploaded_by);
+
+-- Containment (@>) filters on operational tags (I-FR-30) and enriched metadata fields
+CREATE INDEX IF NOT EXISTS idx_assets_operational_tags ON assets USING GIN (operational_tags jsonb_path_ops);
+CREATE INDEX IF NOT EXISTS idx_assets_enriched_metadata ON assets USING GIN (enriched_metadata jsonb_path_ops);
@@ -0,0 +1,23 @@
+-- Full-text search over assets (I-FR-22)
+-- Maintained by Postgres on every insert/update of asset_name or enriched_metadata.
+-- Weights: A title and name, B tags and keywords, C description, D transcript and OCR text.
+-- jsonb_to_tsvector indexes only string values, so JSON keys never match.
+
+ALTER TABLE assets ADD COLUMN IF NOT EXISTS search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', coalesce(enriched_metadata->>'title', '') || ' ' || asset_name), 'A') ||
+    setweight(jsonb_to_tsvector('english', coalesce(enriched_metadata->'tags', '[]'::jsonb), '["string"]'), 'B') ||
+    setweight(jsonb_to_tsvector('english', coalesce(enriched_metadata->'keywords', '[]'::jsonb), '["string"]'), 'B') ||
+    setweight(to_tsvector('english', coalesce(enriched_metadata->>'description', '')), 'C') ||
+    setweight(to_tsvector('english', coalesce(
+        enriched_metadata->>'transcript',
+        enriched_metadata#>>'{text_recognition,transcript}',
+        ''
+    )), 'D') ||
+    setweight(jsonb_to_tsvector('english', coalesce(
+        enriched_metadata->'ocr_results',
+        enriched_metadata#>'{text_recognition,ocr_results}',
+        '[]'::jsonb
+    ), '["string"]'), 'D')
+) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_assets_search_vector ON assets USING GIN (search_vector);
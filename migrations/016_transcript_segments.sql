@@ -0,0 +1,18 @@
+-- Time-coded transcript segments (I-FR-22)
+-- One row per segment produced by speech-to-text, replaced whenever the asset is reprocessed.
+-- enriched_metadata keeps the full transcript text for asset-level search.
+
+CREATE TABLE IF NOT EXISTS transcript_segments (
+    asset_uuid UUID NOT NULL REFERENCES assets(uuid) ON DELETE CASCADE,
+    segment_index INTEGER NOT NULL,
+    start_time DOUBLE PRECISION NOT NULL, -- seconds from the start of the media
+    end_time DOUBLE PRECISION NOT NULL,
+    speaker_id VARCHAR(100),
+    text TEXT NOT NULL,
+    search_vector tsvector GENERATED ALWAYS AS (to_tsvector('english', text)) STORED,
+
+    PRIMARY KEY (asset_uuid, segment_index),
+    CHECK (end_time >= start_time)
+);
+
+CREATE INDEX IF NOT EXISTS idx_transcript_segments_search ON transcript_segments USING GIN (search_vector);
@@ -0,0 +1,8 @@
+-- Typeahead suggestions (GET /api/graph/suggest, I-FR-22)
+-- Trigram indexes serve both substring (ILIKE '%...%') and fuzzy word (<%) matches.
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS idx_graph_nodes_name_trgm ON graph_nodes USING GIN (node_name gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_assets_title_trgm ON assets
+    USING GIN ((COALESCE(enriched_metadata->>'title', asset_name)) gin_trgm_ops);
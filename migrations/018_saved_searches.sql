@@ -0,0 +1,28 @@
+-- Saved searches (I-FR-22)
+-- Named /api/graph/search queries, evaluated against each asset when it finishes processing.
+-- filters holds the normalized search filters; matches are kept per search so users can fetch
+-- what is new since their last check.
+
+CREATE TABLE IF NOT EXISTS saved_searches (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    query TEXT NOT NULL,
+    filters JSONB NOT NULL DEFAULT '{}'::jsonb,
+    webhook_url TEXT, -- POSTed to on every new match
+    last_checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ,
+
+    CONSTRAINT unique_saved_search_name UNIQUE (user_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS saved_search_matches (
+    saved_search_id UUID NOT NULL REFERENCES saved_searches(id) ON DELETE CASCADE,
+    asset_uuid UUID NOT NULL REFERENCES assets(uuid) ON DELETE CASCADE,
+    matched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (saved_search_id, asset_uuid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_saved_search_matches_time ON saved_search_matches(saved_search_id, matched_at);
@@ -0,0 +1,6 @@
+-- Speaker lookups for same_speaker relationships (I-FR-20)
+-- Re-indexing an asset finds other assets by its speakers; without this every index
+-- scanned the whole transcript_segments table.
+
+CREATE INDEX IF NOT EXISTS idx_transcript_segments_speaker ON transcript_segments (speaker_id, asset_uuid)
+    WHERE speaker_id IS NOT NULL;
@@ -0,0 +1,300 @@
+// API error responses
+// Every failed request gets the same JSON body (`ErrorResponse`): a stable `error` code,
+// a human-readable `message`, the HTTP `status_code`, the `request_id` to quote in support
+// requests and, for validation failures, the offending fields in `details`.
+
+use crate::api::openapi::ErrorResponse;
+use crate::middleware::request_id::current_request_id;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::Serialize;
+use std::fmt;
+use utoipa::ToSchema;
+
+// Postgres SQLSTATE codes mapped to client errors
+const UNIQUE_VIOLATION: &str = "23505";
+const FOREIGN_KEY_VIOLATION: &str = "23503";
+const NOT_NULL_VIOLATION: &str = "23502";
+const CHECK_VIOLATION: &str = "23514";
+const INVALID_TEXT_REPRESENTATION: &str = "22P02";
+
+// Unique constraints clients can trip, with the field and message to report
+const UNIQUE_CONSTRAINTS: &[(&str, &str, &str)] = &[
+    ("unique_file_hash", "file_hash", "An asset with identical content already exists"),
+    ("users_email_key", "email", "A user with this email already exists"),
+    ("unique_saved_search_name", "name", "You already have a saved search with this name"),
+];
+
+/// One invalid input field
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+    details: Vec<FieldError>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self { status, code, message: message.into(), details: vec![] }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "BAD_REQUEST", message)
+    }
+
+    /// 400 for input that is well-formed but not acceptable
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "VALIDATION_FAILED", message)
+    }
+
+    /// 400 naming the field that failed validation
+    pub fn invalid_field(field: &str, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self::validation(format!("Invalid {}: {}", field, message)).with_detail(field, message)
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "UNAUTHORIZED", message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, "FORBIDDEN", message)
+    }
+
+    pub fn not_found(resource: &str, id: impl fmt::Display) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "NOT_FOUND", format!("{} {} not found", resource, id))
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, "CONFLICT", message)
+    }
+
+    /// 502 for a failing external service (identity provider, ...)
+    pub fn upstream(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_GATEWAY, "UPSTREAM_ERROR", message)
+    }
+
+    /// 500 with a generic message; the cause is logged, never returned
+    pub fn internal(cause: impl fmt::Display) -> Self {
+        tracing::error!(request_id = current_request_id().as_deref(), "Internal error: {}", cause);
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", "An internal error occurred")
+    }
+
+    /// Failure reading or writing stored files; a missing object is reported as such
+    pub fn storage(error: impl Into<anyhow::Error>) -> Self {
+        let error = error.into();
+        let missing = error.chain()
+            .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+            .any(|e| e.kind() == std::io::ErrorKind::NotFound);
+        if missing {
+            tracing::warn!(request_id = current_request_id().as_deref(), "Stored file missing: {:#}", error);
+            return Self::new(StatusCode::NOT_FOUND, "STORAGE_OBJECT_MISSING", "The stored file could not be found");
+        }
+        tracing::error!(request_id = current_request_id().as_deref(), "Storage error: {:#}", error);
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "STORAGE_ERROR", "The file could not be read from or written to storage")
+    }
+
+    pub fn with_detail(mut self, field: &str, message: impl Into<String>) -> Self {
+        self.details.push(FieldError { field: field.to_string(), message: message.into() });
+        self
+    }
+
+    // Constraint violations are the client's doing; connection trouble is reported as unavailable
+    fn from_database(error: &sqlx::Error) -> Option<Self> {
+        match error {
+            sqlx::Error::RowNotFound => Some(Self::new(StatusCode::NOT_FOUND, "NOT_FOUND", "Resource not found")),
+            sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_) => {
+                tracing::error!(request_id = current_request_id().as_deref(), "Database unavailable: {}", error);
+                Some(Self::new(StatusCode::SERVICE_UNAVAILABLE, "SERVICE_UNAVAILABLE", "The database is temporarily unavailable"))
+            }
+            sqlx::Error::Database(db) => {
+                let constraint = db.constraint().unwrap_or("unknown");
+                let error = match db.code().as_deref() {
+                    Some(UNIQUE_VIOLATION) => match UNIQUE_CONSTRAINTS.iter().find(|(name, ..)| *name == constraint) {
+                        Some((_, field, message)) => return Some(Self::conflict(*message).with_detail(field, "already exists")),
+                        None => Self::conflict("A conflicting record already exists"),
+                    },
+                    Some(FOREIGN_KEY_VIOLATION) => Self::conflict("The change conflicts with related records"),
+                    Some(CHECK_VIOLATION) => Self::validation("A value is outside the allowed range"),
+                    Some(NOT_NULL_VIOLATION) => Self::validation("A required value is missing"),
+                    Some(INVALID_TEXT_REPRESENTATION) => Self::bad_request("A value has an invalid format"),
+                    _ => return None,
+                };
+                // The database's own wording names tables, columns and constraints; it is only logged
+                tracing::warn!(
+                    request_id = current_request_id().as_deref(),
+                    "Database rejected the request ({}): {}", constraint, db.message()
+                );
+                Some(error)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.code, self.status, self.message)
+    }
+}
+
+/// Repository and service errors: database errors anywhere in the chain are mapped, the rest are 500
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        error.chain()
+            .find_map(|cause| cause.downcast_ref::<sqlx::Error>())
+            .and_then(Self::from_database)
+            .unwrap_or_else(|| Self::internal(format!("{:#}", error)))
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::from_database(&error).unwrap_or_else(|| Self::internal(error))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorResponse {
+            error: self.code.to_string(),
+            message: self.message,
+            status_code: self.status.as_u16() as i32,
+            request_id: current_request_id(),
+            details: self.details,
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::error::{DatabaseError, ErrorKind};
+    use std::borrow::Cow;
+
+    #[derive(Debug)]
+    struct PgError {
+        code: &'static str,
+        constraint: &'static str,
+    }
+
+    impl fmt::Display for PgError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "violates constraint {}", self.constraint)
+        }
+    }
+
+    impl std::error::Error for PgError {}
+
+    impl DatabaseError for PgError {
+        fn message(&self) -> &str {
+            "violates constraint"
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.code))
+        }
+
+        fn constraint(&self) -> Option<&str> {
+            Some(self.constraint)
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Other
+        }
+    }
+
+    fn database_error(code: &'static str, constraint: &'static str) -> anyhow::Error {
+        anyhow::Error::new(sqlx::Error::Database(Box::new(PgError { code, constraint })))
+            .context("Failed to insert asset")
+    }
+
+    #[test]
+    fn duplicate_file_hash_is_a_conflict_on_that_field() {
+        let error = ApiError::from(database_error(UNIQUE_VIOLATION, "unique_file_hash"));
+
+        assert_eq!(error.status, StatusCode::CONFLICT);
+        assert_eq!(error.code, "CONFLICT");
+        assert_eq!(error.details[0].field, "file_hash");
+    }
+
+    #[test]
+    fn check_violation_is_a_validation_failure() {
+        let error = ApiError::from(database_error(CHECK_VIOLATION, "api_keys_rate_limit_per_minute_check"));
+
+        assert_eq!(error.status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.code, "VALIDATION_FAILED");
+    }
+
+    #[test]
+    fn unowned_constraints_are_reported_without_schema_details() {
+        let error = ApiError::from(database_error(UNIQUE_VIOLATION, "idx_internal_table_secret_column"));
+        assert_eq!(error.status, StatusCode::CONFLICT);
+        assert!(!error.message.contains("idx_internal"));
+        assert!(error.details.is_empty());
+
+        let error = ApiError::from(database_error(NOT_NULL_VIOLATION, "assets_file_path_not_null"));
+        assert_eq!(error.status, StatusCode::BAD_REQUEST);
+        assert!(!error.message.contains("violates"));
+        assert!(!error.message.contains("file_path"));
+
+        let error = ApiError::from(database_error(INVALID_TEXT_REPRESENTATION, "unknown"));
+        assert_eq!(error.status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.message, "A value has an invalid format");
+
+        let error = ApiError::from(database_error(FOREIGN_KEY_VIOLATION, "assets_uploaded_by_fkey"));
+        assert_eq!(error.status, StatusCode::CONFLICT);
+        assert!(!error.message.contains("fkey"));
+    }
+
+    #[test]
+    fn unrecognised_errors_are_internal_without_leaking_the_cause() {
+        let error = ApiError::from(anyhow::anyhow!("connection string postgres://secret@db"));
+
+        assert_eq!(error.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!error.message.contains("secret"));
+    }
+
+    #[test]
+    fn missing_stored_file_is_not_found() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+        let error = ApiError::storage(anyhow::Error::new(io).context("Failed to open upload"));
+
+        assert_eq!(error.status, StatusCode::NOT_FOUND);
+        assert_eq!(error.code, "STORAGE_OBJECT_MISSING");
+    }
+
+    #[tokio::test]
+    async fn response_body_carries_code_message_and_details() {
+        let response = ApiError::invalid_field("key_name", "is required").into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "VALIDATION_FAILED");
+        assert_eq!(body["status_code"], 400);
+        assert_eq!(body["details"][0]["field"], "key_name");
+    }
+}
@@ -6,12 +6,16 @@
 
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
     response::Json,
 };
 use uuid::Uuid;
+use crate::api::error::ApiError;
 use crate::db::DbPool;
+use crate::middleware::auth::AuthUser;
+use crate::config::RetryConfig;
 use crate::db::repositories::{action_repository::ActionRepository, asset_repository::AssetRepository};
+use crate::db::repositories::settings_repository::{SettingsRepository, RETRY_SETTINGS_KEY};
+use crate::services::graph_service::GraphService;
 use serde_json::json;
 use sqlx::Row;
 
@@ -34,7 +38,7 @@ use sqlx::Row;
 // I-FR-09: Get controller status
 pub async fn get_controller_status(
     State(db_pool): State<DbPool>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let controllers = vec!["BrightcoveIngress", "CloudinaryIngress", "OmnystudioIngress",
                            "BrightcoveEgress", "CloudinaryEgress"];
 
@@ -53,8 +57,7 @@ pub async fn get_controller_status(
         )
         .bind(controller_name)
         .fetch_optional(db_pool.as_ref())
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .await?;
 
         // Get success rate (last 24 hours)
         let success_rate: f64 = sqlx::query_scalar::<_, Option<f64>>(
@@ -97,7 +100,7 @@ pub async fn get_controller_status(
 
 pub async fn get_controller_metrics(
     State(db_pool): State<DbPool>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     // Similar to get_controller_status but with more detailed metrics
     get_controller_status(State(db_pool)).await
 }
@@ -105,9 +108,8 @@ pub async fn get_controller_metrics(
 // I-FR-05: Get action records
 pub async fn get_action_records(
     State(db_pool): State<DbPool>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let records = ActionRepository::get_recent(&db_pool, 100).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let records = ActionRepository::get_recent(&db_pool, 100).await?;
 
     let result: Vec<serde_json::Value> = records.iter().map(|r| {
         json!({
@@ -126,9 +128,8 @@ pub async fn get_action_records(
 pub async fn get_asset_actions(
     State(db_pool): State<DbPool>,
     Path(asset_id): Path<Uuid>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let records = ActionRepository::get_by_asset(&db_pool, asset_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let records = ActionRepository::get_by_asset(&db_pool, asset_id).await?;
 
     let result: Vec<serde_json::Value> = records.iter().map(|r| {
         json!({
@@ -146,8 +147,9 @@ pub async fn get_asset_actions(
 // I-FR-13: Rollback asset
 pub async fn rollback_asset(
     State(db_pool): State<DbPool>,
+    auth: AuthUser,
     Path((asset_id, version_id)): Path<(Uuid, Uuid)>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     // Get version number from version_id
     let version: i32 = sqlx::query_scalar(
         "SELECT version FROM asset_versions WHERE asset_uuid = $1 AND version_id = $2"
@@ -155,12 +157,13 @@ pub async fn rollback_asset(
     .bind(asset_id)
     .bind(version_id)
     .fetch_optional(db_pool.as_ref())
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-    .ok_or(StatusCode::NOT_FOUND)?;
+    .await?
+    .ok_or_else(|| ApiError::not_found("Version", version_id))?;
 
-    AssetRepository::rollback_to_version(&db_pool, asset_id, version).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    AssetRepository::rollback_to_version(&db_pool, asset_id, version, Some(auth.id)).await?;
+    tracing::info!("{} rolled back asset {} to version {}", auth.email, asset_id, version);
+    // I-FR-20: The restored metadata has its own keywords and topics
+    GraphService::sync_metadata_update(&db_pool, asset_id).await?;
 
     Ok(Json(json!({
         "status": "success",
@@ -172,13 +175,12 @@ pub async fn rollback_asset(
 // I-FR-01: Sync interval configuration
 pub async fn get_sync_interval(
     State(db_pool): State<DbPool>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let configs = sqlx::query(
         "SELECT controller_name, sync_interval_minutes FROM controller_configs"
     )
     .fetch_all(db_pool.as_ref())
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .await?;
 
     let result: Vec<serde_json::Value> = configs.iter().map(|row: &sqlx::postgres::PgRow| {
         json!({
@@ -193,9 +195,11 @@ pub async fn get_sync_interval(
 pub async fn update_sync_interval(
     State(db_pool): State<DbPool>,
     Json(payload): Json<serde_json::Value>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let controller_name = payload.get("controller_name").and_then(|s| s.as_str()).ok_or(StatusCode::BAD_REQUEST)?;
-    let interval = payload.get("interval_minutes").and_then(|i| i.as_i64()).ok_or(StatusCode::BAD_REQUEST)?;
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let controller_name = payload.get("controller_name").and_then(|s| s.as_str())
+        .ok_or_else(|| ApiError::invalid_field("controller_name", "is required"))?;
+    let interval = payload.get("interval_minutes").and_then(|i| i.as_i64())
+        .ok_or_else(|| ApiError::invalid_field("interval_minutes", "must be a number of minutes"))?;
 
     sqlx::query(
         "UPDATE controller_configs SET sync_interval_minutes = $1, updated_at = NOW() WHERE controller_name = $2"
@@ -203,8 +207,7 @@ pub async fn update_sync_interval(
     .bind(interval as i32)
     .bind(controller_name)
     .execute(db_pool.as_ref())
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .await?;
 
     Ok(Json(json!({"status": "success"})))
 }
@@ -213,9 +216,11 @@ pub async fn update_sync_interval(
 pub async fn update_logging_level(
     State(db_pool): State<DbPool>,
     Json(payload): Json<serde_json::Value>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let controller_name = payload.get("controller_name").and_then(|s| s.as_str()).ok_or(StatusCode::BAD_REQUEST)?;
-    let level = payload.get("level").and_then(|s| s.as_str()).ok_or(StatusCode::BAD_REQUEST)?;
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let controller_name = payload.get("controller_name").and_then(|s| s.as_str())
+        .ok_or_else(|| ApiError::invalid_field("controller_name", "is required"))?;
+    let level = payload.get("level").and_then(|s| s.as_str())
+        .ok_or_else(|| ApiError::invalid_field("level", "is required"))?;
 
     sqlx::query(
         "UPDATE controller_configs SET logging_level = $1, updated_at = NOW() WHERE controller_name = $2"
@@ -223,31 +228,52 @@ pub async fn update_logging_level(
     .bind(level)
     .bind(controller_name)
     .execute(db_pool.as_ref())
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .await?;
 
     Ok(Json(json!({"status": "success"})))
 }
 
 // I-FR-16: Retry configuration
+pub async fn get_retry_config(
+    State(db_pool): State<DbPool>,
+    State(defaults): State<RetryConfig>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let retry = SettingsRepository::get_retry_config(&db_pool, &defaults).await?;
+
+    Ok(Json(json!(retry)))
+}
+
 pub async fn update_retry_config(
-    State(_db_pool): State<DbPool>,
-    Json(_payload): Json<serde_json::Value>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    // TODO: Store retry config in database
-    Ok(Json(json!({"status": "success"})))
+    State(db_pool): State<DbPool>,
+    State(defaults): State<RetryConfig>,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let current = SettingsRepository::get_retry_config(&db_pool, &defaults).await?;
+
+    let updated = current.with_overrides(&payload);
+    if let Err(reason) = updated.validate() {
+        tracing::warn!("Rejected retry config: {}", reason);
+        return Err(ApiError::validation(reason));
+    }
+
+    let value = serde_json::to_value(&updated).map_err(ApiError::internal)?;
+    SettingsRepository::put(&db_pool, RETRY_SETTINGS_KEY, &value).await?;
+
+    Ok(Json(json!({
+        "status": "success",
+        "retry": value
+    })))
 }
 
 // I-FR-15: Lifecycle management
 pub async fn get_lifecycle_rules(
     State(db_pool): State<DbPool>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let rules = sqlx::query(
         "SELECT rule_id, rule_name, archive_after_days, delete_after_days FROM lifecycle_rules WHERE is_active = true ORDER BY priority DESC"
     )
     .fetch_all(db_pool.as_ref())
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .await?;
 
     let result: Vec<serde_json::Value> = rules.iter().map(|row: &sqlx::postgres::PgRow| {
         json!({
@@ -264,9 +290,10 @@ pub async fn get_lifecycle_rules(
 pub async fn create_lifecycle_rule(
     State(db_pool): State<DbPool>,
     Json(payload): Json<serde_json::Value>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let rule_id = uuid::Uuid::new_v4();
-    let rule_name = payload.get("rule_name").and_then(|s| s.as_str()).ok_or(StatusCode::BAD_REQUEST)?;
+    let rule_name = payload.get("rule_name").and_then(|s| s.as_str())
+        .ok_or_else(|| ApiError::invalid_field("rule_name", "is required"))?;
 
     sqlx::query(
         r#"
@@ -279,8 +306,7 @@ pub async fn create_lifecycle_rule(
     .bind(payload.get("archive_after_days").and_then(|d| d.as_i64()).map(|d| d as i32))
     .bind(payload.get("delete_after_days").and_then(|d| d.as_i64()).map(|d| d as i32))
     .execute(db_pool.as_ref())
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .await?;
 
     Ok(Json(json!({
         "rule_id": rule_id,
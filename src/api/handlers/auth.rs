@@ -4,18 +4,38 @@
 // I-FR-25: Rate limiting
 
 use axum::{
-    extract::{Path, State},
+    extract::{Extension, Path, State},
     http::{StatusCode, HeaderValue, HeaderMap},
     response::{Json, Response, IntoResponse},
 };
 use uuid::Uuid;
+use crate::api::error::ApiError;
 use crate::db::DbPool;
+use crate::db::repositories::oauth_state_repository::{OAuthStateRepository, PendingSignIn};
+use crate::db::repositories::token_repository::{RefreshUse, TokenRepository};
+use crate::db::repositories::settings_repository::{SettingsRepository, RATE_LIMIT_SETTINGS_KEY};
 use crate::db::repositories::user_repository::UserRepository;
+use crate::middleware::auth::{AuthUser, Claims, TokenType};
 use crate::models::user::{ApiKey, ApiKeyStatus, User, UserRole};
 use crate::api::openapi::{GoogleLoginResponse, SSOCallbackResponse};
+use crate::services::oidc::OidcService;
+use crate::services::rate_limiter::RateLimiter;
+use crate::utils::jwt::{IssuedToken, JWTService, REFRESH_TOKEN_TTL_DAYS};
+use serde::Deserialize;
 use serde_json::json;
 use sha2::{Digest, Sha256};
-use chrono::Utc;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+const GOOGLE_PROVIDER: &str = "google";
+// "mc_sk_" plus six hex characters
+const API_KEY_PREFIX_LEN: usize = 12;
+const MAX_API_KEY_LIFETIME_DAYS: i64 = 365;
+const DEFAULT_ROTATION_GRACE_HOURS: i64 = 24;
+const MAX_ROTATION_GRACE_HOURS: i64 = 168;
+// Time allowed between the login redirect and the provider's callback
+const OAUTH_STATE_TTL_MINUTES: i64 = 10;
 
 // I-FR-21: SSO login with Google
 /// Initiates Google Sign-In flow
@@ -30,17 +50,20 @@ use chrono::Utc;
     )
 )]
 pub async fn google_login(
-    State(_db_pool): State<DbPool>,
-) -> Result<Response, StatusCode> {
+    State(db_pool): State<DbPool>,
+) -> Result<Response, ApiError> {
     use crate::services::google_oauth::GoogleOAuthService;
     
     let google_oauth = GoogleOAuthService::new()
-        .map_err(|e| {
-            tracing::error!("Failed to initialize Google OAuth: {:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .map_err(|e| ApiError::internal(format!("Failed to initialize Google OAuth: {:#}", e)))?;
     
-    let (auth_url, _csrf_token) = google_oauth.get_authorization_url();
+    let (auth_url, csrf_token, pkce_verifier) = google_oauth.get_authorization_url();
+    remember_sign_in(&db_pool, PendingSignIn {
+        state: csrf_token.secret().clone(),
+        provider: GOOGLE_PROVIDER.to_string(),
+        pkce_verifier: pkce_verifier.secret().clone(),
+        nonce: None,
+    }).await?;
     
     // Return redirect URL (frontend will redirect)
     // Note: In Swagger UI, this returns JSON. In browser, you can redirect directly
@@ -48,7 +71,7 @@ pub async fn google_login(
         "redirect_url": auth_url.to_string(),
         "callback_url": "http://localhost:3000/api/auth/google/callback",
         "status": "redirect_required",
-        "message": "Redirect user to Google Sign-In. After Google auth, user will be redirected to callback_url with code parameter.",
+        "message": "Redirect user to Google Sign-In. After Google auth, user will be redirected to callback_url with code and state parameters.",
         "instructions": "1. Copy redirect_url and open in browser\n2. Sign in with Google\n3. Google will redirect to callback_url\n4. Get JWT token from response"
     })).into_response())
 }
@@ -61,41 +84,34 @@ pub async fn google_login(
     tag = "Auth",
     params(
         ("code" = String, Query, description = "Authorization code from Google"),
-        ("state" = String, Query, description = "CSRF state token")
+        ("state" = String, Query, description = "CSRF state token issued by /api/auth/google/login")
     ),
     responses(
         (status = 200, description = "Authentication successful. Returns JWT access_token and refresh_token.", body = SSOCallbackResponse),
-        (status = 400, description = "Missing authorization code", body = ErrorResponse),
-        (status = 401, description = "Invalid authorization code", body = ErrorResponse),
+        (status = 400, description = "Missing authorization code or state", body = ErrorResponse),
+        (status = 401, description = "Invalid authorization code, or unknown, expired or reused state", body = ErrorResponse),
+        (status = 403, description = "Account is disabled", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     )
 )]
 pub async fn google_callback(
     State(db_pool): State<DbPool>,
     axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     use crate::services::google_oauth::GoogleOAuthService;
     
-    // Get authorization code from Google
-    let code = params.get("code")
-        .ok_or_else(|| {
-            tracing::warn!("Missing authorization code in callback");
-            StatusCode::BAD_REQUEST
-        })?;
+    let (code, pending) = take_sign_in(&db_pool, &params, GOOGLE_PROVIDER).await?;
     
     // Initialize Google OAuth service
     let google_oauth = GoogleOAuthService::new()
-        .map_err(|e| {
-            tracing::error!("Failed to initialize Google OAuth: {:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .map_err(|e| ApiError::internal(format!("Failed to initialize Google OAuth: {:#}", e)))?;
     
     // Exchange code for access token
-    let access_token = google_oauth.exchange_code(code.clone())
+    let access_token = google_oauth.exchange_code(code, pending.pkce_verifier)
         .await
         .map_err(|e| {
             tracing::error!("Failed to exchange code for token: {:?}", e);
-            StatusCode::UNAUTHORIZED
+            ApiError::unauthorized("Authorization code was rejected by Google")
         })?;
     
     // Get user info from Google
@@ -103,27 +119,183 @@ pub async fn google_callback(
         .await
         .map_err(|e| {
             tracing::error!("Failed to get user info from Google: {:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
+            ApiError::upstream("Could not read the user's Google profile")
         })?;
     
     // Use Google ID as SSO provider ID
     let sso_provider_id = format!("google_{}", google_user.id);
+    let user = sign_in_sso_user(&db_pool, &sso_provider_id, &google_user.email, &google_user.name, None).await?;
+    let (token, refresh_token) = open_session(&db_pool, &user).await?;
     
-    // Get or create user
-    let user = match UserRepository::get_by_sso_id(&db_pool, &sso_provider_id).await {
+    Ok(Json(json!({
+        "access_token": token.token,
+        "refresh_token": refresh_token.token,
+        "token_type": "Bearer",
+        "expires_in": expires_in(&token),
+        "user": {
+            "id": user.id,
+            "email": user.email,
+            "name": user.name,
+            "role": format!("{:?}", user.role),
+            "picture": google_user.picture
+        },
+        "status": "success"
+    })))
+}
+
+// I-FR-21: SSO login with the corporate OpenID Connect provider
+/// Initiates sign-in with the configured OIDC provider
+/// Returns the provider's authorization URL
+#[utoipa::path(
+    get,
+    path = "/api/auth/oidc/login",
+    tag = "Auth",
+    responses(
+        (status = 200, description = "Returns redirect_url, the identity provider's authorization URL"),
+        (status = 404, description = "No OIDC provider is configured", body = ErrorResponse),
+        (status = 502, description = "Identity provider discovery failed", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+pub async fn oidc_login(
+    State(db_pool): State<DbPool>,
+    State(oidc): State<Option<Arc<OidcService>>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let oidc = oidc.ok_or_else(oidc_not_configured)?;
+
+    let request = oidc.authorization_request().await
+        .map_err(|e| {
+            tracing::error!("OIDC discovery failed: {:?}", e);
+            ApiError::upstream("Identity provider discovery failed")
+        })?;
+    remember_sign_in(&db_pool, PendingSignIn {
+        state: request.state,
+        provider: oidc.provider().to_string(),
+        pkce_verifier: request.pkce_verifier,
+        nonce: Some(request.nonce),
+    }).await?;
+
+    Ok(Json(json!({
+        "redirect_url": request.url.to_string(),
+        "status": "redirect_required",
+        "message": format!("Redirect user to {} sign-in. The provider redirects back with code and state parameters.", oidc.provider()),
+    })))
+}
+
+/// OIDC callback handler
+/// Verifies the ID token against the provider's JWKS and generates JWT tokens
+#[utoipa::path(
+    get,
+    path = "/api/auth/oidc/callback",
+    tag = "Auth",
+    params(
+        ("code" = String, Query, description = "Authorization code from the identity provider"),
+        ("state" = String, Query, description = "CSRF state token issued by /api/auth/oidc/login")
+    ),
+    responses(
+        (status = 200, description = "Authentication successful. Returns JWT access_token and refresh_token.", body = SSOCallbackResponse),
+        (status = 400, description = "Missing authorization code or state", body = ErrorResponse),
+        (status = 401, description = "Invalid code or ID token, or unknown, expired or reused state", body = ErrorResponse),
+        (status = 403, description = "Account is disabled", body = ErrorResponse),
+        (status = 404, description = "No OIDC provider is configured", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+pub async fn oidc_callback(
+    State(db_pool): State<DbPool>,
+    State(oidc): State<Option<Arc<OidcService>>>,
+    axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let oidc = oidc.ok_or_else(oidc_not_configured)?;
+    let (code, pending) = take_sign_in(&db_pool, &params, oidc.provider()).await?;
+
+    let id_token = oidc.exchange_code(&code, &pending.pkce_verifier).await
+        .map_err(|e| {
+            tracing::error!("Failed to exchange OIDC code: {:?}", e);
+            ApiError::unauthorized("Authorization code was rejected by the identity provider")
+        })?;
+    let identity = oidc.verify_id_token(&id_token, pending.nonce.as_deref().unwrap_or_default()).await
+        .map_err(|e| {
+            tracing::warn!("Rejected ID token from {}: {:?}", oidc.provider(), e);
+            ApiError::unauthorized("ID token could not be verified")
+        })?;
+
+    let sso_provider_id = format!("{}_{}", oidc.provider(), identity.subject);
+    let user = sign_in_sso_user(&db_pool, &sso_provider_id, &identity.email, &identity.name, identity.role).await?;
+    let (token, refresh_token) = open_session(&db_pool, &user).await?;
+
+    Ok(Json(json!({
+        "access_token": token.token,
+        "refresh_token": refresh_token.token,
+        "token_type": "Bearer",
+        "expires_in": expires_in(&token),
+        "user": {
+            "id": user.id,
+            "email": user.email,
+            "name": user.name,
+            "role": format!("{:?}", user.role),
+        },
+        "status": "success"
+    })))
+}
+
+fn oidc_not_configured() -> ApiError {
+    ApiError::new(StatusCode::NOT_FOUND, "NOT_FOUND", "No OIDC provider is configured")
+}
+
+/// Record a sign-in redirect so its callback can be matched to it
+async fn remember_sign_in(db_pool: &DbPool, pending: PendingSignIn) -> Result<(), ApiError> {
+    let expires_at = Utc::now() + Duration::minutes(OAUTH_STATE_TTL_MINUTES);
+    OAuthStateRepository::create(db_pool, &pending, expires_at).await?;
+    Ok(())
+}
+
+/// Match a callback to the sign-in that started it; each state is accepted once
+async fn take_sign_in(
+    db_pool: &DbPool,
+    params: &std::collections::HashMap<String, String>,
+    provider: &str,
+) -> Result<(String, PendingSignIn), ApiError> {
+    let code = params.get("code")
+        .ok_or_else(|| {
+            tracing::warn!("Missing authorization code in callback");
+            ApiError::invalid_field("code", "is required")
+        })?;
+    let state = params.get("state")
+        .ok_or_else(|| {
+            tracing::warn!("Missing state in callback");
+            ApiError::invalid_field("state", "is required")
+        })?;
+
+    let pending = OAuthStateRepository::take(db_pool, state, provider).await?
+        .ok_or_else(|| {
+            tracing::warn!("Unknown, expired or reused {} OAuth state", provider);
+            ApiError::unauthorized("Sign-in state is unknown, expired or already used; start the sign-in again")
+        })?;
+
+    Ok((code.clone(), pending))
+}
+
+/// Find or create the user behind an SSO identity, keeping its profile current.
+/// `role` is only Some when the identity provider's groups matched a configured mapping;
+/// otherwise an existing user keeps the role an admin gave them.
+async fn sign_in_sso_user(
+    db_pool: &DbPool,
+    sso_provider_id: &str,
+    email: &str,
+    name: &str,
+    role: Option<UserRole>,
+) -> Result<User, ApiError> {
+    let user = match UserRepository::get_by_sso_id(db_pool, sso_provider_id).await {
         Ok(Some(mut existing_user)) => {
+            let role = role.unwrap_or_else(|| existing_user.role.clone());
             // Update user info if changed
-            if existing_user.email != google_user.email || existing_user.name != google_user.name {
-                sqlx::query("UPDATE users SET email = $1, name = $2 WHERE id = $3")
-                    .bind(&google_user.email)
-                    .bind(&google_user.name)
-                    .bind(&existing_user.id)
-                    .execute(db_pool.as_ref())
-                    .await
-                    .ok();
-                
-                existing_user.email = google_user.email.clone();
-                existing_user.name = google_user.name.clone();
+            if existing_user.email != email || existing_user.name != name || existing_user.role != role {
+                UserRepository::update_profile(db_pool, existing_user.id, email, name, &role).await?;
+
+                existing_user.email = email.to_string();
+                existing_user.name = name.to_string();
+                existing_user.role = role;
             }
             existing_user
         }
@@ -131,69 +303,172 @@ pub async fn google_callback(
             // Create new user
             let new_user = User {
                 id: uuid::Uuid::new_v4(),
-                email: google_user.email.clone(),
-                name: google_user.name.clone(),
-                role: UserRole::Viewer, // Default role
-                sso_provider_id: Some(sso_provider_id.clone()),
+                email: email.to_string(),
+                name: name.to_string(),
+                role: role.unwrap_or(UserRole::Viewer), // Default role
+                sso_provider_id: Some(sso_provider_id.to_string()),
                 created_at: chrono::Utc::now(),
                 last_login: None,
+                disabled_at: None,
+                disabled_reason: None,
             };
             
-            UserRepository::create(&db_pool, &new_user).await
-                .map_err(|e| {
-                    tracing::error!("Failed to create user: {:?}", e);
-                    StatusCode::INTERNAL_SERVER_ERROR
-                })?;
+            UserRepository::create(db_pool, &new_user).await?;
             
             new_user
         }
-        Err(e) => {
-            tracing::error!("Database error: {:?}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        }
+        Err(e) => return Err(e.into()),
     };
     
+    if user.disabled_at.is_some() {
+        tracing::warn!("Sign-in refused for disabled user {}", user.id);
+        return Err(ApiError::forbidden("Account is disabled"));
+    }
+    
     // Update last login
     sqlx::query("UPDATE users SET last_login = NOW() WHERE id = $1")
-        .bind(&user.id)
+        .bind(user.id)
         .execute(db_pool.as_ref())
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    // Generate JWT tokens using JWT service
-    use crate::utils::jwt::JWTService;
-    
-    let token = JWTService::generate_access_token(
-        &user.id.to_string(),
-        &user.email,
-        &format!("{:?}", user.role),
-    ).map_err(|e| {
-        tracing::error!("Failed to generate access token: {:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    
-    let refresh_token = JWTService::generate_refresh_token(
-        &user.id.to_string(),
-        &user.email,
-        &format!("{:?}", user.role),
-    ).map_err(|e| {
-        tracing::error!("Failed to generate refresh token: {:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    
+        .await?;
+
+    Ok(user)
+}
+
+/// I-FR-21: Each sign-in opens a session that bounds every token issued from it
+async fn open_session(db_pool: &DbPool, user: &User) -> Result<(IssuedToken, IssuedToken), ApiError> {
+    let session_id = Uuid::new_v4();
+    let session_expires_at = Utc::now() + Duration::days(REFRESH_TOKEN_TTL_DAYS);
+    TokenRepository::create_session(db_pool, session_id, user.id, session_expires_at).await?;
+
+    issue_session_tokens(db_pool, user, session_id, session_expires_at).await
+}
+
+/// Issue an access/refresh pair for a session and record the refresh token as unspent
+async fn issue_session_tokens(
+    db_pool: &DbPool,
+    user: &User,
+    session_id: Uuid,
+    session_expires_at: DateTime<Utc>,
+) -> Result<(IssuedToken, IssuedToken), ApiError> {
+    let user_id = user.id.to_string();
+    let role = format!("{:?}", user.role);
+
+    let access_token = JWTService::generate_access_token(&user_id, &user.email, &role, session_id, session_expires_at)
+        .map_err(|e| ApiError::internal(format!("Failed to generate access token: {:#}", e)))?;
+
+    let refresh_token = JWTService::generate_refresh_token(&user_id, &user.email, &role, session_id, session_expires_at)
+        .map_err(|e| ApiError::internal(format!("Failed to generate refresh token: {:#}", e)))?;
+
+    TokenRepository::store_refresh_token(db_pool, refresh_token.jti, session_id, refresh_token.expires_at).await?;
+
+    Ok((access_token, refresh_token))
+}
+
+fn expires_in(token: &IssuedToken) -> i64 {
+    (token.expires_at - Utc::now()).num_seconds().max(0)
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+/// Exchange a refresh token for a new access/refresh pair
+///
+/// I-FR-21: Refresh tokens are single use. Presenting one that was already
+/// exchanged revokes the whole session, since it can only mean the token leaked.
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    tag = "Auth",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, description = "New access_token and refresh_token; the presented refresh token is spent", body = crate::api::openapi::TokenRefreshResponse),
+        (status = 401, description = "Invalid, expired, reused or revoked refresh token", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+pub async fn refresh_token(
+    State(db_pool): State<DbPool>,
+    Json(payload): Json<RefreshTokenRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let invalid = || ApiError::unauthorized("Invalid or expired refresh token");
+    let claims = JWTService::validate_token(&payload.refresh_token, TokenType::Refresh)
+        .map_err(|_| invalid())?;
+    let jti = Uuid::parse_str(&claims.jti).map_err(|_| invalid())?;
+    let session_id = Uuid::parse_str(&claims.sid).map_err(|_| invalid())?;
+    let user_id = Uuid::parse_str(&claims.user_id).map_err(|_| invalid())?;
+
+    match TokenRepository::use_refresh_token(&db_pool, jti, session_id).await? {
+        RefreshUse::Accepted => {}
+        RefreshUse::Reused => {
+            tracing::warn!("Refresh token {} reused; revoking session {}", jti, session_id);
+            TokenRepository::revoke_session(&db_pool, session_id, "refresh token reuse").await?;
+            return Err(ApiError::unauthorized("Refresh token was already used; the session has been revoked"));
+        }
+        RefreshUse::Unknown => return Err(invalid()),
+    }
+
+    if !TokenRepository::session_is_active(&db_pool, session_id).await? {
+        return Err(ApiError::unauthorized("Session has ended"));
+    }
+
+    // Reload the user so role changes apply from the next access token on
+    let user = UserRepository::get_by_id(&db_pool, user_id).await?
+        .filter(|user| user.disabled_at.is_none())
+        .ok_or_else(|| ApiError::unauthorized("Account is disabled or no longer exists"))?;
+
+    // Refresh tokens expire with their session, so the session keeps its original lifetime
+    let session_expires_at = DateTime::from_timestamp(claims.exp as i64, 0).ok_or_else(invalid)?;
+    let (token, refresh_token) = issue_session_tokens(&db_pool, &user, session_id, session_expires_at).await?;
+
     Ok(Json(json!({
-        "access_token": token,
-        "refresh_token": refresh_token,
+        "access_token": token.token,
+        "refresh_token": refresh_token.token,
         "token_type": "Bearer",
-        "expires_in": 86400, // 24 hours
-        "user": {
-            "id": user.id,
-            "email": user.email,
-            "name": user.name,
-            "role": format!("{:?}", user.role),
-            "picture": google_user.picture
-        },
-        "status": "success"
+        "expires_in": expires_in(&token),
+    })))
+}
+
+/// End the caller's session
+///
+/// Revokes the presented access token immediately along with every token issued
+/// in the same session. API keys have no session and are revoked via /api/access/keys.
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    tag = "Auth",
+    responses(
+        (status = 200, description = "Session revoked"),
+        (status = 400, description = "Caller authenticated with an API key", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn logout(
+    State(db_pool): State<DbPool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let jti = Uuid::parse_str(&claims.jti).map_err(|_| ApiError::unauthorized("Invalid token"))?;
+    let session_id = Uuid::parse_str(&claims.sid).map_err(|_| ApiError::unauthorized("Invalid token"))?;
+
+    // API keys carry their key id as the session, which never matches an active session
+    if !TokenRepository::session_is_active(&db_pool, session_id).await? {
+        return Err(ApiError::bad_request("No active session; API keys are revoked via /api/access/keys"));
+    }
+
+    TokenRepository::revoke_session(&db_pool, session_id, "logout").await?;
+    let expires_at = DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_else(Utc::now);
+    TokenRepository::revoke_token(&db_pool, jti, expires_at).await?;
+
+    tracing::info!("User {} logged out of session {}", claims.email, session_id);
+
+    Ok(Json(json!({
+        "status": "success",
+        "message": "Logged out"
     })))
 }
 
@@ -204,19 +479,21 @@ pub async fn google_callback(
 /// **WARNING**: The API key is only returned once. Store it securely.
 #[utoipa::path(
     post,
-    path = "/api/auth/api-keys",
+    path = "/api/access/keys",
     tag = "Auth",
     request_body(
         content = serde_json::Value,
-        description = "API key generation request",
+        description = "API key generation request. Permissions must be within the caller's role; expiry and quota are optional.",
         example = json!({
             "key_name": "Production Integration Key",
-            "permissions": ["submit:media", "read:metadata"]
+            "permissions": ["submit:media", "read:metadata"],
+            "expires_in_days": 90,
+            "rate_limit_per_minute": 120
         })
     ),
     responses(
         (status = 201, description = "API key generated successfully", body = ApiKeyResponse),
-        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 400, description = "Missing name, out-of-role permission, or invalid expiry or quota", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     security(
@@ -227,14 +504,159 @@ pub async fn google_callback(
 // I-FR-23: API key management
 pub async fn generate_api_key(
     State(db_pool): State<DbPool>,
+    State(limiter): State<Arc<RateLimiter>>,
+    auth: AuthUser,
     Json(payload): Json<serde_json::Value>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let key_name = payload.get("key_name").and_then(|s| s.as_str()).ok_or(StatusCode::BAD_REQUEST)?;
-    let user_id = payload.get("user_id")
-        .and_then(|u| u.as_str())
-        .and_then(|s| Uuid::parse_str(s).ok())
-        .unwrap_or_else(|| Uuid::new_v4()); // TODO: Get from auth
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let key_name = payload.get("key_name").and_then(|s| s.as_str())
+        .ok_or_else(|| ApiError::invalid_field("key_name", "is required"))?;
+    // Keys always belong to the caller
+    let owner = UserRepository::get_by_id(&db_pool, auth.id).await?
+        .ok_or_else(|| ApiError::unauthorized("Caller no longer exists"))?;
+
+    let invalid_permissions = || ApiError::invalid_field("permissions", "must be an array of permission strings");
+    let permissions: Vec<String> = match payload.get("permissions") {
+        None | Some(serde_json::Value::Null) => vec![],
+        Some(serde_json::Value::Array(scopes)) => scopes.iter()
+            .map(|v| v.as_str().map(|s| s.to_string()).ok_or_else(invalid_permissions))
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(invalid_permissions()),
+    };
+    // Scopes must be a subset of the owner's role
+    if let Some(scope) = permissions.iter().find(|scope| !owner.role.can_grant(scope)) {
+        tracing::warn!("{} requested scope {} outside role {:?}", auth.email, scope, owner.role);
+        return Err(ApiError::invalid_field("permissions", format!("{} is not granted to role {:?}", scope, owner.role)));
+    }
+
+    let expires_at = match payload.get("expires_in_days") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(days) => Some(Utc::now() + Duration::days(key_lifetime_days(days)?)),
+    };
+
+    // I-FR-25: A key's quota can only be lower than its owner's
+    let role_limit = limiter.limit_for(Some(&owner.role), None).await;
+    let rate_limit_per_minute = match payload.get("rate_limit_per_minute") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(limit) => Some(
+            limit.as_u64()
+                .filter(|l| (1..=role_limit as u64).contains(l))
+                .ok_or_else(|| ApiError::invalid_field(
+                    "rate_limit_per_minute",
+                    format!("must be between 1 and the role limit of {}", role_limit),
+                ))? as i32,
+        ),
+    };
+
+    let (api_key, api_key_record) = new_api_key(owner.id, key_name, permissions, expires_at, rate_limit_per_minute, None);
+    UserRepository::create_api_key(&db_pool, &api_key_record).await?;
+
+    // Return plaintext key (only time it's shown!)
+    Ok((StatusCode::CREATED, Json(json!({
+        "api_key": api_key,
+        "key_id": api_key_record.id,
+        "key_prefix": api_key_record.key_prefix,
+        "expires_at": api_key_record.expires_at,
+        "rate_limit_per_minute": api_key_record.rate_limit_per_minute.map(|l| l as u32).unwrap_or(role_limit),
+        "warning": "Store this key securely. It cannot be retrieved again."
+    }))))
+}
+
+/// Rotate an API key
+///
+/// I-FR-23: Issues a replacement with the same name, permissions and quota. The old key keeps
+/// working for the grace period so clients can switch over, then expires.
+#[utoipa::path(
+    post,
+    path = "/api/access/keys/{key_id}/rotate",
+    tag = "Auth",
+    params(("key_id" = Uuid, Path, description = "Key to replace")),
+    request_body(
+        content = serde_json::Value,
+        description = "Optional grace period (hours the old key stays valid, default 24, up to 168) and expiry for the new key",
+        example = json!({ "grace_period_hours": 24, "expires_in_days": 90 })
+    ),
+    responses(
+        (status = 201, description = "Replacement key; shown only once", body = ApiKeyResponse),
+        (status = 400, description = "Invalid grace period or expiry", body = ErrorResponse),
+        (status = 404, description = "Key not found for the caller", body = ErrorResponse),
+        (status = 409, description = "Key is revoked, expired or already rotated", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn rotate_api_key(
+    State(db_pool): State<DbPool>,
+    auth: AuthUser,
+    Path(key_id): Path<Uuid>,
+    payload: Option<Json<serde_json::Value>>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    let old = UserRepository::get_api_key(&db_pool, key_id, auth.id).await?
+        .ok_or_else(|| ApiError::not_found("API key", key_id))?;
+    if !matches!(old.status, ApiKeyStatus::Active) || old.is_expired() {
+        return Err(ApiError::conflict("Only active, unexpired keys can be rotated"));
+    }
+
+    let grace_hours = match payload.get("grace_period_hours") {
+        None | Some(serde_json::Value::Null) => DEFAULT_ROTATION_GRACE_HOURS,
+        Some(hours) => hours.as_i64()
+            .filter(|h| (0..=MAX_ROTATION_GRACE_HOURS).contains(h))
+            .ok_or_else(|| ApiError::invalid_field(
+                "grace_period_hours",
+                format!("must be between 0 and {}", MAX_ROTATION_GRACE_HOURS),
+            ))?,
+    };
+    // The replacement keeps the old key's lifetime unless a new one is given
+    let expires_at = match payload.get("expires_in_days") {
+        None | Some(serde_json::Value::Null) => old.expires_at.map(|at| Utc::now() + (at - old.created_at)),
+        Some(days) => Some(Utc::now() + Duration::days(key_lifetime_days(days)?)),
+    };
+
+    let (api_key, replacement) = new_api_key(
+        auth.id,
+        &old.key_name,
+        old.permissions.clone(),
+        expires_at,
+        old.rate_limit_per_minute,
+        Some(old.id),
+    );
+    let old_expires_at = Utc::now() + Duration::hours(grace_hours);
+    if !UserRepository::rotate_api_key(&db_pool, old.id, &replacement, old_expires_at).await? {
+        return Err(ApiError::conflict("Key was rotated or revoked concurrently"));
+    }
+    tracing::info!("{} rotated API key {} to {}", auth.email, old.id, replacement.id);
 
+    Ok((StatusCode::CREATED, Json(json!({
+        "api_key": api_key,
+        "key_id": replacement.id,
+        "key_prefix": replacement.key_prefix,
+        "expires_at": replacement.expires_at,
+        "rotated_from": old.id,
+        "previous_key_expires_at": old.expires_at.map_or(old_expires_at, |at| at.min(old_expires_at)),
+        "warning": "Store this key securely. It cannot be retrieved again."
+    }))))
+}
+
+fn key_lifetime_days(days: &serde_json::Value) -> Result<i64, ApiError> {
+    days.as_i64()
+        .filter(|d| (1..=MAX_API_KEY_LIFETIME_DAYS).contains(d))
+        .ok_or_else(|| ApiError::invalid_field(
+            "expires_in_days",
+            format!("must be between 1 and {}", MAX_API_KEY_LIFETIME_DAYS),
+        ))
+}
+
+/// Generate key material; only the hash and a short prefix are stored
+fn new_api_key(
+    user_id: Uuid,
+    key_name: &str,
+    permissions: Vec<String>,
+    expires_at: Option<DateTime<Utc>>,
+    rate_limit_per_minute: Option<i32>,
+    rotated_from: Option<Uuid>,
+) -> (String, ApiKey) {
     // Generate secure random key
     let api_key = format!("mc_sk_{}", Uuid::new_v4().to_string().replace("-", ""));
     
@@ -243,60 +665,65 @@ pub async fn generate_api_key(
     hasher.update(api_key.as_bytes());
     let key_hash = format!("{:x}", hasher.finalize());
 
-    let permissions: Vec<String> = payload.get("permissions")
-        .and_then(|p| p.as_array())
-        .map(|a| a.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
-        .unwrap_or_default();
-
-    let api_key_record = ApiKey {
+    let record = ApiKey {
         id: Uuid::new_v4(),
         user_id,
         key_name: key_name.to_string(),
         key_hash,
+        key_prefix: Some(api_key[..API_KEY_PREFIX_LEN].to_string()),
         permissions,
         created_at: Utc::now(),
         last_used: None,
         status: ApiKeyStatus::Active,
+        expires_at,
+        rate_limit_per_minute,
+        rotated_from,
     };
 
-    UserRepository::create_api_key(&db_pool, &api_key_record).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    (api_key, record)
+}
 
-    // Return plaintext key (only time it's shown!)
-    Ok(Json(json!({
-        "api_key": api_key,
-        "key_id": api_key_record.id,
-        "warning": "Store this key securely. It cannot be retrieved again."
-    })))
+/// API key as shown to its owner and to admins; never includes the hash
+pub(crate) fn api_key_summary(key: &ApiKey) -> serde_json::Value {
+    let status = match key.status {
+        ApiKeyStatus::Active if key.is_expired() => "Expired".to_string(),
+        ref status => format!("{:?}", status),
+    };
+    json!({
+        "id": key.id,
+        "key_name": key.key_name,
+        "key_prefix": key.key_prefix,
+        "permissions": key.permissions,
+        "created_at": key.created_at,
+        "last_used": key.last_used,
+        "expires_at": key.expires_at,
+        "rate_limit_per_minute": key.rate_limit_per_minute,
+        "rotated_from": key.rotated_from,
+        "status": status,
+    })
 }
 
 pub async fn list_api_keys(
     State(db_pool): State<DbPool>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let user_id = Uuid::new_v4(); // TODO: Get from auth
-    let keys = UserRepository::list_api_keys(&db_pool, user_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    let result: Vec<serde_json::Value> = keys.iter().map(|k| {
-        json!({
-            "id": k.id,
-            "key_name": k.key_name,
-            "permissions": k.permissions,
-            "created_at": k.created_at,
-            "last_used": k.last_used,
-            "status": format!("{:?}", k.status),
-        })
-    }).collect();
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let keys = UserRepository::list_api_keys(&db_pool, auth.id).await?;
+
+    let result: Vec<serde_json::Value> = keys.iter().map(api_key_summary).collect();
 
     Ok(Json(json!(result)))
 }
 
 pub async fn revoke_api_key(
     State(db_pool): State<DbPool>,
+    auth: AuthUser,
     Path(key_id): Path<Uuid>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    UserRepository::revoke_api_key(&db_pool, key_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+) -> Result<Json<serde_json::Value>, ApiError> {
+    // Only the caller's own keys can be revoked
+    let revoked = UserRepository::revoke_api_key(&db_pool, key_id, auth.id).await?;
+    if !revoked {
+        return Err(ApiError::not_found("API key", key_id));
+    }
 
     Ok(Json(json!({
         "status": "success",
@@ -307,19 +734,12 @@ pub async fn revoke_api_key(
 pub async fn get_permissions(
     State(db_pool): State<DbPool>,
     Path(user_id): Path<Uuid>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let user = UserRepository::get_by_id(&db_pool, user_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
-
-    // Map role to permissions
-    let permissions = match user.role {
-        UserRole::Admin => vec!["*"],
-        UserRole::ContentManager => vec!["read:assets", "write:metadata", "upload:media"],
-        UserRole::Editor => vec!["read:assets", "write:metadata"],
-        UserRole::Developer => vec!["submit:media", "read:metadata"],
-        UserRole::Viewer => vec!["read:assets"],
-    };
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user = UserRepository::get_by_id(&db_pool, user_id).await?
+        .ok_or_else(|| ApiError::not_found("User", user_id))?;
+
+    // Same mapping the authorization middleware enforces
+    let permissions: Vec<&str> = user.role.permissions().iter().map(|p| p.as_str()).collect();
 
     Ok(Json(json!({
         "user_id": user_id,
@@ -330,24 +750,158 @@ pub async fn get_permissions(
 
 // I-FR-25: Rate limiting configuration
 pub async fn get_rate_limit_config(
-    State(_db_pool): State<DbPool>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+    State(db_pool): State<DbPool>,
+    State(limiter): State<Arc<RateLimiter>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let config = SettingsRepository::get_rate_limit_config(&db_pool, limiter.defaults()).await?;
+    let per_api_key = rate_limited_keys(&db_pool).await?;
+
     Ok(Json(json!({
-        "default_limit_per_minute": 100,
-        "per_role": {
-            "admin": 1000,
-            "developer": 500,
-            "content_manager": 200,
-            "editor": 100,
-            "viewer": 50
-        }
+        "default_limit_per_minute": config.default_limit_per_minute,
+        "per_role": config.per_role,
+        "per_api_key": per_api_key,
+        "backend": config.backend
     })))
 }
 
 pub async fn update_rate_limit_config(
-    State(_db_pool): State<DbPool>,
-    Json(_payload): Json<serde_json::Value>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    // TODO: Store rate limit config
-    Ok(Json(json!({"status": "success"})))
-}
\ No newline at end of file
+    State(db_pool): State<DbPool>,
+    State(limiter): State<Arc<RateLimiter>>,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let current = SettingsRepository::get_rate_limit_config(&db_pool, limiter.defaults()).await?;
+
+    let updated = current.with_overrides(&payload);
+    if let Err(reason) = updated.validate() {
+        tracing::warn!("Rejected rate limit config: {}", reason);
+        return Err(ApiError::validation(reason));
+    }
+
+    // Per-key quotas live on the keys: a number sets one, null clears it
+    let key_limits: Vec<(Uuid, Option<i32>)> = match payload.get("per_api_key") {
+        None | Some(serde_json::Value::Null) => vec![],
+        Some(serde_json::Value::Object(entries)) => entries.iter()
+            .map(|(key, limit)| {
+                let field = format!("per_api_key.{}", key);
+                let key_id = Uuid::parse_str(key)
+                    .map_err(|_| ApiError::invalid_field(&field, "key must be an API key id"))?;
+                let limit = match limit {
+                    serde_json::Value::Null => None,
+                    limit => Some(
+                        limit.as_u64()
+                            .filter(|l| (1..=i32::MAX as u64).contains(l))
+                            .ok_or_else(|| ApiError::invalid_field(&field, "must be a positive number or null"))? as i32,
+                    ),
+                };
+                Ok((key_id, limit))
+            })
+            .collect::<Result<_, ApiError>>()?,
+        Some(_) => return Err(ApiError::invalid_field("per_api_key", "must be an object of key id to limit")),
+    };
+
+    for (key_id, limit) in key_limits {
+        let found = UserRepository::set_api_key_rate_limit(&db_pool, key_id, limit).await?;
+        if !found {
+            return Err(ApiError::not_found("API key", key_id));
+        }
+    }
+    let value = serde_json::to_value(&updated).map_err(ApiError::internal)?;
+    SettingsRepository::put(&db_pool, RATE_LIMIT_SETTINGS_KEY, &value).await?;
+    // Other instances pick the change up on their next refresh
+    limiter.set_config(updated.clone()).await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "default_limit_per_minute": updated.default_limit_per_minute,
+        "per_role": updated.per_role,
+        "per_api_key": rate_limited_keys(&db_pool).await?
+    })))
+}
+
+// Active keys with their own quota; the owner's role limit still caps them
+async fn rate_limited_keys(db_pool: &DbPool) -> Result<Vec<serde_json::Value>, ApiError> {
+    let keys = UserRepository::list_api_key_rate_limits(db_pool).await?;
+    Ok(keys.into_iter()
+        .map(|(key_id, key_prefix, limit)| json!({
+            "key_id": key_id,
+            "key_prefix": key_prefix,
+            "rate_limit_per_minute": limit
+        }))
+        .collect())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{db_pool, insert_user};
+    use sqlx::PgPool;
+
+    async fn sign_in(pool: &DbPool) -> (IssuedToken, IssuedToken) {
+        let user_id = insert_user(pool, "viewer@example.com", "VIEWER").await;
+        let user = UserRepository::get_by_id(pool, user_id).await.unwrap().unwrap();
+        open_session(pool, &user).await.unwrap()
+    }
+
+    fn refresh(token: &IssuedToken) -> Json<RefreshTokenRequest> {
+        Json(RefreshTokenRequest { refresh_token: token.token.clone() })
+    }
+
+    fn session_of(token: &IssuedToken) -> Uuid {
+        let claims = JWTService::validate_token(&token.token, TokenType::Access).unwrap();
+        Uuid::parse_str(&claims.sid).unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn reused_refresh_token_revokes_the_session(pool: PgPool) {
+        let pool = db_pool(pool);
+        let (access, first) = sign_in(&pool).await;
+
+        let Json(body) = refresh_token(State(pool.clone()), refresh(&first)).await.unwrap();
+        let second = body["refresh_token"].as_str().unwrap().to_string();
+
+        let error = refresh_token(State(pool.clone()), refresh(&first)).await.unwrap_err();
+        assert!(error.to_string().contains("already used"), "{}", error);
+        assert!(!TokenRepository::session_is_active(&pool, session_of(&access)).await.unwrap());
+
+        // The replacement issued before the replay dies with the session
+        let replacement = Json(RefreshTokenRequest { refresh_token: second });
+        assert!(refresh_token(State(pool.clone()), replacement).await.is_err());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn access_token_is_refused_as_refresh_token(pool: PgPool) {
+        let pool = db_pool(pool);
+        let (access, refresh_token_) = sign_in(&pool).await;
+
+        let error = refresh_token(State(pool.clone()), refresh(&access)).await.unwrap_err();
+        assert!(error.to_string().contains("Invalid or expired refresh token"), "{}", error);
+
+        // The refused attempt must not have spent the real refresh token
+        assert!(refresh_token(State(pool.clone()), refresh(&refresh_token_)).await.is_ok());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn logout_revokes_the_token_and_its_session(pool: PgPool) {
+        let pool = db_pool(pool);
+        let (access, refresh_token_) = sign_in(&pool).await;
+        let session_id = session_of(&access);
+        let claims = JWTService::validate_token(&access.token, TokenType::Access).unwrap();
+
+        let Json(body) = logout(State(pool.clone()), Extension(claims.clone())).await.unwrap();
+        assert_eq!(body["status"], "success");
+
+        assert!(TokenRepository::is_revoked(&pool, access.jti, session_id).await.unwrap());
+        let revoked_jti: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM revoked_tokens WHERE jti = $1)")
+            .bind(access.jti)
+            .fetch_one(pool.as_ref())
+            .await
+            .unwrap();
+        assert!(revoked_jti);
+        assert!(refresh_token(State(pool.clone()), refresh(&refresh_token_)).await.is_err());
+
+        let error = logout(State(pool.clone()), Extension(claims)).await.unwrap_err();
+        assert!(error.to_string().contains("No active session"), "{}", error);
+    }
+}
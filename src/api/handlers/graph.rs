@@ -3,19 +3,44 @@
 // I-FR-22: Graph-based search
 
 use axum::{
-    extract::State,
-    http::StatusCode,
+    extract::{Query, State},
     response::Json,
 };
+use crate::api::error::ApiError;
 use crate::db::DbPool;
-use crate::db::repositories::{graph_repository::GraphRepository, asset_repository::AssetRepository};
+use crate::db::repositories::graph_repository::{
+    DateInterval, FacetBucket, GraphRepository, SearchFilter, SuggestionKind,
+};
+use crate::db::repositories::transcript_repository::TranscriptRepository;
+use crate::models::asset::{AssetStatus, AssetType, SourceSystem};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use serde_json::{json, Value};
 use sqlx::Row;
 
-/// Search assets using graph-based relationships
+const DEFAULT_MAX_RESULTS: i64 = 50;
+const MAX_RESULTS_LIMIT: i64 = 200;
+const DEFAULT_FACET_SIZE: i64 = 10;
+const MAX_FACET_SIZE: i64 = 100;
+const DEFAULT_SEGMENTS_PER_ASSET: i64 = 5;
+const MAX_SEGMENTS_PER_ASSET: i64 = 50;
+
+/// Search assets by text
 /// 
 /// I-FR-22: User friendly search and graph exploration
-/// I-FR-20: Graph indexing
+///
+/// `query` uses web search syntax: `"exact phrase"`, `or`, and `-excluded`.
+/// Matches in the title rank above tags and keywords, then description, then transcript and OCR text.
+/// Page with `offset`; `next_offset` is null on the last page.
+///
+/// `facets` counts every match (not just the page) by type, source, status, language,
+/// top keywords and topics, and creation date. Any bucket value can be sent back in `filters`
+/// to narrow the search; filters take a single value or an array.
+///
+/// With `"mode": "transcript"` the query is matched against individual transcript segments
+/// instead: each asset lists its best `segments_per_asset` segments with start/end times,
+/// speaker and a `deep_link` to that point of the download (a `#t=start,end` media fragment).
+/// Facets are not computed in this mode.
 #[utoipa::path(
     post,
     path = "/api/graph/search",
@@ -26,13 +51,25 @@ use sqlx::Row;
         example = json!({
             "query": "CEO strategy 2025",
             "filters": {
-                "asset_type": "VIDEO"
+                "asset_type": ["VIDEO", "AUDIO"],
+                "source_system": "BRIGHTCOVE",
+                "status": "PROCESSED",
+                "language": "en",
+                "keywords": ["earnings"],
+                "topics": [],
+                "created_after": "2025-01-01T00:00:00Z",
+                "created_before": "2026-01-01T00:00:00Z"
             },
-            "max_results": 50
+            "facet_size": 10,
+            "date_interval": "month",
+            "mode": "assets",
+            "segments_per_asset": 5,
+            "max_results": 50,
+            "offset": 0
         })
     ),
     responses(
-        (status = 200, description = "Search results", body = GraphSearchResponse),
+        (status = 200, description = "Search results, best match first, with facet counts", body = GraphSearchResponse),
         (status = 400, description = "Bad request", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
@@ -41,58 +78,268 @@ use sqlx::Row;
         ("bearer_auth" = [])
     )
 )]
-// I-FR-22: Graph-based content discovery
+// I-FR-22: Ranked, faceted content discovery
 pub async fn search(
     State(db_pool): State<DbPool>,
     Json(payload): Json<serde_json::Value>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let query = payload.get("query")
         .and_then(|q| q.as_str())
-        .ok_or(StatusCode::BAD_REQUEST)?;
+        .filter(|q| !q.trim().is_empty())
+        .ok_or_else(|| ApiError::invalid_field("query", "is required"))?;
 
-    let filters = payload.get("filters").and_then(|f| f.as_object());
-    let asset_type = filters
-        .and_then(|f| f.get("asset_type"))
-        .and_then(|a| a.as_str());
+    let filter = parse_filter(payload.get("filters"))?;
 
     let limit = payload.get("max_results")
         .and_then(|l| l.as_i64())
-        .unwrap_or(50);
+        .unwrap_or(DEFAULT_MAX_RESULTS)
+        .clamp(1, MAX_RESULTS_LIMIT);
+    let offset = payload.get("offset")
+        .and_then(|o| o.as_i64())
+        .unwrap_or(0)
+        .max(0);
+    let facet_size = payload.get("facet_size")
+        .and_then(|s| s.as_i64())
+        .unwrap_or(DEFAULT_FACET_SIZE)
+        .clamp(1, MAX_FACET_SIZE);
+    let interval = match payload.get("date_interval").and_then(|i| i.as_str()) {
+        None => DateInterval::Month,
+        Some(name) => DateInterval::from_name(name)
+            .ok_or_else(|| ApiError::invalid_field("date_interval", "must be day, week, month or year"))?,
+    };
+
+    match payload.get("mode").and_then(|m| m.as_str()) {
+        None | Some("assets") => {}
+        Some("transcript") => {
+            let segments_per_asset = payload.get("segments_per_asset")
+                .and_then(|s| s.as_i64())
+                .unwrap_or(DEFAULT_SEGMENTS_PER_ASSET)
+                .clamp(1, MAX_SEGMENTS_PER_ASSET);
+            return search_transcripts(&db_pool, query, &filter, segments_per_asset, limit, offset).await;
+        }
+        Some(_) => return Err(ApiError::invalid_field("mode", "must be 'assets' or 'transcript'")),
+    }
+
+    let (hits, facets) = tokio::try_join!(
+        GraphRepository::search(&db_pool, query, &filter, limit, offset),
+        GraphRepository::search_facets(&db_pool, query, &filter, facet_size, interval),
+    )?;
+    let total = facets.total();
+
+    let assets: Vec<Value> = hits.iter().map(|hit| {
+        let metadata = hit.enriched_metadata.as_ref();
+        json!({
+            "uuid": hit.uuid,
+            "name": hit.asset_name,
+            "type": format!("{:?}", hit.asset_type),
+            "title": metadata.and_then(|m| m.get("title")),
+            "description": metadata.and_then(|m| m.get("description")),
+            "snippet": hit.snippet,
+            "rank": hit.rank,
+            "created_at": hit.created_at,
+        })
+    }).collect();
+    let next_offset = (offset + (hits.len() as i64) < total).then_some(offset + hits.len() as i64);
+
+    Ok(Json(json!({
+        "mode": "assets",
+        "assets": assets,
+        "total_results": total,
+        "offset": offset,
+        "max_results": limit,
+        "next_offset": next_offset,
+        "facets": {
+            "asset_type": buckets(&facets.asset_types),
+            "source_system": buckets(&facets.source_systems),
+            "status": buckets(&facets.statuses),
+            "language": buckets(&facets.languages),
+            "keywords": buckets(&facets.keywords),
+            "topics": buckets(&facets.topics),
+            "created": {
+                "interval": interval.as_str(),
+                "buckets": buckets(&facets.created),
+            },
+        },
+        "query": query
+    })))
+}
 
-    // Search graph database
-    let asset_uuids = GraphRepository::search(&db_pool, query, asset_type, limit).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+// I-FR-22: Matching transcript segments, grouped by asset in rank order
+async fn search_transcripts(
+    db_pool: &DbPool,
+    query: &str,
+    filter: &SearchFilter,
+    segments_per_asset: i64,
+    limit: i64,
+    offset: i64,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let (matches, total) = tokio::try_join!(
+        TranscriptRepository::search(db_pool, query, filter, segments_per_asset, limit, offset),
+        TranscriptRepository::count_matching_assets(db_pool, query, filter),
+    )?;
 
-    // Get full asset details
-    let mut assets = Vec::new();
-    for uuid in asset_uuids {
-        if let Ok(Some(asset)) = AssetRepository::get_by_uuid(&db_pool, uuid).await {
+    let mut assets: Vec<Value> = Vec::new();
+    for (i, segment) in matches.iter().enumerate() {
+        if i == 0 || matches[i - 1].asset_uuid != segment.asset_uuid {
             assets.push(json!({
-                "uuid": asset.uuid,
-                "name": asset.asset_name,
-                "type": format!("{:?}", asset.asset_type),
-                "title": asset.enriched_metadata.get("title"),
-                "description": asset.enriched_metadata.get("description"),
-                "created_at": asset.created_at,
+                "uuid": segment.asset_uuid,
+                "name": segment.asset_name,
+                "type": format!("{:?}", segment.asset_type),
+                "rank": segment.best_rank,
+                "matching_segments": segment.segment_count,
+                "segments": [],
+            }));
+        }
+        if let Some(Value::Array(segments)) = assets.last_mut().and_then(|asset| asset.get_mut("segments")) {
+            segments.push(json!({
+                "segment_index": segment.segment_index,
+                "start_time": segment.start_time,
+                "end_time": segment.end_time,
+                "speaker_id": segment.speaker_id,
+                "snippet": segment.snippet,
+                "deep_link": format!(
+                    "/api/media/{}/download#t={},{}",
+                    segment.asset_uuid, segment.start_time, segment.end_time
+                ),
             }));
         }
     }
+    let next_offset = (offset + (assets.len() as i64) < total).then_some(offset + assets.len() as i64);
 
     Ok(Json(json!({
+        "mode": "transcript",
         "assets": assets,
-        "total_results": assets.len(),
+        "total_results": total,
+        "offset": offset,
+        "max_results": limit,
+        "next_offset": next_offset,
         "query": query
     })))
 }
 
+fn buckets(buckets: &[FacetBucket]) -> Vec<Value> {
+    buckets.iter().map(|b| json!({ "value": b.value, "count": b.count })).collect()
+}
+
+pub(crate) fn parse_filter(filters: Option<&Value>) -> Result<SearchFilter, ApiError> {
+    let filters = match filters {
+        None | Some(Value::Null) => return Ok(SearchFilter::default()),
+        Some(Value::Object(filters)) => filters,
+        Some(_) => return Err(ApiError::invalid_field("filters", "must be an object")),
+    };
+    let field = |name: &str| filters.get(name).filter(|v| !v.is_null());
+
+    Ok(SearchFilter {
+        asset_types: parse_values(field("asset_type"), "filters.asset_type", AssetType::from_name)?,
+        source_systems: parse_values(field("source_system"), "filters.source_system", SourceSystem::from_name)?,
+        statuses: parse_values(field("status"), "filters.status", AssetStatus::from_name)?,
+        languages: parse_values(field("language"), "filters.language", |v| Some(v.to_string()))?,
+        keywords: parse_values(field("keywords"), "filters.keywords", |v| Some(v.to_string()))?,
+        topics: parse_values(field("topics"), "filters.topics", |v| Some(v.to_string()))?,
+        created_after: parse_date(field("created_after"), "filters.created_after")?,
+        created_before: parse_date(field("created_before"), "filters.created_before")?,
+    })
+}
+
+// A single string or an array of strings
+fn parse_values<T>(value: Option<&Value>, field: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Vec<T>, ApiError> {
+    let values: Vec<&Value> = match value {
+        None => return Ok(vec![]),
+        Some(Value::Array(values)) => values.iter().collect(),
+        Some(value) => vec![value],
+    };
+    values.into_iter()
+        .map(|value| {
+            let text = value.as_str()
+                .ok_or_else(|| ApiError::invalid_field(field, "must be a string or an array of strings"))?;
+            parse(text).ok_or_else(|| ApiError::invalid_field(field, format!("'{}' is not a known value", text)))
+        })
+        .collect()
+}
+
+fn parse_date(value: Option<&Value>, field: &str) -> Result<Option<DateTime<Utc>>, ApiError> {
+    value
+        .map(|value| value.as_str()
+            .and_then(|text| DateTime::parse_from_rfc3339(text).ok())
+            .map(|date| date.with_timezone(&Utc))
+            .ok_or_else(|| ApiError::invalid_field(field, "must be an RFC 3339 timestamp")))
+        .transpose()
+}
+
+const DEFAULT_SUGGESTIONS: i64 = 10;
+const MAX_SUGGESTIONS: i64 = 50;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SuggestQuery {
+    /// What the user has typed so far
+    pub q: String,
+    /// Comma-separated subset of keyword, topic, contributor, asset (default: all)
+    pub types: Option<String>,
+    /// 1-50, default 10
+    pub limit: Option<i64>,
+}
+
+/// Suggest search terms as the user types
+///
+/// I-FR-22: Typeahead over keywords, topics, contributors and asset titles.
+/// Partial and misspelt words match; suggestions starting with `q` come first,
+/// then those linked to the most assets.
+#[utoipa::path(
+    get,
+    path = "/api/graph/suggest",
+    tag = "Graph",
+    params(SuggestQuery),
+    responses(
+        (status = 200, description = "Typed suggestions, best first"),
+        (status = 400, description = "Empty query or unknown type", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn suggest(
+    State(db_pool): State<DbPool>,
+    Query(query): Query<SuggestQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let partial = query.q.trim();
+    if partial.is_empty() {
+        return Err(ApiError::invalid_field("q", "is required"));
+    }
+    let kinds = match query.types.as_deref() {
+        None => SuggestionKind::ALL.to_vec(),
+        Some(types) => types.split(',')
+            .map(str::trim)
+            .filter(|kind| !kind.is_empty())
+            .map(|kind| SuggestionKind::from_name(kind)
+                .ok_or_else(|| ApiError::invalid_field("types", format!("'{}' is not keyword, topic, contributor or asset", kind))))
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_SUGGESTIONS).clamp(1, MAX_SUGGESTIONS);
+
+    let suggestions = GraphRepository::suggest(&db_pool, partial, &kinds, limit).await?;
+
+    Ok(Json(json!({
+        "query": partial,
+        "suggestions": suggestions.iter().map(|s| json!({
+            "type": s.kind,
+            "text": s.text,
+            "asset_uuid": s.asset_uuid,
+            "asset_count": s.asset_count,
+        })).collect::<Vec<_>>(),
+    })))
+}
+
 pub async fn get_relationships(
     State(db_pool): State<DbPool>,
     Json(payload): Json<serde_json::Value>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let asset_uuid = payload.get("asset_uuid")
         .and_then(|u| u.as_str())
         .and_then(|s| uuid::Uuid::parse_str(s).ok())
-        .ok_or(StatusCode::BAD_REQUEST)?;
+        .ok_or_else(|| ApiError::invalid_field("asset_uuid", "must be a UUID"))?;
 
     // Get relationships from graph
     let relationships = sqlx::query(
@@ -100,12 +347,12 @@ pub async fn get_relationships(
         SELECT target_asset_uuid, relationship_type, relationship_data
         FROM graph_relationships
         WHERE source_asset_uuid = $1
+        ORDER BY (relationship_data->>'score')::float8 DESC NULLS LAST, relationship_type, target_asset_uuid
         "#
     )
     .bind(asset_uuid)
     .fetch_all(db_pool.as_ref())
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .await?;
 
     let result: Vec<serde_json::Value> = relationships.iter().map(|row: &sqlx::postgres::PgRow| {
         json!({
@@ -119,4 +366,74 @@ pub async fn get_relationships(
         "source_asset_uuid": asset_uuid,
         "relationships": result
     })))
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejects(filters: Value, field: &str) {
+        let error = parse_filter(Some(&filters)).unwrap_err();
+        assert!(error.to_string().contains(field), "{} should name {}", error, field);
+    }
+
+    #[test]
+    fn missing_or_null_filters_match_everything() {
+        assert!(parse_filter(None).unwrap().asset_types.is_empty());
+        assert!(parse_filter(Some(&Value::Null)).unwrap().keywords.is_empty());
+        assert!(parse_filter(Some(&json!({"asset_type": null}))).unwrap().asset_types.is_empty());
+    }
+
+    #[test]
+    fn parses_every_filter() {
+        let filter = parse_filter(Some(&json!({
+            "asset_type": ["video", "AUDIO"],
+            "source_system": "brightcove",
+            "status": ["processed"],
+            "language": "en",
+            "keywords": ["budget", "council"],
+            "topics": "politics",
+            "created_after": "2024-01-01T00:00:00Z",
+            "created_before": "2024-02-01T00:00:00+01:00",
+        })))
+        .unwrap();
+
+        assert!(matches!(filter.asset_types.as_slice(), [AssetType::Video, AssetType::Audio]));
+        assert!(matches!(filter.source_systems.as_slice(), [SourceSystem::Brightcove]));
+        assert!(matches!(filter.statuses.as_slice(), [AssetStatus::Processed]));
+        assert_eq!(filter.languages, vec!["en"]);
+        assert_eq!(filter.keywords, vec!["budget", "council"]);
+        assert_eq!(filter.topics, vec!["politics"]);
+        assert_eq!(filter.created_after.unwrap().to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert_eq!(filter.created_before.unwrap().to_rfc3339(), "2024-01-31T23:00:00+00:00");
+    }
+
+    #[test]
+    fn rejects_invalid_filters() {
+        assert!(parse_filter(Some(&json!(["video"]))).is_err());
+        rejects(json!({"asset_type": "hologram"}), "filters.asset_type");
+        rejects(json!({"source_system": ["brightcove", 7]}), "filters.source_system");
+        rejects(json!({"keywords": {"any": "budget"}}), "filters.keywords");
+        rejects(json!({"created_after": "yesterday"}), "filters.created_after");
+        rejects(json!({"created_before": 1704067200}), "filters.created_before");
+    }
+
+    #[test]
+    fn parse_values_accepts_one_string_or_an_array() {
+        let one = parse_values(Some(&json!("a")), "f", |v| Some(v.to_uppercase())).unwrap();
+        assert_eq!(one, vec!["A"]);
+        let many = parse_values(Some(&json!(["a", "b"])), "f", |v| Some(v.to_uppercase())).unwrap();
+        assert_eq!(many, vec!["A", "B"]);
+        let none = parse_values(None, "f", |v| Some(v.to_string())).unwrap();
+        assert!(none.is_empty());
+        let empty = parse_values(Some(&json!([])), "f", |v| Some(v.to_string())).unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn parse_values_reports_the_bad_value() {
+        let error = parse_values(Some(&json!(["ok", "bad"])), "f", |v| (v == "ok").then_some(())).unwrap_err();
+        assert!(error.to_string().contains("'bad' is not a known value"));
+        let error = parse_values(Some(&json!([true])), "f", |v| Some(v.to_string())).unwrap_err();
+        assert!(error.to_string().contains("must be a string or an array of strings"));
+    }
+}
@@ -3,23 +3,30 @@
 // I-FR-31: Media upload (UI)
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{StatusCode, HeaderMap, HeaderValue},
     response::{Json, Response, IntoResponse},
-    body::Bytes,
+    body::Body,
 };
-use axum_extra::extract::multipart::Multipart;
+use axum_extra::extract::multipart::{Field, Multipart};
 use uuid::Uuid;
+use crate::api::error::ApiError;
 use crate::db::DbPool;
+use crate::middleware::auth::AuthUser;
 use crate::api::openapi::{MediaSubmitResponse, MediaUploadResponse};
 use crate::db::repositories::{asset_repository::AssetRepository, workflow_repository::WorkflowRepository};
-use crate::models::asset::{Asset, AssetStatus, AssetType, SourceSystem};
+use crate::models::asset::{
+    Asset, AssetCursor, AssetFilter, AssetSortField, AssetSortValue, AssetStatus, AssetType, SourceSystem,
+};
 use crate::utils::hash;
-use crate::aws::s3::S3Service;
+use crate::utils::http_range::{self, RangeRequest};
+use crate::services::content_sniffing::{self, DetectedContent};
 use crate::services::preprocessing_service;
-use crate::services::local_storage::LocalStorageService;
-use crate::services::ai_processing::AIProcessingService;
-use chrono::Utc;
+use crate::services::storage::StorageRegistry;
+use std::sync::Arc;
+use chrono::{DateTime, Timelike, Utc};
+use tokio_util::io::ReaderStream;
+use serde::Deserialize;
 use serde_json::json;
 
 /// Submit media for AI processing (Technical Users)
@@ -39,6 +46,7 @@ use serde_json::json;
         (status = 202, description = "Media submitted successfully", body = MediaSubmitResponse),
         (status = 400, description = "Bad request - missing file or invalid data", body = ErrorResponse),
         (status = 409, description = "Duplicate asset detected", body = ErrorResponse),
+        (status = 415, description = "File content is not an allowed media or document type", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     security(
@@ -49,42 +57,41 @@ use serde_json::json;
 // I-FR-29: Technical user API submission
 pub async fn submit_media(
     State(db_pool): State<DbPool>,
+    State(storage): State<Arc<StorageRegistry>>,
+    auth: AuthUser,
     mut multipart: Multipart,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let mut file_data: Option<Vec<u8>> = None;
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let asset_uuid = Uuid::new_v4();
+    let mut upload: Option<StoredUpload> = None;
     let mut metadata_json: Option<String> = None;
     let mut operational_tags_json: Option<String> = None;
     let mut filename: Option<String> = None;
 
-    // Parse multipart form
-    while let Some(field) = multipart.next_field().await.map_err(|e| {
+    // Parse multipart form; the file is streamed to storage as it arrives
+    while let Some(mut field) = multipart.next_field().await.map_err(|e| {
         tracing::error!("Multipart parsing error: {:?}", e);
-        StatusCode::BAD_REQUEST
+        ApiError::bad_request("Malformed multipart form data")
     })? {
-        let name = field.name().unwrap_or("");
+        let name = field.name().unwrap_or("").to_string();
         
-        match name {
+        match name.as_str() {
+            "file" if upload.is_some() => {
+                tracing::warn!("Ignoring additional file field");
+            }
             "file" => {
-                filename = field.file_name().map(|s| s.to_string());
-                let data = field.bytes().await.map_err(|e| {
-                    tracing::error!("Error reading file bytes: {:?}", e);
-                    StatusCode::BAD_REQUEST
+                let name = field.file_name().ok_or_else(|| {
+                    tracing::error!("Missing filename in file field");
+                    ApiError::invalid_field("file", "must include a filename")
                 })?;
-                file_data = Some(data.to_vec());
+                let name = upload_filename(name, "file")?;
+                upload = Some(store_upload_field(&mut field, &storage, "api-submissions", asset_uuid, &name).await?);
+                filename = Some(name);
             }
             "metadata" => {
-                let data = field.text().await.map_err(|e| {
-                    tracing::warn!("Error reading metadata field: {:?}", e);
-                    StatusCode::BAD_REQUEST
-                })?;
-                metadata_json = Some(data);
+                metadata_json = Some(read_text_field(&mut field, "metadata").await?);
             }
             "operational_tags" => {
-                let data = field.text().await.map_err(|e| {
-                    tracing::warn!("Error reading operational_tags field: {:?}", e);
-                    StatusCode::BAD_REQUEST
-                })?;
-                operational_tags_json = Some(data);
+                operational_tags_json = Some(read_text_field(&mut field, "operational_tags").await?);
             }
             _ => {
                 tracing::debug!("Ignoring unknown field: {}", name);
@@ -92,51 +99,21 @@ pub async fn submit_media(
         }
     }
 
-    let file_data = file_data.ok_or_else(|| {
+    let StoredUpload { uri: storage_path, file_hash, file_size, content } = upload.ok_or_else(|| {
         tracing::error!("Missing 'file' field in multipart form");
-        StatusCode::BAD_REQUEST
-    })?;
-    let filename = filename.ok_or_else(|| {
-        tracing::error!("Missing filename in file field");
-        StatusCode::BAD_REQUEST
+        ApiError::invalid_field("file", "is required")
     })?;
-    
-    // I-FR-02: Calculate hash for deduplication
-    let file_hash = hash::calculate_file_hash_async(&file_data).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let filename = filename.ok_or_else(|| ApiError::invalid_field("file", "must include a filename"))?;
 
-    // Check for duplicate
+    // I-FR-02: Hash was computed while streaming; drop the stored copy of a duplicate
     if let Ok(Some(existing)) = AssetRepository::find_by_hash(&db_pool, &file_hash).await {
+        discard_upload(&storage, &storage_path).await;
         return Ok(Json(json!({
             "asset_uuid": existing.uuid,
             "status": "DUPLICATE",
             "message": "Asset with same hash already exists"
         })));
     }
-
-    // Upload to storage (local for testing, S3 for production)
-    let file_size = file_data.len() as i64;
-    let storage_path = if std::env::var("USE_LOCAL_STORAGE").is_ok() || std::env::var("USE_LOCAL_STORAGE").is_err() {
-        // Use local storage (default for testing)
-        let local_storage = LocalStorageService::new(
-            std::env::var("LOCAL_STORAGE_PATH").ok()
-        );
-        local_storage.save_file("api-submissions", &filename, file_data.clone()).await
-            .map_err(|e| {
-                tracing::error!("Failed to save file to local storage: {:?}", e);
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?
-    } else {
-        // Use S3 for production
-        let aws_config = aws_config::load_from_env().await;
-        let s3_service = S3Service::new(&aws_config, "mediacorp-ai-api-uploads".to_string());
-        let s3_key = s3_service.generate_key("api-submissions", &filename);
-        s3_service.upload_file(&s3_key, file_data).await
-            .map_err(|e| {
-                tracing::error!("Failed to upload file to S3: {:?}", e);
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?
-    };
     
     tracing::info!("File saved to: {}", storage_path);
 
@@ -148,31 +125,14 @@ pub async fn submit_media(
     let operational_tags: Option<serde_json::Value> = operational_tags_json
         .and_then(|s| serde_json::from_str(&s).ok());
 
-    // Determine asset type from filename extension (support all formats)
-    let asset_type = {
-        let ext = filename.split('.').last().unwrap_or("").to_lowercase();
-        match ext.as_str() {
-            // Video formats
-            "mp4" | "avi" | "mov" | "mkv" | "webm" | "flv" | "wmv" | "m4v" | "3gp" => AssetType::Video,
-            // Image formats
-            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "svg" | "ico" | "tiff" | "tif" => AssetType::Image,
-            // Audio formats
-            "mp3" | "wav" | "m4a" | "aac" | "flac" | "ogg" | "wma" | "opus" | "amr" => AssetType::Audio,
-            // Text formats
-            "pdf" | "txt" | "doc" | "docx" | "html" | "json" | "xml" | "csv" | "md" | "rtf" | "odt" => AssetType::Text,
-            _ => {
-                tracing::warn!("Unknown file extension: {}, defaulting to Text", ext);
-                AssetType::Text
-            }
-        }
-    };
+    // Asset type comes from the sniffed content; a misleading extension is only flagged
+    let mut metadata = metadata;
+    content.record_mismatch(&mut metadata, &filename);
 
     // Create asset
-    let asset_uuid = Uuid::new_v4();
-    let asset_type_clone = asset_type.clone();
     let asset = Asset {
         uuid: asset_uuid,
-        asset_type,
+        asset_type: content.asset_type.clone(),
         asset_name: filename.clone(),
         source_system: SourceSystem::ApiSubmission,
         source_id: None,
@@ -180,7 +140,7 @@ pub async fn submit_media(
         file_hash,
         file_size,
         duration: metadata.get("duration_seconds").and_then(|d| d.as_i64()).map(|d| d as i32),
-        format: filename.split('.').last().unwrap_or("unknown").to_uppercase(),
+        format: content.format.to_string(),
         status: AssetStatus::Queued,
         version: 1,
         version_id: Uuid::new_v4(),
@@ -189,11 +149,17 @@ pub async fn submit_media(
         created_at: Utc::now(),
         updated_at: None,
         processing_completed_at: None,
-        uploaded_by: None, // TODO: Get from auth middleware
+        uploaded_by: Some(auth.id),
+        technical_metadata: None,
+        mime_type: Some(content.mime_type.to_string()),
     };
 
-    AssetRepository::create(&db_pool, &asset).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // A concurrent upload of the same content surfaces here as a file_hash conflict (409)
+    if let Err(e) = AssetRepository::create(&db_pool, &asset).await {
+        tracing::error!("Failed to create asset {}: {:?}", asset_uuid, e);
+        discard_upload(&storage, &storage_path).await;
+        return Err(e.into());
+    }
 
     // I-FR-33: Determine workflow based on preprocessing logic
     let workflow_name = preprocessing_service::determine_workflow(&asset)
@@ -218,70 +184,8 @@ pub async fn submit_media(
         retry_config: None,
     };
 
-    WorkflowRepository::create_job(&db_pool, &job).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    // Trigger AI processing (local processing for testing)
-    let db_pool_clone = db_pool.clone();
-    let asset_uuid_clone = asset_uuid;
-    let workflow_name_clone = workflow_name.clone();
-    let asset_type_str = format!("{:?}", asset_type_clone);
-    let storage_path_clone = storage_path.clone();
-    tokio::spawn(async move {
-        // Update job status to processing
-        WorkflowRepository::update_job_status(
-            &db_pool_clone,
-            job_id,
-            crate::models::workflow::JobStatus::Processing,
-            Some(0), // Progress percentage
-            None,
-        ).await.ok();
-        
-        // Process with AI
-        let ai_results = AIProcessingService::process_asset(
-            &storage_path_clone,
-            &asset_type_str,
-        ).await;
-        
-        if let Ok(enriched_metadata) = ai_results {
-            // Update asset with enriched metadata
-            let current_asset = AssetRepository::get_by_uuid(&db_pool_clone, asset_uuid_clone).await;
-            if let Ok(Some(mut asset)) = current_asset {
-                // Merge AI results with existing metadata
-                let mut metadata = asset.enriched_metadata.clone();
-                for (key, value) in enriched_metadata.as_object().unwrap() {
-                    metadata[key] = value.clone();
-                }
-                
-                // Update asset
-                AssetRepository::update_metadata(
-                    &db_pool_clone,
-                    asset_uuid_clone,
-                    metadata,
-                    asset.version,
-                    asset.version_id,
-                    uuid::Uuid::new_v4(), // TODO: Get from auth
-                ).await.ok();
-                
-                    // Update asset status with completion timestamp
-                    AssetRepository::update_status(
-                        &db_pool_clone,
-                        asset_uuid_clone,
-                        AssetStatus::Processed,
-                        Some(Utc::now())
-                    ).await.ok();
-                
-                // Update job status
-                WorkflowRepository::update_job_status(
-                    &db_pool_clone,
-                    job_id,
-                    crate::models::workflow::JobStatus::Completed,
-                    Some(100), // 100% complete
-                    None, // No error
-                ).await.ok();
-            }
-        }
-    });
+    // Picked up by the durable worker pool (services::job_worker)
+    WorkflowRepository::create_job(&db_pool, &job).await?;
 
     Ok(Json(json!({
         "asset_uuid": asset_uuid,
@@ -307,6 +211,7 @@ pub async fn submit_media(
     responses(
         (status = 201, description = "Media uploaded successfully", body = MediaUploadResponse),
         (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 415, description = "File content is not an allowed media or document type", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     security(
@@ -317,36 +222,45 @@ pub async fn submit_media(
 // I-FR-31: Naive user manual upload
 pub async fn upload_media(
     State(db_pool): State<DbPool>,
+    State(storage): State<Arc<StorageRegistry>>,
+    auth: AuthUser,
     mut multipart: Multipart,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let mut file_data: Option<Vec<u8>> = None;
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let asset_uuid = Uuid::new_v4();
+    let mut upload: Option<StoredUpload> = None;
     let mut filename: Option<String> = None;
     let mut title: Option<String> = None;
     let mut description: Option<String> = None;
     let mut tags: Option<String> = None;
     let mut category: Option<String> = None;
 
-    // Parse multipart form (UI-friendly format)
-    while let Some(field) = multipart.next_field().await.map_err(|_| StatusCode::BAD_REQUEST)? {
-        let name = field.name().unwrap_or("");
+    // Parse multipart form (UI-friendly format); the file is streamed to storage
+    while let Some(mut field) = multipart.next_field().await
+        .map_err(|_| ApiError::bad_request("Malformed multipart form data"))? {
+        let name = field.name().unwrap_or("").to_string();
         
-        match name {
+        match name.as_str() {
+            "file" if upload.is_some() => {
+                tracing::warn!("Ignoring additional file field");
+            }
             "file" => {
-                filename = field.file_name().map(|s| s.to_string());
-                let data = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?;
-                file_data = Some(data.to_vec());
+                let name = field.file_name()
+                    .ok_or_else(|| ApiError::invalid_field("file", "must include a filename"))?;
+                let name = upload_filename(name, "file")?;
+                upload = Some(store_upload_field(&mut field, &storage, "uploads", asset_uuid, &name).await?);
+                filename = Some(name);
             }
             "title" => {
-                title = field.text().await.ok();
+                title = Some(read_text_field(&mut field, "title").await?);
             }
             "description" => {
-                description = field.text().await.ok();
+                description = Some(read_text_field(&mut field, "description").await?);
             }
             "tags" => {
-                tags = field.text().await.ok();
+                tags = Some(read_text_field(&mut field, "tags").await?);
             }
             "category" => {
-                category = field.text().await.ok();
+                category = Some(read_text_field(&mut field, "category").await?);
             }
             // Also support metadata as JSON (for advanced users)
             "metadata" => {
@@ -356,40 +270,20 @@ pub async fn upload_media(
         }
     }
 
-    let file_data = file_data.ok_or(StatusCode::BAD_REQUEST)?;
-    let filename = filename.ok_or(StatusCode::BAD_REQUEST)?;
-    
-    // I-FR-02: Calculate hash for deduplication
-    let file_hash = hash::calculate_file_hash_async(&file_data).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let upload = upload.ok_or_else(|| ApiError::invalid_field("file", "is required"))?;
+    let filename = filename.ok_or_else(|| ApiError::invalid_field("file", "must include a filename"))?;
+    let metadata = upload_metadata(title, description, tags, category);
 
-    // Check for duplicate
-    if let Ok(Some(existing)) = AssetRepository::find_by_hash(&db_pool, &file_hash).await {
-        return Ok(Json(json!({
-            "asset_uuid": existing.uuid,
-            "status": "DUPLICATE",
-            "message": "Asset with same hash already exists"
-        })));
-    }
-
-    // Upload to local storage (for local testing)
-    let file_size = file_data.len() as i64;
-    let storage_path = if std::env::var("USE_LOCAL_STORAGE").is_ok() {
-        let local_storage = LocalStorageService::new(
-            std::env::var("LOCAL_STORAGE_PATH").ok()
-        );
-        local_storage.save_file("uploads", &filename, file_data.clone()).await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-    } else {
-        // Use S3 for production
-        let aws_config = aws_config::load_from_env().await;
-        let s3_service = S3Service::new(&aws_config, "mediacorp-ai-api-uploads".to_string());
-        let s3_key = s3_service.generate_key("user-uploads", &filename);
-        s3_service.upload_file(&s3_key, file_data).await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-    };
+    register_user_upload(&db_pool, &storage, asset_uuid, &filename, upload, metadata, auth.id).await
+}
 
-    // Build metadata from form fields
+/// Build asset metadata from the UI upload form fields
+pub(crate) fn upload_metadata(
+    title: Option<String>,
+    description: Option<String>,
+    tags: Option<String>,
+    category: Option<String>,
+) -> serde_json::Value {
     let mut metadata = json!({});
     if let Some(t) = title {
         metadata["title"] = json!(t);
@@ -404,37 +298,48 @@ pub async fn upload_media(
     if let Some(c) = category {
         metadata["category"] = json!(c);
     }
+    metadata
+}
 
-    // Determine asset type from filename extension (support more formats)
-    let asset_type = {
-        let ext = filename.split('.').last().unwrap_or("").to_lowercase();
-        match ext.as_str() {
-            // Video formats
-            "mp4" | "avi" | "mov" | "mkv" | "webm" | "flv" | "wmv" => AssetType::Video,
-            // Image formats
-            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "svg" | "ico" => AssetType::Image,
-            // Audio formats
-            "mp3" | "wav" | "m4a" | "aac" | "flac" | "ogg" | "wma" => AssetType::Audio,
-            // Text formats
-            "pdf" | "txt" | "doc" | "docx" | "html" | "json" | "xml" | "csv" | "md" => AssetType::Text,
-            _ => AssetType::Text, // Default to text for unknown formats
-        }
-    };
+/// Turn a stored user upload into an asset and queue it for processing (I-FR-31).
+/// Shared by `upload_media` and resumable uploads so both follow the same dedup/job pipeline.
+pub(crate) async fn register_user_upload(
+    db_pool: &DbPool,
+    storage: &StorageRegistry,
+    asset_uuid: Uuid,
+    filename: &str,
+    upload: StoredUpload,
+    metadata: serde_json::Value,
+    uploaded_by: Uuid,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let StoredUpload { uri: storage_path, file_hash, file_size, content } = upload;
+
+    // I-FR-02: Hash was computed while streaming; drop the stored copy of a duplicate
+    if let Ok(Some(existing)) = AssetRepository::find_by_hash(db_pool, &file_hash).await {
+        discard_upload(storage, &storage_path).await;
+        return Ok(Json(json!({
+            "asset_uuid": existing.uuid,
+            "status": "DUPLICATE",
+            "message": "Asset with same hash already exists"
+        })));
+    }
+
+    // Asset type comes from the sniffed content; a misleading extension is only flagged
+    let mut metadata = metadata;
+    content.record_mismatch(&mut metadata, filename);
 
     // Create asset record
-    let asset_uuid = Uuid::new_v4();
-    let asset_type_clone = asset_type.clone();
     let asset = Asset {
         uuid: asset_uuid,
-        asset_type,
-        asset_name: filename.clone(),
+        asset_type: content.asset_type.clone(),
+        asset_name: filename.to_string(),
         source_system: SourceSystem::UserUpload,
         source_id: None,
         file_path: storage_path.clone(),
         file_hash,
         file_size,
         duration: metadata.get("duration_seconds").and_then(|d| d.as_i64()).map(|d| d as i32),
-        format: filename.split('.').last().unwrap_or("unknown").to_uppercase(),
+        format: content.format.to_string(),
         status: AssetStatus::Queued,
         version: 1,
         version_id: Uuid::new_v4(),
@@ -443,12 +348,17 @@ pub async fn upload_media(
         created_at: Utc::now(),
         updated_at: None,
         processing_completed_at: None,
-        uploaded_by: None, // TODO: Get from auth middleware
+        uploaded_by: Some(uploaded_by),
+        technical_metadata: None,
+        mime_type: Some(content.mime_type.to_string()),
     };
 
-    // Save to database
-    AssetRepository::create(&db_pool, &asset).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Save to database; a concurrent upload of the same content is a file_hash conflict (409)
+    if let Err(e) = AssetRepository::create(db_pool, &asset).await {
+        tracing::error!("Failed to create asset {}: {:?}", asset_uuid, e);
+        discard_upload(storage, &storage_path).await;
+        return Err(e.into());
+    }
 
     // I-FR-33: Determine workflow based on preprocessing logic
     let workflow_name = preprocessing_service::determine_workflow(&asset)
@@ -473,75 +383,13 @@ pub async fn upload_media(
         retry_config: None,
     };
 
-    WorkflowRepository::create_job(&db_pool, &job).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    // Trigger AI processing in background
-    let db_pool_clone = db_pool.clone();
-    let asset_uuid_clone = asset_uuid;
-    let workflow_name_clone = workflow_name.clone();
-    let asset_type_str = format!("{:?}", asset_type_clone);
-    let storage_path_clone = storage_path.clone();
-    tokio::spawn(async move {
-        // Update job status to processing
-        WorkflowRepository::update_job_status(
-            &db_pool_clone,
-            job_id,
-            crate::models::workflow::JobStatus::Processing,
-            Some(0),
-            None,
-        ).await.ok();
-        
-        // Process with AI
-        let ai_results = AIProcessingService::process_asset(
-            &storage_path_clone,
-            &asset_type_str,
-        ).await;
-        
-        if let Ok(enriched_metadata) = ai_results {
-            // Update asset with enriched metadata
-            if let Ok(Some(mut asset)) = AssetRepository::get_by_uuid(&db_pool_clone, asset_uuid_clone).await {
-                // Merge AI results with existing metadata
-                let mut current_metadata = asset.enriched_metadata.clone();
-                if let Some(obj) = current_metadata.as_object_mut() {
-                    if let Some(ai_obj) = enriched_metadata.as_object() {
-                        for (key, value) in ai_obj {
-                            obj.insert(key.clone(), value.clone());
-                        }
-                    }
-                }
-                
-                asset.enriched_metadata = current_metadata;
-                asset.status = AssetStatus::Processed;
-                asset.processing_completed_at = Some(Utc::now());
-                
-                AssetRepository::update(&db_pool_clone, &asset).await.ok();
-            }
-            
-            // Update job status to completed
-            WorkflowRepository::update_job_status(
-                &db_pool_clone,
-                job_id,
-                crate::models::workflow::JobStatus::Completed,
-                Some(100),
-                None,
-            ).await.ok();
-        } else {
-            // Update job status to failed
-            WorkflowRepository::update_job_status(
-                &db_pool_clone,
-                job_id,
-                crate::models::workflow::JobStatus::Failed,
-                None,
-                Some("AI processing failed".to_string()),
-            ).await.ok();
-        }
-    });
+    // Picked up by the durable worker pool (services::job_worker)
+    WorkflowRepository::create_job(db_pool, &job).await?;
 
     Ok(Json(json!({
         "asset_uuid": asset_uuid,
-        "status": "PROCESSING",
-        "message": "Upload successful. AI processing started.",
+        "status": "QUEUED",
+        "message": "Upload successful. Queued for AI processing.",
         "file_saved": true,
         "metadata_saved": true,
         "workflow": workflow_name,
@@ -549,6 +397,353 @@ pub async fn upload_media(
     })))
 }
 
+/// File that has been streamed into storage
+pub(crate) struct StoredUpload {
+    pub(crate) uri: String,
+    pub(crate) file_hash: String,
+    pub(crate) file_size: i64,
+    pub(crate) content: DetectedContent,
+}
+
+/// An upload's filename, refused unless it is a single path component: it becomes part of the
+/// storage key, and a separator or `..` would let it escape the storage root
+pub(crate) fn upload_filename(raw: &str, field: &'static str) -> Result<String, ApiError> {
+    let name = raw.trim();
+    if name.is_empty()
+        || name == "."
+        || name == ".."
+        || name.contains(['/', '\\', '\0'])
+    {
+        tracing::warn!("Rejected upload filename {:?}", raw);
+        return Err(ApiError::invalid_field(field, "must be a non-empty name without path separators"));
+    }
+    Ok(name.to_string())
+}
+
+/// Identify an upload from its leading bytes, rejecting disallowed content with 415
+pub(crate) fn sniff_upload(head: &[u8], filename: &str) -> Result<DetectedContent, ApiError> {
+    let content = content_sniffing::sniff(head, filename).ok_or_else(|| {
+        tracing::warn!("Rejected upload {}: content is not an allowed media or document type", filename);
+        ApiError::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "UNSUPPORTED_MEDIA_TYPE",
+            "File content is not an allowed media or document type",
+        )
+    })?;
+    if content.extension_mismatch {
+        tracing::warn!("Upload {} has the extension of a different format than its {} content", filename, content.format);
+    }
+    Ok(content)
+}
+
+/// Stream a multipart file field into storage chunk by chunk, hashing it on the way (I-FR-02).
+/// Nothing is buffered beyond the backend's own part size; oversized files are rejected with 413.
+async fn store_upload_field(
+    field: &mut Field,
+    storage: &StorageRegistry,
+    category: &str,
+    asset_uuid: Uuid,
+    filename: &str,
+) -> Result<StoredUpload, ApiError> {
+    // Prefix with the asset id so uploads that share a filename never overwrite each other
+    let mut writer = storage.writer(category, &format!("{}-{}", asset_uuid, filename)).await
+        .map_err(ApiError::storage)?;
+
+    let max_bytes = storage.max_upload_bytes();
+    let mut hasher = hash::StreamingHasher::new();
+    let mut size: u64 = 0;
+    let mut head = Vec::with_capacity(content_sniffing::SNIFF_BYTES);
+    let mut content = None;
+
+    loop {
+        let chunk = match field.chunk().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(e) if e.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+                tracing::warn!("Upload {} exceeds the request body limit", filename);
+                writer.abort().await.ok();
+                return Err(payload_too_large(max_bytes));
+            }
+            Err(e) => {
+                tracing::error!("Error reading file bytes: {:?}", e);
+                writer.abort().await.ok();
+                return Err(ApiError::bad_request("File upload was interrupted or malformed"));
+            }
+        };
+
+        size += chunk.len() as u64;
+        if size > max_bytes {
+            tracing::warn!("Upload {} exceeds the {} byte limit", filename, max_bytes);
+            writer.abort().await.ok();
+            return Err(payload_too_large(max_bytes));
+        }
+
+        // Reject disallowed content as soon as enough of it has arrived
+        if content.is_none() {
+            let wanted = content_sniffing::SNIFF_BYTES - head.len();
+            head.extend_from_slice(&chunk[..chunk.len().min(wanted)]);
+            if head.len() == content_sniffing::SNIFF_BYTES {
+                match sniff_upload(&head, filename) {
+                    Ok(detected) => content = Some(detected),
+                    Err(error) => {
+                        writer.abort().await.ok();
+                        return Err(error);
+                    }
+                }
+            }
+        }
+
+        hasher.update(&chunk);
+        if let Err(e) = writer.write(&chunk).await {
+            writer.abort().await.ok();
+            return Err(ApiError::storage(e));
+        }
+    }
+
+    // Files shorter than the sniff window
+    let content = match content {
+        Some(content) => content,
+        None => match sniff_upload(&head, filename) {
+            Ok(content) => content,
+            Err(error) => {
+                writer.abort().await.ok();
+                return Err(error);
+            }
+        },
+    };
+
+    let uri = writer.finish().await.map_err(ApiError::storage)?;
+
+    Ok(StoredUpload {
+        uri,
+        file_hash: hasher.finalize(),
+        file_size: size as i64,
+        content,
+    })
+}
+
+/// Read a non-file form field as UTF-8 text, refusing fields longer than `MAX_TEXT_FIELD_BYTES`
+async fn read_text_field(field: &mut Field, name: &'static str) -> Result<String, ApiError> {
+    let mut data = Vec::new();
+    loop {
+        match field.chunk().await {
+            Ok(Some(chunk)) => {
+                if data.len() + chunk.len() > MAX_TEXT_FIELD_BYTES {
+                    return Err(ApiError::invalid_field(
+                        name,
+                        format!("must be at most {} bytes", MAX_TEXT_FIELD_BYTES),
+                    ));
+                }
+                data.extend_from_slice(&chunk);
+            }
+            Ok(None) => break,
+            Err(e) => {
+                tracing::warn!("Error reading {} field: {:?}", name, e);
+                return Err(ApiError::invalid_field(name, "could not be read"));
+            }
+        }
+    }
+    String::from_utf8(data).map_err(|_| ApiError::invalid_field(name, "must be UTF-8 text"))
+}
+
+/// 413 for uploads over the storage limit
+pub(crate) fn payload_too_large(max_bytes: u64) -> ApiError {
+    ApiError::new(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        "PAYLOAD_TOO_LARGE",
+        format!("File exceeds the upload limit of {} bytes", max_bytes),
+    )
+}
+
+/// Remove an uploaded object that will not become an asset
+pub(crate) async fn discard_upload(storage: &StorageRegistry, uri: &str) {
+    if let Err(e) = storage.delete(uri).await {
+        tracing::warn!("Failed to remove discarded upload {}: {:?}", uri, e);
+    }
+}
+
+// Largest accepted metadata, tags or other text field in an upload form
+const MAX_TEXT_FIELD_BYTES: usize = 64 * 1024;
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListMediaQuery {
+    /// Comma-separated: Video, Image, Audio, Text
+    pub asset_type: Option<String>,
+    /// Comma-separated source systems, e.g. UserUpload,Brightcove
+    pub source_system: Option<String>,
+    /// Comma-separated: Staged, Queued, Processing, Processed, Failed, Archived
+    pub status: Option<String>,
+    pub uploaded_by: Option<Uuid>,
+    /// RFC 3339; inclusive
+    pub created_after: Option<DateTime<Utc>>,
+    /// RFC 3339; exclusive
+    pub created_before: Option<DateTime<Utc>>,
+    /// RFC 3339; inclusive. Never-edited assets have no updated_at and are excluded
+    pub updated_after: Option<DateTime<Utc>>,
+    /// RFC 3339; exclusive
+    pub updated_before: Option<DateTime<Utc>>,
+    /// Operational tags as comma-separated key:value pairs, all of which must match
+    pub tags: Option<String>,
+    /// Top-level enriched metadata fields as comma-separated key:value pairs, all of which must match
+    pub metadata: Option<String>,
+    /// created_at (default), updated_at, asset_name or file_size
+    pub sort: Option<String>,
+    /// asc or desc (default)
+    pub order: Option<String>,
+    /// 1-200, default 50
+    pub limit: Option<i64>,
+    /// next_cursor from the previous page; requires the same sort and order
+    pub cursor: Option<String>,
+}
+
+/// List media assets
+///
+/// Filters combine with AND; comma-separated values of one filter combine with OR.
+/// Pages are cursor-based: pass `next_cursor` back as `cursor` until it is null.
+#[utoipa::path(
+    get,
+    path = "/api/media",
+    tag = "Media",
+    params(ListMediaQuery),
+    responses(
+        (status = 200, description = "One page of matching assets and the cursor of the next page"),
+        (status = 400, description = "Unknown filter value, sort or malformed cursor", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_media(
+    State(db_pool): State<DbPool>,
+    Query(query): Query<ListMediaQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let filter = AssetFilter {
+        asset_types: parse_list(query.asset_type.as_deref(), "asset_type", AssetType::from_name)?,
+        source_systems: parse_list(query.source_system.as_deref(), "source_system", SourceSystem::from_name)?,
+        statuses: parse_list(query.status.as_deref(), "status", AssetStatus::from_name)?,
+        uploaded_by: query.uploaded_by,
+        created_after: query.created_after,
+        created_before: query.created_before,
+        updated_after: query.updated_after,
+        updated_before: query.updated_before,
+        operational_tags: parse_pairs(query.tags.as_deref(), "tags")?,
+        metadata: parse_pairs(query.metadata.as_deref(), "metadata")?,
+    };
+    let sort = match query.sort.as_deref() {
+        None => AssetSortField::CreatedAt,
+        Some(name) => AssetSortField::from_name(name).ok_or_else(|| {
+            ApiError::invalid_field("sort", "must be created_at, updated_at, asset_name or file_size")
+        })?,
+    };
+    let descending = match query.order.as_deref() {
+        None | Some("desc") => true,
+        Some("asc") => false,
+        Some(_) => return Err(ApiError::invalid_field("order", "must be 'asc' or 'desc'")),
+    };
+    let cursor = query.cursor.as_deref()
+        .map(|cursor| decode_cursor(cursor, sort, descending)
+            .ok_or_else(|| ApiError::invalid_field("cursor", "is malformed or was issued for a different sort")))
+        .transpose()?;
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+    // One extra row tells whether another page follows
+    let mut assets = AssetRepository::list(&db_pool, &filter, sort, descending, cursor.as_ref(), limit + 1).await?;
+    let next_cursor = if assets.len() as i64 > limit {
+        assets.truncate(limit as usize);
+        assets.last().map(|last| encode_cursor(
+            &AssetCursor { value: sort.value_of(last), uuid: last.uuid },
+            sort,
+            descending,
+        ))
+    } else {
+        None
+    };
+
+    Ok(Json(json!({
+        "assets": assets.iter().map(|asset| json!({
+            "uuid": asset.uuid,
+            "asset_type": format!("{:?}", asset.asset_type),
+            "asset_name": asset.asset_name,
+            "source_system": format!("{:?}", asset.source_system),
+            "status": format!("{:?}", asset.status),
+            "file_size": asset.file_size,
+            "mime_type": asset.mime_type,
+            "operational_tags": asset.operational_tags,
+            "enriched_metadata": asset.enriched_metadata,
+            "uploaded_by": asset.uploaded_by,
+            "created_at": asset.created_at,
+            "updated_at": asset.updated_at,
+            "download_url": format!("/api/media/{}/download", asset.uuid),
+        })).collect::<Vec<_>>(),
+        "limit": limit,
+        "next_cursor": next_cursor,
+    })))
+}
+
+fn parse_list<T>(raw: Option<&str>, field: &str, parse: fn(&str) -> Option<T>) -> Result<Vec<T>, ApiError> {
+    raw.map(|raw| raw.split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| parse(value)
+                .ok_or_else(|| ApiError::invalid_field(field, format!("'{}' is not a known value", value))))
+            .collect())
+        .unwrap_or(Ok(vec![]))
+}
+
+fn parse_pairs(raw: Option<&str>, field: &str) -> Result<Vec<(String, String)>, ApiError> {
+    raw.map(|raw| raw.split(',')
+            .filter(|pair| !pair.trim().is_empty())
+            .map(|pair| match pair.split_once(':') {
+                Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.trim().to_string())),
+                _ => Err(ApiError::invalid_field(field, "must be comma-separated key:value pairs")),
+            })
+            .collect())
+        .unwrap_or(Ok(vec![]))
+}
+
+// Cursors are hex-encoded JSON carrying the sort they were issued for, so a changed
+// sort or order is rejected rather than silently skipping or repeating rows
+fn encode_cursor(cursor: &AssetCursor, sort: AssetSortField, descending: bool) -> String {
+    let value = match &cursor.value {
+        AssetSortValue::Time(time) => json!(time),
+        AssetSortValue::Text(text) => json!(text),
+        AssetSortValue::Number(number) => json!(number),
+    };
+    let payload = json!({ "sort": sort.as_str(), "desc": descending, "value": value, "uuid": cursor.uuid });
+    payload.to_string().bytes().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_cursor(raw: &str, sort: AssetSortField, descending: bool) -> Option<AssetCursor> {
+    if !raw.len().is_multiple_of(2) || !raw.is_ascii() {
+        return None;
+    }
+    let bytes = (0..raw.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&raw[i..i + 2], 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    let payload: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+
+    if payload.get("sort")?.as_str()? != sort.as_str() || payload.get("desc")?.as_bool()? != descending {
+        return None;
+    }
+    let value = payload.get("value")?;
+    let value = match sort {
+        AssetSortField::CreatedAt | AssetSortField::UpdatedAt => {
+            AssetSortValue::Time(serde_json::from_value(value.clone()).ok()?)
+        }
+        AssetSortField::AssetName => AssetSortValue::Text(value.as_str()?.to_string()),
+        AssetSortField::FileSize => AssetSortValue::Number(value.as_i64()?),
+    };
+    let uuid = Uuid::parse_str(payload.get("uuid")?.as_str()?).ok()?;
+
+    Some(AssetCursor { value, uuid })
+}
+
 /// Get media asset information
 /// 
 /// Retrieves asset details by UUID
@@ -573,10 +768,9 @@ pub async fn upload_media(
 pub async fn get_media(
     State(db_pool): State<DbPool>,
     Path(asset_id): Path<Uuid>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let asset = AssetRepository::get_by_uuid(&db_pool, asset_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let asset = AssetRepository::get_by_uuid(&db_pool, asset_id).await?
+        .ok_or_else(|| ApiError::not_found("Asset", asset_id))?;
 
     Ok(Json(json!({
         "uuid": asset.uuid,
@@ -592,7 +786,10 @@ pub async fn get_media(
 
 /// Download/Stream media file (Video, Audio, Image, Text)
 /// 
-/// Returns the actual file content for playback/download
+/// Returns the actual file content for playback/download.
+/// Supports `Range`/`If-Range` (206) and `If-None-Match`/`If-Modified-Since` (304).
+/// Transcript search deep links append a `#t=start,end` media fragment; players seek there
+/// themselves and fetch the needed bytes with `Range` requests.
 #[utoipa::path(
     get,
     path = "/api/media/{asset_id}/download",
@@ -602,7 +799,10 @@ pub async fn get_media(
     ),
     responses(
         (status = 200, description = "File content", content_type = "video/mp4, audio/mpeg, image/png, application/pdf"),
+        (status = 206, description = "Requested byte range of the file"),
+        (status = 304, description = "Not modified since the cached copy"),
         (status = 404, description = "Asset not found", body = ErrorResponse),
+        (status = 416, description = "Requested range not satisfiable"),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     security(
@@ -612,99 +812,169 @@ pub async fn get_media(
 )]
 pub async fn download_media(
     State(db_pool): State<DbPool>,
+    State(storage): State<Arc<StorageRegistry>>,
     Path(asset_id): Path<Uuid>,
-) -> Result<Response, StatusCode> {
+    request_headers: HeaderMap,
+) -> Result<Response, ApiError> {
     // Get asset from database
-    let asset = AssetRepository::get_by_uuid(&db_pool, asset_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
-
-    // Read file from storage
-    let file_data = if std::env::var("USE_LOCAL_STORAGE").is_ok() {
-        // Read from local storage
-        let local_storage = LocalStorageService::new(
-            std::env::var("LOCAL_STORAGE_PATH").ok()
-        );
-        local_storage.read_file(&asset.file_path).await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-    } else {
-        // Read from S3
-        let aws_config = aws_config::load_from_env().await;
-        let s3_service = S3Service::new(&aws_config, "mediacorp-ai-api-uploads".to_string());
-        
-        // Extract key from S3 path (format: s3://bucket/key)
-        let key = asset.file_path
-            .strip_prefix("s3://")
-            .and_then(|s| s.splitn(2, '/').nth(1))
-            .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
-        
-        s3_service.download_file(key).await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    let asset = AssetRepository::get_by_uuid(&db_pool, asset_id).await?
+        .ok_or_else(|| ApiError::not_found("Asset", asset_id))?;
+
+    let stat = storage.stat(&asset.file_path).await.map_err(ApiError::storage)?;
+    let size = stat.size;
+
+    // Content hash identifies the bytes exactly, so it doubles as a strong validator
+    let etag = format!("\"{}\"", asset.file_hash);
+    let last_modified = stat.last_modified
+        .or(asset.updated_at)
+        .unwrap_or(asset.created_at)
+        .with_nanosecond(0)
+        .unwrap_or(asset.created_at);
+
+    let header_str = |name: axum::http::header::HeaderName| {
+        request_headers.get(name).and_then(|v| v.to_str().ok())
     };
 
-    // Determine content type based on asset type and format
-    let content_type = match asset.asset_type {
-        AssetType::Video => {
-            match asset.format.as_str() {
-                "MP4" => "video/mp4",
-                "AVI" => "video/x-msvideo",
-                "MOV" => "video/quicktime",
-                _ => "video/mp4",
-            }
-        }
-        AssetType::Audio => {
-            match asset.format.as_str() {
-                "MP3" => "audio/mpeg",
-                "WAV" => "audio/wav",
-                "M4A" => "audio/mp4",
-                _ => "audio/mpeg",
-            }
-        }
-        AssetType::Image => {
-            match asset.format.as_str() {
-                "PNG" => "image/png",
-                "JPG" | "JPEG" => "image/jpeg",
-                "GIF" => "image/gif",
-                _ => "image/png",
-            }
+    // Conditional GET: If-None-Match takes precedence over If-Modified-Since
+    let not_modified = match header_str(axum::http::header::IF_NONE_MATCH) {
+        Some(tags) => http_range::etag_matches(tags, &etag),
+        None => header_str(axum::http::header::IF_MODIFIED_SINCE)
+            .and_then(http_range::parse_http_date)
+            .map(|since| last_modified <= since)
+            .unwrap_or(false),
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(axum::http::header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    headers.insert(
+        axum::http::header::ETAG,
+        HeaderValue::from_str(&etag).map_err(ApiError::internal)?,
+    );
+    headers.insert(
+        axum::http::header::LAST_MODIFIED,
+        HeaderValue::from_str(&http_range::http_date(last_modified)).map_err(ApiError::internal)?,
+    );
+
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, headers).into_response());
+    }
+
+    // If-Range: only honour Range when the client's copy is still current
+    let range_allowed = match header_str(axum::http::header::IF_RANGE) {
+        Some(validator) if validator.trim_start().starts_with('"') || validator.trim_start().starts_with("W/") => {
+            validator.trim() == etag
         }
-        AssetType::Text => {
-            match asset.format.as_str() {
-                "PDF" => "application/pdf",
-                "TXT" => "text/plain",
-                "HTML" => "text/html",
-                "JSON" => "application/json",
-                "DOC" | "DOCX" => "application/msword",
-                _ => "application/octet-stream",
-            }
+        Some(validator) => http_range::parse_http_date(validator)
+            .map(|date| date == last_modified)
+            .unwrap_or(false),
+        None => true,
+    };
+    let range = match header_str(axum::http::header::RANGE) {
+        Some(value) if range_allowed => http_range::parse_range_header(value, size),
+        _ => RangeRequest::Full,
+    };
+
+    let (status, range) = match range {
+        RangeRequest::Full => (StatusCode::OK, None),
+        RangeRequest::Partial(range) => (StatusCode::PARTIAL_CONTENT, Some(range)),
+        RangeRequest::Unsatisfiable => {
+            headers.insert(
+                axum::http::header::CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes */{}", size)).map_err(ApiError::internal)?,
+            );
+            return Ok((StatusCode::RANGE_NOT_SATISFIABLE, headers).into_response());
         }
     };
 
+    // Stream from whichever backend holds the file
+    let reader = storage.stream(&asset.file_path, range).await.map_err(ApiError::storage)?;
+
+    // Content type was sniffed at ingest; older assets are sniffed now
+    let content_type = match asset.mime_type.clone() {
+        Some(mime_type) => mime_type,
+        None => content_sniffing::sniff_stored(&storage, &asset.file_path, &asset.asset_name).await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to sniff content type of {}: {:?}", asset.file_path, e);
+                None
+            })
+            .map(|content| content.mime_type)
+            .unwrap_or("application/octet-stream")
+            .to_string(),
+    };
+
     // Set headers for file download/streaming
-    let mut headers = HeaderMap::new();
     headers.insert(
         axum::http::header::CONTENT_TYPE,
-        HeaderValue::from_str(content_type).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+        HeaderValue::from_str(&content_type).map_err(ApiError::internal)?,
     );
+    // Markup that a browser would run as a page is never rendered inline from our origin
+    let disposition = if content_sniffing::is_active_content(&content_type) { "attachment" } else { "inline" };
     headers.insert(
         axum::http::header::CONTENT_DISPOSITION,
-        HeaderValue::from_str(&format!("inline; filename=\"{}\"", asset.asset_name))
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+        HeaderValue::from_str(&format!("{}; filename=\"{}\"", disposition, asset.asset_name)).map_err(ApiError::internal)?,
+    );
+    headers.insert(axum::http::header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    headers.insert(
+        axum::http::header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static("default-src 'none'; media-src 'self'; img-src 'self'; style-src 'unsafe-inline'; sandbox"),
     );
+    let content_length = range.map(|r| r.length()).unwrap_or(size);
     headers.insert(
         axum::http::header::CONTENT_LENGTH,
-        HeaderValue::from_str(&file_data.len().to_string())
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+        HeaderValue::from_str(&content_length.to_string()).map_err(ApiError::internal)?,
     );
-    
-    // For video/audio, add range support headers for streaming
-    if matches!(asset.asset_type, AssetType::Video | AssetType::Audio) {
+    if let Some(range) = range {
         headers.insert(
-            axum::http::header::ACCEPT_RANGES,
-            HeaderValue::from_static("bytes"),
+            axum::http::header::CONTENT_RANGE,
+            HeaderValue::from_str(&format!("bytes {}-{}/{}", range.start, range.end, size)).map_err(ApiError::internal)?,
         );
     }
 
-    // Return file as response
-    Ok((headers, Bytes::from(file_data)).into_response())
-}
\ No newline at end of file
+    // Return file as a streamed body
+    let body = Body::from_stream(ReaderStream::new(reader));
+    Ok((status, headers, body).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips_for_its_own_sort() {
+        let cursor = AssetCursor {
+            value: AssetSortValue::Time(Utc::now()),
+            uuid: Uuid::new_v4(),
+        };
+        let encoded = encode_cursor(&cursor, AssetSortField::UpdatedAt, false);
+
+        assert_eq!(decode_cursor(&encoded, AssetSortField::UpdatedAt, false), Some(cursor));
+        assert_eq!(decode_cursor(&encoded, AssetSortField::UpdatedAt, true), None);
+        assert_eq!(decode_cursor(&encoded, AssetSortField::CreatedAt, false), None);
+    }
+
+    #[test]
+    fn malformed_cursor_is_rejected() {
+        assert_eq!(decode_cursor("zz", AssetSortField::CreatedAt, true), None);
+        assert_eq!(decode_cursor("7b7d", AssetSortField::CreatedAt, true), None);
+    }
+
+    #[test]
+    fn upload_filenames_stay_inside_the_storage_root() {
+        assert_eq!(upload_filename(" clip.mp4 ", "file").unwrap(), "clip.mp4");
+        assert_eq!(upload_filename("report..final.pdf", "file").unwrap(), "report..final.pdf");
+        for name in ["", "  ", ".", "..", "../../../../x", "a/b.mp4", "..\\..\\x", "c:\\x", "a\0b"] {
+            assert!(upload_filename(name, "file").is_err(), "{:?} should be refused", name);
+        }
+    }
+
+    #[test]
+    fn filter_values_are_validated() {
+        assert_eq!(parse_list(Some("video, AUDIO"), "asset_type", AssetType::from_name).unwrap().len(), 2);
+        assert!(parse_list(Some("Podcast"), "asset_type", AssetType::from_name).is_err());
+        assert_eq!(
+            parse_pairs(Some("region:eu,priority:high"), "tags").unwrap(),
+            vec![("region".to_string(), "eu".to_string()), ("priority".to_string(), "high".to_string())],
+        );
+        assert!(parse_pairs(Some("region"), "tags").is_err());
+    }
+}
@@ -5,14 +5,16 @@
 
 use axum::{
     extract::{Path, State},
-    http::{HeaderMap, StatusCode},
+    http::HeaderMap,
     response::Json,
 };
 use uuid::Uuid;
+use crate::api::error::ApiError;
 use crate::db::DbPool;
-use crate::db::repositories::{asset_repository::AssetRepository, graph_repository::GraphRepository};
+use crate::db::repositories::asset_repository::AssetRepository;
 use crate::models::metadata::MetadataUpdate;
-use crate::middleware::auth::Claims;
+use crate::middleware::auth::AuthUser;
+use crate::services::graph_service::GraphService;
 use serde_json::json;
 use chrono::Utc;
 
@@ -40,10 +42,9 @@ use chrono::Utc;
 pub async fn get_metadata(
     State(db_pool): State<DbPool>,
     Path(asset_id): Path<Uuid>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let asset = AssetRepository::get_by_uuid(&db_pool, asset_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let asset = AssetRepository::get_by_uuid(&db_pool, asset_id).await?
+        .ok_or_else(|| ApiError::not_found("Asset", asset_id))?;
 
     Ok(Json(json!({
         "asset_uuid": asset.uuid,
@@ -80,14 +81,14 @@ pub async fn get_metadata(
 // I-FR-27: Update metadata with conflict detection
 pub async fn update_metadata(
     State(db_pool): State<DbPool>,
+    auth: AuthUser,
     Path(asset_id): Path<Uuid>,
     headers: HeaderMap,
     Json(update): Json<MetadataUpdate>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     // Get current asset
-    let current_asset = AssetRepository::get_by_uuid(&db_pool, asset_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let current_asset = AssetRepository::get_by_uuid(&db_pool, asset_id).await?
+        .ok_or_else(|| ApiError::not_found("Asset", asset_id))?;
 
     // I-FR-19: Check for conflicts using version_id
     let provided_version_id = headers
@@ -100,7 +101,7 @@ pub async fn update_metadata(
             &db_pool,
             asset_id,
             version_id,
-        ).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        ).await?;
 
         if has_conflict {
             return Ok(Json(json!({
@@ -113,8 +114,7 @@ pub async fn update_metadata(
         }
     }
 
-    // Get user from auth (TODO: Extract from middleware)
-    let user_id = Uuid::new_v4(); // Placeholder
+    let user_id = auth.id;
 
     // Create new version (I-FR-18)
     let new_version = current_asset.version + 1;
@@ -128,7 +128,7 @@ pub async fn update_metadata(
         current_asset.version_id,
         current_asset.enriched_metadata.clone(),
         user_id,
-    ).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    ).await?;
 
     // Merge metadata updates
     let mut updated_metadata = current_asset.enriched_metadata.clone();
@@ -153,13 +153,10 @@ pub async fn update_metadata(
         new_version,
         new_version_id,
         user_id,
-    ).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    ).await?;
 
-    // I-FR-20: Update graph database
-    let keywords = update.tags.unwrap_or_default();
-    let topics = vec![]; // Extract from metadata if needed
-    GraphRepository::index_asset(&db_pool, asset_id, &keywords, &topics).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // I-FR-20: Update graph database and the asset's relationships
+    GraphService::sync_metadata_update(&db_pool, asset_id).await?;
 
     Ok(Json(json!({
         "status": "success",
@@ -195,22 +192,22 @@ pub async fn update_metadata(
 // I-FR-19: Resolve metadata conflicts
 pub async fn resolve_conflict(
     State(db_pool): State<DbPool>,
+    auth: AuthUser,
     Path(asset_id): Path<Uuid>,
     Json(payload): Json<serde_json::Value>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let resolution_strategy = payload.get("resolution_strategy")
         .and_then(|s| s.as_str())
         .unwrap_or("merge");
 
-    let current_asset = AssetRepository::get_by_uuid(&db_pool, asset_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let current_asset = AssetRepository::get_by_uuid(&db_pool, asset_id).await?
+        .ok_or_else(|| ApiError::not_found("Asset", asset_id))?;
 
     let resolved_metadata = payload.get("resolved_metadata")
         .and_then(|m| m.as_object())
-        .ok_or(StatusCode::BAD_REQUEST)?;
+        .ok_or_else(|| ApiError::invalid_field("resolved_metadata", "must be an object"))?;
 
-    let user_id = Uuid::new_v4(); // TODO: Get from auth
+    let user_id = auth.id;
     let new_version = current_asset.version + 1;
     let new_version_id = Uuid::new_v4();
 
@@ -222,7 +219,7 @@ pub async fn resolve_conflict(
         current_asset.version_id,
         current_asset.enriched_metadata.clone(),
         user_id,
-    ).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    ).await?;
 
     // Apply resolved metadata
     let resolved_json: serde_json::Value = serde_json::from_value(
@@ -236,7 +233,10 @@ pub async fn resolve_conflict(
         new_version,
         new_version_id,
         user_id,
-    ).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    ).await?;
+
+    // I-FR-20: Resolved metadata may change keywords and topics
+    GraphService::sync_metadata_update(&db_pool, asset_id).await?;
 
     Ok(Json(json!({
         "status": "success",
@@ -1,8 +1,11 @@
 // API handlers (request/response logic)
 
 pub mod media;
+pub mod uploads;
 pub mod metadata;
 pub mod workflow;
 pub mod graph;
 pub mod admin;
+pub mod users;
 pub mod auth;
+pub mod saved_searches;
@@ -0,0 +1,314 @@
+// Saved search handlers
+// I-FR-22: Named searches with new-match tracking
+//
+// GET    /api/saved-searches               the caller's saved searches with new-match counts
+// POST   /api/saved-searches               save a search
+// GET    /api/saved-searches/:id           one saved search
+// PUT    /api/saved-searches/:id           replace its name, query, filters and webhook
+// DELETE /api/saved-searches/:id           delete it and its match history
+// GET    /api/saved-searches/:id/matches   assets that matched since the last check
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+use crate::api::error::ApiError;
+use crate::api::handlers::graph::parse_filter;
+use crate::db::DbPool;
+use crate::db::repositories::saved_search_repository::SavedSearchRepository;
+use crate::middleware::auth::AuthUser;
+use crate::models::saved_search::SavedSearch;
+use crate::utils::public_url;
+
+const MAX_NAME_LENGTH: usize = 255;
+const DEFAULT_MATCH_PAGE_SIZE: i64 = 100;
+const MAX_MATCH_PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct SavedSearchRequest {
+    pub name: String,
+    /// Same syntax as /api/graph/search
+    pub query: String,
+    /// Same shape as the search request's `filters`
+    #[schema(value_type = Option<Object>)]
+    pub filters: Option<serde_json::Value>,
+    /// http(s) URL on a public host, POSTed to when an asset newly matches
+    pub webhook_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MatchesQuery {
+    /// RFC 3339. Omit to get matches since the last check and advance it past the returned matches
+    pub since: Option<DateTime<Utc>>,
+    /// 1-500, default 100
+    pub limit: Option<i64>,
+}
+
+/// List your saved searches
+#[utoipa::path(
+    get,
+    path = "/api/saved-searches",
+    tag = "Graph",
+    responses(
+        (status = 200, description = "Saved searches by name, each with `new_matches` since its last check")
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_saved_searches(
+    State(db_pool): State<DbPool>,
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let searches = SavedSearchRepository::list_for_user(&db_pool, auth.id).await?;
+
+    Ok(Json(json!({
+        "saved_searches": searches.iter().map(|(search, new_matches)| {
+            let mut summary = saved_search_summary(search);
+            summary["new_matches"] = json!(new_matches);
+            summary
+        }).collect::<Vec<_>>(),
+    })))
+}
+
+/// Save a search
+///
+/// I-FR-22: The search is checked against every asset that finishes processing from now on.
+#[utoipa::path(
+    post,
+    path = "/api/saved-searches",
+    tag = "Graph",
+    request_body = SavedSearchRequest,
+    responses(
+        (status = 201, description = "Saved search created"),
+        (status = 400, description = "Invalid name, query, filters or webhook URL", body = ErrorResponse),
+        (status = 409, description = "You already have a saved search with this name", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_saved_search(
+    State(db_pool): State<DbPool>,
+    auth: AuthUser,
+    Json(payload): Json<SavedSearchRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let now = Utc::now();
+    let mut search = SavedSearch {
+        id: Uuid::new_v4(),
+        user_id: auth.id,
+        name: String::new(),
+        query: String::new(),
+        filters: json!({}),
+        webhook_url: None,
+        last_checked_at: now,
+        created_at: now,
+        updated_at: None,
+    };
+    apply_request(&mut search, payload).await?;
+
+    SavedSearchRepository::create(&db_pool, &search).await?;
+    tracing::info!("{} saved search {} ({})", auth.email, search.id, search.name);
+
+    Ok((StatusCode::CREATED, Json(saved_search_summary(&search))))
+}
+
+/// Get one of your saved searches
+#[utoipa::path(
+    get,
+    path = "/api/saved-searches/{id}",
+    tag = "Graph",
+    params(("id" = Uuid, Path, description = "Saved search ID")),
+    responses(
+        (status = 200, description = "Saved search"),
+        (status = 404, description = "No such saved search of yours", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_saved_search(
+    State(db_pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let search = find_saved_search(&db_pool, id, &auth).await?;
+    Ok(Json(saved_search_summary(&search)))
+}
+
+/// Replace a saved search
+///
+/// Name, query, filters and webhook are all replaced; omitted filters and webhook are cleared.
+/// Matches recorded so far are kept.
+#[utoipa::path(
+    put,
+    path = "/api/saved-searches/{id}",
+    tag = "Graph",
+    params(("id" = Uuid, Path, description = "Saved search ID")),
+    request_body = SavedSearchRequest,
+    responses(
+        (status = 200, description = "Saved search updated"),
+        (status = 400, description = "Invalid name, query, filters or webhook URL", body = ErrorResponse),
+        (status = 404, description = "No such saved search of yours", body = ErrorResponse),
+        (status = 409, description = "You already have a saved search with this name", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_saved_search(
+    State(db_pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<SavedSearchRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let mut search = find_saved_search(&db_pool, id, &auth).await?;
+    apply_request(&mut search, payload).await?;
+
+    if !SavedSearchRepository::update(&db_pool, &search).await? {
+        return Err(ApiError::not_found("Saved search", id));
+    }
+    search.updated_at = Some(Utc::now());
+
+    Ok(Json(saved_search_summary(&search)))
+}
+
+/// Delete a saved search
+#[utoipa::path(
+    delete,
+    path = "/api/saved-searches/{id}",
+    tag = "Graph",
+    params(("id" = Uuid, Path, description = "Saved search ID")),
+    responses(
+        (status = 204, description = "Saved search and its match history deleted"),
+        (status = 404, description = "No such saved search of yours", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_saved_search(
+    State(db_pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    if !SavedSearchRepository::delete(&db_pool, id, auth.id).await? {
+        return Err(ApiError::not_found("Saved search", id));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// What's new for a saved search
+///
+/// Without `since`, returns matches recorded since the last check and moves the last check
+/// past them, so calling again returns only newer matches (or the rest, if `has_more`).
+/// With `since`, matches after that time are returned and the last check is left alone.
+#[utoipa::path(
+    get,
+    path = "/api/saved-searches/{id}/matches",
+    tag = "Graph",
+    params(
+        ("id" = Uuid, Path, description = "Saved search ID"),
+        MatchesQuery
+    ),
+    responses(
+        (status = 200, description = "Matching assets, oldest match first"),
+        (status = 404, description = "No such saved search of yours", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_saved_search_matches(
+    State(db_pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<MatchesQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let search = find_saved_search(&db_pool, id, &auth).await?;
+    let since = query.since.unwrap_or(search.last_checked_at);
+    let limit = query.limit.unwrap_or(DEFAULT_MATCH_PAGE_SIZE).clamp(1, MAX_MATCH_PAGE_SIZE);
+
+    let mut matches = SavedSearchRepository::matches_since(&db_pool, id, since, limit + 1).await?;
+    let has_more = matches.len() as i64 > limit;
+    matches.truncate(limit as usize);
+
+    if query.since.is_none() {
+        if let Some(last) = matches.last() {
+            SavedSearchRepository::mark_checked(&db_pool, id, last.matched_at).await?;
+        }
+    }
+
+    Ok(Json(json!({
+        "saved_search_id": id,
+        "since": since,
+        "matches": matches.iter().map(|m| json!({
+            "asset_uuid": m.asset_uuid,
+            "asset_name": m.asset_name,
+            "title": m.title,
+            "matched_at": m.matched_at,
+            "url": format!("/api/media/{}", m.asset_uuid),
+        })).collect::<Vec<_>>(),
+        "has_more": has_more,
+    })))
+}
+
+async fn find_saved_search(db_pool: &DbPool, id: Uuid, auth: &AuthUser) -> Result<SavedSearch, ApiError> {
+    SavedSearchRepository::get(db_pool, id, auth.id).await?
+        .ok_or_else(|| ApiError::not_found("Saved search", id))
+}
+
+// Validate a create/replace request onto `search`
+async fn apply_request(search: &mut SavedSearch, payload: SavedSearchRequest) -> Result<(), ApiError> {
+    let name = payload.name.trim();
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        return Err(ApiError::invalid_field("name", format!("must be 1 to {} characters", MAX_NAME_LENGTH)));
+    }
+    let query = payload.query.trim();
+    if query.is_empty() {
+        return Err(ApiError::invalid_field("query", "is required"));
+    }
+    let filter = parse_filter(payload.filters.as_ref())?;
+    // Webhooks may only point at public hosts; checked again whenever one is sent
+    let webhook_url = match payload.webhook_url.as_deref().map(str::trim).filter(|url| !url.is_empty()) {
+        Some(url) => match public_url::resolve_public(url).await {
+            Ok(target) => Some(target.url.to_string()),
+            Err(e) => {
+                tracing::info!("Rejected webhook URL {}: {}", url, e);
+                return Err(ApiError::invalid_field("webhook_url", "must be an http or https URL on a public host"));
+            }
+        },
+        None => None,
+    };
+
+    search.name = name.to_string();
+    search.query = query.to_string();
+    search.filters = serde_json::to_value(&filter).map_err(ApiError::internal)?;
+    search.webhook_url = webhook_url;
+    Ok(())
+}
+
+fn saved_search_summary(search: &SavedSearch) -> serde_json::Value {
+    json!({
+        "id": search.id,
+        "name": search.name,
+        "query": search.query,
+        "filters": search.filters,
+        "webhook_url": search.webhook_url,
+        "last_checked_at": search.last_checked_at,
+        "created_at": search.created_at,
+        "updated_at": search.updated_at,
+    })
+}
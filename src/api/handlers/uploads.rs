@@ -0,0 +1,699 @@
+// Resumable upload handlers (tus-style)
+// I-FR-31: Media upload and ingestion for large files
+//
+// POST   /api/media/uploads                     create a session
+// PATCH  /api/media/uploads/:upload_id          append bytes at Upload-Offset
+// HEAD   /api/media/uploads/:upload_id          current Upload-Offset
+// POST   /api/media/uploads/:upload_id/complete store and register the asset
+// DELETE /api/media/uploads/:upload_id          abandon the session
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use uuid::Uuid;
+use crate::api::error::ApiError;
+use crate::api::handlers::media::{
+    payload_too_large, register_user_upload, sniff_upload, upload_filename, upload_metadata, StoredUpload,
+};
+use crate::db::DbPool;
+use crate::db::advisory_lock::AdvisoryLock;
+use crate::db::repositories::upload_repository::UploadRepository;
+use crate::middleware::auth::AuthUser;
+use crate::models::upload::UploadSession;
+use crate::services::content_sniffing;
+use crate::services::storage::StorageRegistry;
+use crate::services::upload_sessions::{ChunkFile, UploadSessionService};
+use crate::utils::hash;
+
+const UPLOAD_OFFSET: &str = "Upload-Offset";
+const UPLOAD_LENGTH: &str = "Upload-Length";
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct CreateUploadRequest {
+    pub filename: String,
+    /// Total size of the file in bytes
+    pub upload_length: i64,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Comma-separated tags
+    pub tags: Option<String>,
+    pub category: Option<String>,
+}
+
+/// Create a resumable upload session
+///
+/// I-FR-31: Large media uploads that survive dropped connections
+#[utoipa::path(
+    post,
+    path = "/api/media/uploads",
+    tag = "Media",
+    request_body = CreateUploadRequest,
+    responses(
+        (status = 201, description = "Upload session created"),
+        (status = 400, description = "Invalid filename or length", body = ErrorResponse),
+        (status = 413, description = "File exceeds the maximum upload size", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_upload(
+    State(db_pool): State<DbPool>,
+    State(storage): State<Arc<StorageRegistry>>,
+    State(sessions): State<Arc<UploadSessionService>>,
+    auth: AuthUser,
+    Json(request): Json<CreateUploadRequest>,
+) -> Result<Response, ApiError> {
+    let filename = upload_filename(&request.filename, "filename")?;
+    if request.upload_length <= 0 {
+        return Err(ApiError::invalid_field("upload_length", "must be greater than zero"));
+    }
+    if request.upload_length as u64 > storage.max_upload_bytes() {
+        return Err(payload_too_large(storage.max_upload_bytes()));
+    }
+
+    let upload_id = Uuid::new_v4();
+    let staging_path = sessions.staging_path(upload_id);
+    tokio::fs::File::create(&staging_path).await.map_err(ApiError::storage)?;
+
+    let now = chrono::Utc::now();
+    let session = UploadSession {
+        upload_id,
+        filename,
+        upload_length: request.upload_length,
+        upload_offset: 0,
+        metadata: upload_metadata(request.title, request.description, request.tags, request.category),
+        staging_path: staging_path.to_string_lossy().to_string(),
+        created_by: Some(auth.id.to_string()),
+        created_at: now,
+        updated_at: now,
+        expires_at: sessions.next_expiry(),
+    };
+
+    if let Err(e) = UploadRepository::create(&db_pool, &session).await {
+        tokio::fs::remove_file(&staging_path).await.ok();
+        return Err(e.into());
+    }
+
+    let upload_url = format!("/api/media/uploads/{}", upload_id);
+    let mut headers = offset_headers(&session)?;
+    headers.insert(
+        axum::http::header::LOCATION,
+        HeaderValue::from_str(&upload_url).map_err(ApiError::internal)?,
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        headers,
+        Json(json!({
+            "upload_id": upload_id,
+            "upload_offset": 0,
+            "upload_length": session.upload_length,
+            "expires_at": session.expires_at,
+            "upload_url": upload_url,
+        })),
+    ).into_response())
+}
+
+/// Get the current offset of a resumable upload
+#[utoipa::path(
+    head,
+    path = "/api/media/uploads/{upload_id}",
+    tag = "Media",
+    params(
+        ("upload_id" = Uuid, Path, description = "Upload session ID")
+    ),
+    responses(
+        (status = 200, description = "Offset in the Upload-Offset header"),
+        (status = 404, description = "Upload not found or expired", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_upload_offset(
+    State(db_pool): State<DbPool>,
+    auth: AuthUser,
+    Path(upload_id): Path<Uuid>,
+) -> Result<Response, ApiError> {
+    let session = load_session(&db_pool, upload_id, &auth).await?;
+    let mut headers = offset_headers(&session)?;
+    headers.insert(axum::http::header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    Ok((StatusCode::OK, headers).into_response())
+}
+
+/// Append a chunk to a resumable upload
+///
+/// The `Upload-Offset` header must equal the session's current offset. Bytes received
+/// before a dropped connection are kept, so the client resumes from the offset HEAD reports.
+#[utoipa::path(
+    patch,
+    path = "/api/media/uploads/{upload_id}",
+    tag = "Media",
+    params(
+        ("upload_id" = Uuid, Path, description = "Upload session ID")
+    ),
+    request_body(content = String, description = "Raw file bytes", content_type = "application/offset+octet-stream"),
+    responses(
+        (status = 204, description = "Chunk stored; new offset in Upload-Offset"),
+        (status = 404, description = "Upload not found or expired", body = ErrorResponse),
+        (status = 409, description = "Upload-Offset does not match the current offset", body = ErrorResponse),
+        (status = 413, description = "Chunk runs past the declared upload length", body = ErrorResponse),
+        (status = 423, description = "Another request is writing to this upload", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn append_chunk(
+    State(db_pool): State<DbPool>,
+    State(sessions): State<Arc<UploadSessionService>>,
+    auth: AuthUser,
+    Path(upload_id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, ApiError> {
+    let client_offset = headers.get(UPLOAD_OFFSET)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .ok_or_else(|| ApiError::invalid_field("Upload-Offset", "header must be a byte offset"))?;
+
+    let session = write_chunk(&db_pool, &sessions, upload_id, &auth, client_offset, body).await?;
+    Ok((StatusCode::NO_CONTENT, offset_headers(&session)?).into_response())
+}
+
+/// Receive the body into its own chunk file with no lock or connection held, so slow clients
+/// cannot exhaust the pool, then commit it if the session is still at the offset it was sent for
+async fn write_chunk(
+    db_pool: &DbPool,
+    sessions: &UploadSessionService,
+    upload_id: Uuid,
+    auth: &AuthUser,
+    client_offset: i64,
+    body: Body,
+) -> Result<UploadSession, ApiError> {
+    let mut session = load_session(db_pool, upload_id, auth).await?;
+    if client_offset != session.upload_offset {
+        return Err(offset_conflict(client_offset, session.upload_offset));
+    }
+
+    let chunk = sessions.chunk_file(upload_id);
+    let mut file = tokio::fs::File::create(chunk.path()).await.map_err(ApiError::storage)?;
+
+    let remaining = (session.upload_length - session.upload_offset) as u64;
+    let mut written: u64 = 0;
+    let mut outcome = Ok(());
+    let mut stream = body.into_data_stream();
+
+    while let Some(data) = stream.next().await {
+        let data = match data {
+            Ok(data) => data,
+            Err(e) => {
+                // Connection dropped: keep what arrived so the client can resume from it
+                tracing::warn!("Upload {} interrupted after {} bytes: {:?}", upload_id, written, e);
+                outcome = Err(ApiError::bad_request("Upload was interrupted; resume from the current offset"));
+                break;
+            }
+        };
+
+        if written + data.len() as u64 > remaining {
+            outcome = Err(ApiError::new(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "PAYLOAD_TOO_LARGE",
+                "Chunk runs past the declared upload length",
+            ));
+            break;
+        }
+        if let Err(e) = file.write_all(&data).await {
+            outcome = Err(ApiError::storage(e));
+            break;
+        }
+        written += data.len() as u64;
+    }
+
+    file.flush().await.map_err(ApiError::storage)?;
+    drop(file);
+
+    if written > 0 {
+        let committed = session.upload_offset + written as i64;
+        session.expires_at = sessions.next_expiry();
+        commit_chunk(db_pool, sessions, &session, &chunk, committed).await?;
+        session.upload_offset = committed;
+    }
+
+    outcome.map(|_| session)
+}
+
+/// Append a received chunk and advance the offset in one short transaction. The session lock is
+/// only held here; a chunk committed by another request in the meantime makes this one a conflict.
+async fn commit_chunk(
+    db_pool: &DbPool,
+    sessions: &UploadSessionService,
+    session: &UploadSession,
+    chunk: &ChunkFile,
+    committed: i64,
+) -> Result<(), ApiError> {
+    let mut tx = db_pool.begin().await?;
+    if !UploadRepository::try_lock_for_commit(&mut tx, session.upload_id).await? {
+        return Err(upload_locked());
+    }
+
+    match UploadRepository::current_offset(&mut *tx, session.upload_id).await? {
+        None => return Err(ApiError::not_found("Upload", session.upload_id)),
+        Some(current) if current != session.upload_offset => {
+            return Err(offset_conflict(session.upload_offset, current));
+        }
+        Some(_) => {}
+    }
+
+    sessions.append_chunk(&session.staging_path, session.upload_offset as u64, chunk).await
+        .map_err(ApiError::storage)?;
+    if !UploadRepository::advance_offset(&mut *tx, session.upload_id, session.upload_offset, committed, session.expires_at).await? {
+        return Err(ApiError::conflict("Another request committed a chunk first; resume from the current offset"));
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+fn offset_conflict(client_offset: i64, current: i64) -> ApiError {
+    ApiError::conflict(format!("Upload-Offset {} does not match the current offset {}", client_offset, current))
+}
+
+/// Finish a resumable upload and queue the asset for AI processing
+///
+/// Runs the same dedup, asset and job pipeline as `/api/media/upload`.
+#[utoipa::path(
+    post,
+    path = "/api/media/uploads/{upload_id}/complete",
+    tag = "Media",
+    params(
+        ("upload_id" = Uuid, Path, description = "Upload session ID")
+    ),
+    responses(
+        (status = 200, description = "Upload stored and queued", body = MediaUploadResponse),
+        (status = 404, description = "Upload not found or expired", body = ErrorResponse),
+        (status = 409, description = "Upload is missing bytes", body = ErrorResponse),
+        (status = 415, description = "File content is not an allowed media or document type", body = ErrorResponse),
+        (status = 423, description = "Another request is writing to this upload", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn complete_upload(
+    State(db_pool): State<DbPool>,
+    State(storage): State<Arc<StorageRegistry>>,
+    State(sessions): State<Arc<UploadSessionService>>,
+    auth: AuthUser,
+    Path(upload_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let lock = UploadRepository::try_lock(&db_pool, upload_id).await?
+        .ok_or_else(upload_locked)?;
+
+    let result = finalize_upload(&db_pool, &storage, &sessions, upload_id, &auth).await;
+    release(lock, upload_id).await;
+    result
+}
+
+async fn finalize_upload(
+    db_pool: &DbPool,
+    storage: &StorageRegistry,
+    sessions: &UploadSessionService,
+    upload_id: Uuid,
+    auth: &AuthUser,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let session = load_session(db_pool, upload_id, auth).await?;
+    if !session.is_complete() {
+        return Err(ApiError::conflict(format!(
+            "Upload has {} of {} bytes", session.upload_offset, session.upload_length
+        )));
+    }
+
+    let asset_uuid = Uuid::new_v4();
+    let upload = store_staged_file(storage, &session, asset_uuid).await?;
+    let response = register_user_upload(
+        db_pool,
+        storage,
+        asset_uuid,
+        &session.filename,
+        upload,
+        session.metadata.clone(),
+        auth.id,
+    ).await?;
+
+    if let Err(e) = sessions.remove(db_pool, upload_id, &session.staging_path).await {
+        // Expiry cleanup will collect it
+        tracing::warn!("Failed to clean up finished upload {}: {:?}", upload_id, e);
+    }
+
+    Ok(response)
+}
+
+/// Copy the staged file into the storage backend, hashing it on the way (I-FR-02)
+async fn store_staged_file(
+    storage: &StorageRegistry,
+    session: &UploadSession,
+    asset_uuid: Uuid,
+) -> Result<StoredUpload, ApiError> {
+    let mut file = tokio::fs::File::open(&session.staging_path).await.map_err(ApiError::storage)?;
+
+    // Check the content before copying anything into storage
+    let mut head = Vec::with_capacity(content_sniffing::SNIFF_BYTES);
+    (&mut file).take(content_sniffing::SNIFF_BYTES as u64).read_to_end(&mut head).await
+        .and(file.rewind().await)
+        .map_err(ApiError::storage)?;
+    let content = sniff_upload(&head, &session.filename)?;
+
+    // Prefix with the asset id so uploads that share a filename never overwrite each other
+    let mut writer = storage.writer("uploads", &format!("{}-{}", asset_uuid, session.filename)).await
+        .map_err(ApiError::storage)?;
+
+    let mut hasher = hash::StreamingHasher::new();
+    let mut size: i64 = 0;
+    let mut buffer = vec![0u8; 1024 * 1024];
+
+    loop {
+        let read = match file.read(&mut buffer).await {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) => {
+                writer.abort().await.ok();
+                return Err(ApiError::storage(e));
+            }
+        };
+
+        hasher.update(&buffer[..read]);
+        if let Err(e) = writer.write(&buffer[..read]).await {
+            writer.abort().await.ok();
+            return Err(ApiError::storage(e));
+        }
+        size += read as i64;
+    }
+
+    let uri = writer.finish().await.map_err(ApiError::storage)?;
+
+    Ok(StoredUpload {
+        uri,
+        file_hash: hasher.finalize(),
+        file_size: size,
+        content,
+    })
+}
+
+/// Abandon a resumable upload and discard its staged bytes
+#[utoipa::path(
+    delete,
+    path = "/api/media/uploads/{upload_id}",
+    tag = "Media",
+    params(
+        ("upload_id" = Uuid, Path, description = "Upload session ID")
+    ),
+    responses(
+        (status = 204, description = "Upload discarded"),
+        (status = 404, description = "Upload not found or expired", body = ErrorResponse),
+        (status = 423, description = "Another request is writing to this upload", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn cancel_upload(
+    State(db_pool): State<DbPool>,
+    State(sessions): State<Arc<UploadSessionService>>,
+    auth: AuthUser,
+    Path(upload_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let lock = UploadRepository::try_lock(&db_pool, upload_id).await?
+        .ok_or_else(upload_locked)?;
+
+    let result = match load_session(&db_pool, upload_id, &auth).await {
+        Ok(session) => sessions.remove(&db_pool, upload_id, &session.staging_path).await
+            .map(|_| StatusCode::NO_CONTENT)
+            .map_err(ApiError::storage),
+        Err(error) => Err(error),
+    };
+    release(lock, upload_id).await;
+    result
+}
+
+async fn release(lock: AdvisoryLock, upload_id: Uuid) {
+    if let Err(e) = lock.release().await {
+        tracing::error!("Failed to release the lock on upload {}: {}", upload_id, e);
+    }
+}
+
+fn upload_locked() -> ApiError {
+    ApiError::new(StatusCode::LOCKED, "UPLOAD_LOCKED", "Another request is writing to this upload")
+}
+
+/// Active session owned by the caller; other users' sessions look like missing ones
+async fn load_session(db_pool: &DbPool, upload_id: Uuid, auth: &AuthUser) -> Result<UploadSession, ApiError> {
+    let session = UploadRepository::get(db_pool, upload_id).await?
+        .ok_or_else(|| ApiError::not_found("Upload", upload_id))?;
+
+    match &session.created_by {
+        Some(owner) if owner != &auth.id.to_string() => Err(ApiError::not_found("Upload", upload_id)),
+        _ => Ok(session),
+    }
+}
+
+fn offset_headers(session: &UploadSession) -> Result<HeaderMap, ApiError> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        UPLOAD_OFFSET,
+        HeaderValue::from_str(&session.upload_offset.to_string()).map_err(ApiError::internal)?,
+    );
+    headers.insert(
+        UPLOAD_LENGTH,
+        HeaderValue::from_str(&session.upload_length.to_string()).map_err(ApiError::internal)?,
+    );
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StorageConfig;
+    use crate::db::test_support::{db_pool, insert_user};
+    use crate::services::local_storage::LocalStorageService;
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+    use sqlx::PgPool;
+    use std::path::PathBuf;
+
+    struct Fixture {
+        db_pool: DbPool,
+        storage: Arc<StorageRegistry>,
+        sessions: Arc<UploadSessionService>,
+        user: AuthUser,
+        dir: PathBuf,
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.dir).ok();
+        }
+    }
+
+    async fn fixture(pool: PgPool) -> Fixture {
+        let db_pool = db_pool(pool);
+        let dir = std::env::temp_dir().join(format!("uploads-test-{}", Uuid::new_v4()));
+        let config = StorageConfig {
+            backend: "local".to_string(),
+            local_path: dir.join("storage").to_string_lossy().to_string(),
+            max_upload_bytes: 1024,
+            upload_staging_path: dir.join("staging").to_string_lossy().to_string(),
+            upload_session_ttl_hours: 1,
+        };
+        let storage = StorageRegistry::new(Arc::new(LocalStorageService::new(Some(config.local_path.clone()))))
+            .with_max_upload_bytes(config.max_upload_bytes);
+        let id = insert_user(&db_pool, "uploader@example.com", "EDITOR").await;
+
+        Fixture {
+            sessions: Arc::new(UploadSessionService::new(&config).unwrap()),
+            storage: Arc::new(storage),
+            user: AuthUser { id, email: "uploader@example.com".to_string() },
+            db_pool,
+            dir,
+        }
+    }
+
+    fn caller(f: &Fixture) -> AuthUser {
+        AuthUser { id: f.user.id, email: f.user.email.clone() }
+    }
+
+    async fn create(f: &Fixture, upload_length: i64) -> Uuid {
+        let request = CreateUploadRequest {
+            filename: "clip.mp4".to_string(),
+            upload_length,
+            title: None,
+            description: None,
+            tags: None,
+            category: None,
+        };
+        let response = create_upload(State(f.db_pool.clone()), State(f.storage.clone()), State(f.sessions.clone()), caller(f), Json(request))
+            .await
+            .unwrap();
+        let location = response.headers()[axum::http::header::LOCATION].to_str().unwrap();
+        location.rsplit('/').next().unwrap().parse().unwrap()
+    }
+
+    async fn patch(f: &Fixture, upload_id: Uuid, offset: i64, body: impl Into<Body>) -> Result<Response, ApiError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(UPLOAD_OFFSET, HeaderValue::from_str(&offset.to_string()).unwrap());
+        append_chunk(State(f.db_pool.clone()), State(f.sessions.clone()), caller(f), Path(upload_id), headers, body.into()).await
+    }
+
+    async fn offset(f: &Fixture, upload_id: Uuid) -> i64 {
+        let response = get_upload_offset(State(f.db_pool.clone()), caller(f), Path(upload_id)).await.unwrap();
+        response.headers()[UPLOAD_OFFSET].to_str().unwrap().parse().unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn interrupted_uploads_resume_from_the_committed_offset(pool: PgPool) {
+        let f = fixture(pool).await;
+        let upload_id = create(&f, 10).await;
+
+        let response = patch(&f, upload_id, 0, "abcd").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(response.headers()[UPLOAD_OFFSET], "4");
+
+        // The connection drops after two more bytes arrive; they are kept
+        let dropped = futures_util::stream::iter(vec![
+            Ok(axum::body::Bytes::from_static(b"ef")),
+            Err(std::io::Error::other("connection reset")),
+        ]);
+        let error = patch(&f, upload_id, 4, Body::from_stream(dropped)).await.unwrap_err();
+        assert!(error.to_string().contains("resume from the current offset"), "{}", error);
+        assert_eq!(offset(&f, upload_id).await, 6);
+
+        patch(&f, upload_id, 6, "ghij").await.unwrap();
+        assert_eq!(offset(&f, upload_id).await, 10);
+        let staged = std::fs::read(f.sessions.staging_path(upload_id)).unwrap();
+        assert_eq!(staged, b"abcdefghij");
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn out_of_order_and_overlong_chunks_are_refused(pool: PgPool) {
+        let f = fixture(pool).await;
+        let upload_id = create(&f, 6).await;
+        patch(&f, upload_id, 0, "abc").await.unwrap();
+
+        for stale_or_ahead in [0, 2, 5] {
+            let error = patch(&f, upload_id, stale_or_ahead, "x").await.unwrap_err();
+            assert!(error.to_string().contains("CONFLICT"), "{}", error);
+        }
+        let error = patch(&f, upload_id, 3, "defg").await.unwrap_err();
+        assert!(error.to_string().contains("PAYLOAD_TOO_LARGE"), "{}", error);
+        assert_eq!(offset(&f, upload_id).await, 3);
+
+        patch(&f, upload_id, 3, "def").await.unwrap();
+        assert_eq!(std::fs::read(f.sessions.staging_path(upload_id)).unwrap(), b"abcdef");
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn locked_and_expired_uploads_are_refused(pool: PgPool) {
+        let f = fixture(pool).await;
+        let upload_id = create(&f, 4).await;
+
+        let lock = UploadRepository::try_lock(&f.db_pool, upload_id).await.unwrap().unwrap();
+        let error = patch(&f, upload_id, 0, "ab").await.unwrap_err();
+        assert!(error.to_string().contains("UPLOAD_LOCKED"), "{}", error);
+        lock.release().await.unwrap();
+        patch(&f, upload_id, 0, "ab").await.unwrap();
+
+        sqlx::query("UPDATE upload_sessions SET expires_at = NOW() - INTERVAL '1 minute' WHERE upload_id = $1")
+            .bind(upload_id)
+            .execute(f.db_pool.as_ref())
+            .await
+            .unwrap();
+        let error = patch(&f, upload_id, 2, "cd").await.unwrap_err();
+        assert!(error.to_string().contains("NOT_FOUND"), "{}", error);
+    }
+
+    fn channel_body() -> (tokio::sync::mpsc::Sender<Result<axum::body::Bytes, std::io::Error>>, Body) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let stream = futures_util::stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|item| (item, rx)) });
+        (tx, Body::from_stream(stream))
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn chunk_transfer_holds_no_database_connection(pool_options: PgPoolOptions, connect_options: PgConnectOptions) {
+        // With a single connection, other requests only get through if the PATCH is not holding it
+        let pool = pool_options
+            .max_connections(1)
+            .acquire_timeout(std::time::Duration::from_secs(2))
+            .connect_with(connect_options)
+            .await
+            .unwrap();
+        let f = fixture(pool).await;
+        let upload_id = create(&f, 4).await;
+
+        let (sender, body) = channel_body();
+        let client = async {
+            sender.send(Ok(axum::body::Bytes::from_static(b"ab"))).await.unwrap();
+            assert_eq!(offset(&f, upload_id).await, 0);
+            sender.send(Ok(axum::body::Bytes::from_static(b"cd"))).await.unwrap();
+            assert_eq!(offset(&f, upload_id).await, 0);
+            drop(sender);
+        };
+        let (response, ()) = tokio::join!(patch(&f, upload_id, 0, body), client);
+
+        assert_eq!(response.unwrap().headers()[UPLOAD_OFFSET], "4");
+        assert_eq!(std::fs::read(f.sessions.staging_path(upload_id)).unwrap(), b"abcd");
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn concurrent_chunks_for_one_offset_commit_only_once(pool: PgPool) {
+        let f = fixture(pool).await;
+        let upload_id = create(&f, 6).await;
+
+        let (first_sender, first_body) = channel_body();
+        let (second_sender, second_body) = channel_body();
+        let client = async move {
+            // The second request is receiving at offset 0 before the first one commits
+            second_sender.send(Ok(axum::body::Bytes::from_static(b"xyz"))).await.unwrap();
+            while second_sender.capacity() == 0 {
+                tokio::task::yield_now().await;
+            }
+            first_sender.send(Ok(axum::body::Bytes::from_static(b"abc"))).await.unwrap();
+            second_sender
+        };
+        let first = async {
+            let (response, second_sender) = tokio::join!(patch(&f, upload_id, 0, first_body), client);
+            drop(second_sender);
+            response
+        };
+        let (first, second) = tokio::join!(first, patch(&f, upload_id, 0, second_body));
+
+        assert_eq!(first.unwrap().headers()[UPLOAD_OFFSET], "3");
+        let error = second.unwrap_err();
+        assert!(error.to_string().contains("CONFLICT"), "{}", error);
+        assert_eq!(offset(&f, upload_id).await, 3);
+        assert_eq!(std::fs::read(f.sessions.staging_path(upload_id)).unwrap(), b"abc");
+
+        // Neither request leaves its chunk file behind
+        let leftovers = std::fs::read_dir(f.dir.join("staging")).unwrap()
+            .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().ends_with(".chunk"))
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+}
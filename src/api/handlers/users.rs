@@ -0,0 +1,453 @@
+// User management handlers
+// I-FR-23: Secure API and access governance - roles and account lifecycle
+// I-FR-05: Every change is written to action_records
+//
+// GET  /api/admin/users                    list and search accounts
+// GET  /api/admin/users/:user_id           one account with its API keys
+// PUT  /api/admin/users/:user_id/role      change role
+// POST /api/admin/users/:user_id/disable   reject all of the user's credentials
+// POST /api/admin/users/:user_id/enable    undo a disable
+
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::PgConnection;
+use uuid::Uuid;
+use crate::api::error::ApiError;
+use crate::api::handlers::auth::api_key_summary;
+use crate::db::DbPool;
+use crate::db::repositories::{
+    action_repository::ActionRepository,
+    token_repository::TokenRepository,
+    user_repository::UserRepository,
+};
+use crate::middleware::auth::AuthUser;
+use crate::models::action_record::{ActionRecord, ActionStatus, ActionType, Direction};
+use crate::models::user::{User, UserRole};
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListUsersQuery {
+    /// Case-insensitive substring of the email or name
+    pub search: Option<String>,
+    /// Admin, ContentManager, Editor, Developer or Viewer
+    pub role: Option<String>,
+    /// active or disabled
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct UpdateRoleRequest {
+    /// Admin, ContentManager, Editor, Developer or Viewer
+    pub role: String,
+}
+
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+pub struct DisableUserRequest {
+    pub reason: Option<String>,
+}
+
+/// List users
+///
+/// I-FR-23: Account administration
+#[utoipa::path(
+    get,
+    path = "/api/admin/users",
+    tag = "Admin",
+    params(ListUsersQuery),
+    responses(
+        (status = 200, description = "Matching users, newest first"),
+        (status = 400, description = "Unknown role or status filter", body = ErrorResponse),
+        (status = 403, description = "Missing manage:users permission", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_users(
+    State(db_pool): State<DbPool>,
+    Query(query): Query<ListUsersQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let role = query.role.as_deref()
+        .map(|role| UserRole::from_claim(role).ok_or_else(|| ApiError::invalid_field("role", "is not a known role")))
+        .transpose()?;
+    let disabled = match query.status.as_deref() {
+        None => None,
+        Some("active") => Some(false),
+        Some("disabled") => Some(true),
+        Some(_) => return Err(ApiError::invalid_field("status", "must be 'active' or 'disabled'")),
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let users = UserRepository::list(&db_pool, query.search.as_deref(), role.as_ref(), disabled, limit, offset).await?;
+
+    Ok(Json(json!({
+        "users": users.iter().map(user_summary).collect::<Vec<_>>(),
+        "limit": limit,
+        "offset": offset,
+    })))
+}
+
+/// Get a user with their last login and API keys
+///
+/// I-FR-23: Account administration
+#[utoipa::path(
+    get,
+    path = "/api/admin/users/{user_id}",
+    tag = "Admin",
+    params(("user_id" = Uuid, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "User, effective permissions and API keys (without secrets)"),
+        (status = 403, description = "Missing manage:users permission", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_user(
+    State(db_pool): State<DbPool>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user = find_user(&db_pool, user_id).await?;
+    let keys = UserRepository::list_api_keys(&db_pool, user_id).await?;
+
+    let mut result = user_summary(&user);
+    result["permissions"] = json!(user.role.permissions().iter().map(|p| p.as_str()).collect::<Vec<_>>());
+    result["api_keys"] = json!(keys.iter().map(api_key_summary).collect::<Vec<_>>());
+
+    Ok(Json(result))
+}
+
+/// Change a user's role
+///
+/// Open sessions are revoked so the new role applies from the user's next sign-in or refresh.
+/// API keys follow the owner's current role on every request.
+#[utoipa::path(
+    put,
+    path = "/api/admin/users/{user_id}/role",
+    tag = "Admin",
+    params(("user_id" = Uuid, Path, description = "User ID")),
+    request_body = UpdateRoleRequest,
+    responses(
+        (status = 200, description = "Role updated"),
+        (status = 400, description = "Unknown role, an admin removing their own admin role, or demoting the last enabled admin", body = ErrorResponse),
+        (status = 403, description = "Missing manage:users permission", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_user_role(
+    State(db_pool): State<DbPool>,
+    auth: AuthUser,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<UpdateRoleRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let role = UserRole::from_claim(&payload.role)
+        .ok_or_else(|| ApiError::invalid_field("role", "is not a known role"))?;
+    // Keep at least the caller able to administer accounts
+    if user_id == auth.id && role != UserRole::Admin {
+        return Err(ApiError::invalid_field("role", "admins cannot remove their own admin role"));
+    }
+
+    let user = find_user(&db_pool, user_id).await?;
+    if user.role == role {
+        return Ok(Json(json!({
+            "status": "unchanged",
+            "user_id": user_id,
+            "role": format!("{:?}", role),
+        })));
+    }
+
+    // Role, session revocation and the action record land together or not at all
+    let mut tx = db_pool.begin().await?;
+    if user.role == UserRole::Admin {
+        ensure_another_admin(&mut tx, user_id, "role").await?;
+    }
+    UserRepository::update_role(&mut *tx, user_id, &role).await?;
+    let sessions_revoked = TokenRepository::revoke_user_sessions(&mut *tx, user_id, "role changed").await?;
+    record_user_change(&mut tx, ActionType::UserRoleChanged, &auth, json!({
+        "target_user_id": user_id,
+        "target_email": user.email,
+        "previous_role": format!("{:?}", user.role),
+        "new_role": format!("{:?}", role),
+        "sessions_revoked": sessions_revoked,
+    })).await?;
+    tx.commit().await?;
+    tracing::info!("{} changed role of {} from {:?} to {:?}", auth.email, user.email, user.role, role);
+
+    Ok(Json(json!({
+        "status": "success",
+        "user_id": user_id,
+        "previous_role": format!("{:?}", user.role),
+        "role": format!("{:?}", role),
+        "sessions_revoked": sessions_revoked,
+    })))
+}
+
+/// Disable an account
+///
+/// Sign-in, refresh, existing access tokens and the user's API keys are all rejected until
+/// the account is enabled again. Keys are kept, not revoked.
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{user_id}/disable",
+    tag = "Admin",
+    params(("user_id" = Uuid, Path, description = "User ID")),
+    request_body(content = DisableUserRequest, description = "Optional reason, kept on the account"),
+    responses(
+        (status = 200, description = "Account disabled"),
+        (status = 400, description = "Admins cannot disable themselves or the last enabled admin", body = ErrorResponse),
+        (status = 403, description = "Missing manage:users permission", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 409, description = "Account is already disabled", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn disable_user(
+    State(db_pool): State<DbPool>,
+    auth: AuthUser,
+    Path(user_id): Path<Uuid>,
+    payload: Option<Json<DisableUserRequest>>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if user_id == auth.id {
+        return Err(ApiError::bad_request("Admins cannot disable their own account"));
+    }
+    let reason = payload.and_then(|Json(p)| p.reason);
+
+    let user = find_user(&db_pool, user_id).await?;
+    let mut tx = db_pool.begin().await?;
+    if user.role == UserRole::Admin {
+        ensure_another_admin(&mut tx, user_id, "account").await?;
+    }
+    if !UserRepository::disable(&mut *tx, user_id, reason.as_deref()).await? {
+        return Err(ApiError::conflict("Account is already disabled"));
+    }
+    let sessions_revoked = TokenRepository::revoke_user_sessions(&mut *tx, user_id, "account disabled").await?;
+    record_user_change(&mut tx, ActionType::UserDisabled, &auth, json!({
+        "target_user_id": user_id,
+        "target_email": user.email,
+        "reason": reason,
+        "sessions_revoked": sessions_revoked,
+    })).await?;
+    tx.commit().await?;
+    tracing::info!("{} disabled account {}", auth.email, user.email);
+
+    Ok(Json(json!({
+        "status": "success",
+        "user_id": user_id,
+        "account_status": "disabled",
+        "sessions_revoked": sessions_revoked,
+    })))
+}
+
+/// Re-enable a disabled account
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{user_id}/enable",
+    tag = "Admin",
+    params(("user_id" = Uuid, Path, description = "User ID")),
+    responses(
+        (status = 200, description = "Account enabled; its API keys work again"),
+        (status = 403, description = "Missing manage:users permission", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 409, description = "Account is not disabled", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = []),
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn enable_user(
+    State(db_pool): State<DbPool>,
+    auth: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user = find_user(&db_pool, user_id).await?;
+    let mut tx = db_pool.begin().await?;
+    if !UserRepository::enable(&mut *tx, user_id).await? {
+        return Err(ApiError::conflict("Account is not disabled"));
+    }
+    record_user_change(&mut tx, ActionType::UserEnabled, &auth, json!({
+        "target_user_id": user_id,
+        "target_email": user.email,
+        "disabled_at": user.disabled_at,
+        "disabled_reason": user.disabled_reason,
+    })).await?;
+    tx.commit().await?;
+    tracing::info!("{} enabled account {}", auth.email, user.email);
+
+    Ok(Json(json!({
+        "status": "success",
+        "user_id": user_id,
+        "account_status": "active",
+    })))
+}
+
+async fn find_user(db_pool: &DbPool, user_id: Uuid) -> Result<User, ApiError> {
+    UserRepository::get_by_id(db_pool, user_id).await?
+        .ok_or_else(|| ApiError::not_found("User", user_id))
+}
+
+fn user_summary(user: &User) -> serde_json::Value {
+    json!({
+        "id": user.id,
+        "email": user.email,
+        "name": user.name,
+        "role": format!("{:?}", user.role),
+        "sso_provider_id": user.sso_provider_id,
+        "created_at": user.created_at,
+        "last_login": user.last_login,
+        "status": if user.disabled_at.is_some() { "disabled" } else { "active" },
+        "disabled_at": user.disabled_at,
+        "disabled_reason": user.disabled_reason,
+    })
+}
+
+// Refuse to demote or disable `user_id` when no other enabled admin would remain.
+// The admin rows stay locked until the transaction ends, so two such changes cannot race.
+async fn ensure_another_admin(tx: &mut PgConnection, user_id: Uuid, field: &str) -> Result<(), ApiError> {
+    let admins = UserRepository::lock_active_admins(&mut *tx).await?;
+    if admins.iter().all(|id| *id == user_id) {
+        return Err(ApiError::invalid_field(field, "the last enabled admin cannot be demoted or disabled"));
+    }
+    Ok(())
+}
+
+// I-FR-05: The acting admin is the record's user; the account changed goes in metadata
+async fn record_user_change(
+    tx: &mut PgConnection,
+    action_type: ActionType,
+    auth: &AuthUser,
+    metadata: serde_json::Value,
+) -> Result<(), ApiError> {
+    ActionRepository::insert(&mut *tx, &ActionRecord {
+        record_id: Uuid::new_v4(),
+        asset_uuid: None,
+        action_type,
+        direction: Direction::Internal,
+        controller_name: "UserAdminAPI".to_string(),
+        controller_version: "v1.0.0".to_string(),
+        source_system: None,
+        destination_system: None,
+        status: ActionStatus::Success,
+        timestamp: chrono::Utc::now(),
+        metadata: Some(metadata),
+        user_id: Some(auth.id),
+    }).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{db_pool, insert_user};
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    fn admin(id: Uuid) -> AuthUser {
+        AuthUser { id, email: "admin@example.com".to_string() }
+    }
+
+    fn role(role: &str) -> Json<UpdateRoleRequest> {
+        Json(UpdateRoleRequest { role: role.to_string() })
+    }
+
+    async fn open_session(pool: &DbPool, user_id: Uuid) -> Uuid {
+        let session_id = Uuid::new_v4();
+        TokenRepository::create_session(pool, session_id, user_id, Utc::now() + Duration::hours(1)).await.unwrap();
+        session_id
+    }
+
+    async fn action_count(pool: &DbPool, action_type: &str) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM action_records WHERE action_type::text = $1")
+            .bind(action_type)
+            .fetch_one(pool.as_ref())
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn admins_cannot_demote_themselves(pool: PgPool) {
+        let pool = db_pool(pool);
+        let me = insert_user(&pool, "admin@example.com", "ADMIN").await;
+        insert_user(&pool, "other-admin@example.com", "ADMIN").await;
+
+        let error = update_user_role(State(pool.clone()), admin(me), Path(me), role("Viewer")).await.unwrap_err();
+        assert!(error.to_string().contains("VALIDATION_FAILED"), "{}", error);
+        assert_eq!(find_user(&pool, me).await.unwrap().role, UserRole::Admin);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn last_enabled_admin_is_kept(pool: PgPool) {
+        let pool = db_pool(pool);
+        let actor = insert_user(&pool, "manager@example.com", "ADMIN").await;
+        let last = insert_user(&pool, "last-admin@example.com", "ADMIN").await;
+        UserRepository::disable(pool.as_ref(), actor, None).await.unwrap();
+
+        let error = update_user_role(State(pool.clone()), admin(actor), Path(last), role("Editor")).await.unwrap_err();
+        assert!(error.to_string().contains("last enabled admin"), "{}", error);
+        let error = disable_user(State(pool.clone()), admin(actor), Path(last), None).await.unwrap_err();
+        assert!(error.to_string().contains("last enabled admin"), "{}", error);
+
+        let user = find_user(&pool, last).await.unwrap();
+        assert_eq!(user.role, UserRole::Admin);
+        assert!(user.disabled_at.is_none());
+        assert_eq!(action_count(&pool, "USER_ROLE_CHANGED").await, 0);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn role_change_revokes_sessions_and_is_recorded(pool: PgPool) {
+        let pool = db_pool(pool);
+        let me = insert_user(&pool, "admin@example.com", "ADMIN").await;
+        let editor = insert_user(&pool, "editor@example.com", "EDITOR").await;
+        let session = open_session(&pool, editor).await;
+        let my_session = open_session(&pool, me).await;
+
+        let Json(body) = update_user_role(State(pool.clone()), admin(me), Path(editor), role("Viewer")).await.unwrap();
+        assert_eq!(body["sessions_revoked"], 1);
+        assert_eq!(find_user(&pool, editor).await.unwrap().role, UserRole::Viewer);
+        assert!(!TokenRepository::session_is_active(&pool, session).await.unwrap());
+        assert!(TokenRepository::session_is_active(&pool, my_session).await.unwrap());
+        assert_eq!(action_count(&pool, "USER_ROLE_CHANGED").await, 1);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn disable_revokes_sessions_and_is_recorded(pool: PgPool) {
+        let pool = db_pool(pool);
+        let me = insert_user(&pool, "admin@example.com", "ADMIN").await;
+        let other_admin = insert_user(&pool, "second@example.com", "ADMIN").await;
+        let session = open_session(&pool, other_admin).await;
+
+        let Json(body) = disable_user(State(pool.clone()), admin(me), Path(other_admin), None).await.unwrap();
+        assert_eq!(body["sessions_revoked"], 1);
+        assert!(find_user(&pool, other_admin).await.unwrap().disabled_at.is_some());
+        assert!(!TokenRepository::session_is_active(&pool, session).await.unwrap());
+        assert_eq!(action_count(&pool, "USER_DISABLED").await, 1);
+
+        let error = disable_user(State(pool.clone()), admin(me), Path(other_admin), None).await.unwrap_err();
+        assert!(error.to_string().contains("CONFLICT"), "{}", error);
+        assert_eq!(action_count(&pool, "USER_DISABLED").await, 1);
+    }
+}
@@ -4,12 +4,15 @@
 
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
     response::Json,
 };
 use uuid::Uuid;
+use crate::api::error::ApiError;
 use crate::db::DbPool;
-use crate::db::repositories::workflow_repository::WorkflowRepository;
+use crate::middleware::auth::AuthUser;
+use crate::db::repositories::{action_repository::ActionRepository, workflow_repository::WorkflowRepository};
+use crate::models::action_record::{ActionRecord, ActionStatus, ActionType, Direction};
+use crate::models::workflow::JobStatus;
 use serde_json::json;
 use sqlx::Row;
 
@@ -37,10 +40,9 @@ use sqlx::Row;
 pub async fn get_workflow_status(
     State(db_pool): State<DbPool>,
     Path(asset_id): Path<Uuid>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     // Get latest job for asset using repository
-    let job = WorkflowRepository::get_job_by_asset(&db_pool, asset_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let job = WorkflowRepository::get_job_by_asset(&db_pool, asset_id).await?;
 
     if let Some(job) = job {
         Ok(Json(json!({
@@ -65,10 +67,9 @@ pub async fn get_workflow_status(
 pub async fn get_job_status(
     State(db_pool): State<DbPool>,
     Path(job_id): Path<Uuid>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let job = WorkflowRepository::get_job(&db_pool, job_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let job = WorkflowRepository::get_job(&db_pool, job_id).await?
+        .ok_or_else(|| ApiError::not_found("Job", job_id))?;
 
     Ok(Json(json!({
         "job_id": job.job_id,
@@ -85,35 +86,61 @@ pub async fn get_job_status(
     })))
 }
 
+// I-FR-16: Manually re-queue a job, optionally with a per-job retry configuration
 pub async fn retry_job(
     State(db_pool): State<DbPool>,
+    auth: AuthUser,
     Path(job_id): Path<Uuid>,
     Json(config): Json<serde_json::Value>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let mut job = WorkflowRepository::get_job(&db_pool, job_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let job = WorkflowRepository::get_job(&db_pool, job_id).await?
+        .ok_or_else(|| ApiError::not_found("Job", job_id))?;
 
-    // Update retry config
-    job.retry_count += 1;
-    job.retry_config = Some(config.clone());
-    job.status = crate::models::workflow::JobStatus::Retrying;
+    if !matches!(job.status, JobStatus::Failed | JobStatus::Cancelled | JobStatus::Retrying) {
+        return Err(ApiError::conflict(format!("Job is {:?}; only failed or cancelled jobs can be retried", job.status)));
+    }
+
+    // Only persist a per-job override when the caller supplied retry fields
+    let retry_config = config.as_object()
+        .filter(|c| !c.is_empty())
+        .map(|_| config.clone());
+    let retry_count = job.retry_count + 1;
 
-    WorkflowRepository::update_job_status(
+    WorkflowRepository::schedule_retry(
         &db_pool,
         job_id,
-        job.status.clone(),
         None,
+        retry_count,
+        chrono::Utc::now(),
         None,
-    ).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    // TODO: Trigger workflow with retry config
+        retry_config.as_ref(),
+    ).await?;
+
+    ActionRepository::create(&db_pool, &ActionRecord {
+        record_id: Uuid::new_v4(),
+        asset_uuid: Some(job.asset_uuid),
+        action_type: ActionType::JobRetry,
+        direction: Direction::Internal,
+        controller_name: "WorkflowAPI".to_string(),
+        controller_version: "v1.0.0".to_string(),
+        source_system: None,
+        destination_system: None,
+        status: ActionStatus::Initiated,
+        timestamp: chrono::Utc::now(),
+        metadata: Some(json!({
+            "job_id": job_id,
+            "manual": true,
+            "retry_count": retry_count,
+            "retry_config": retry_config,
+        })),
+        user_id: Some(auth.id),
+    }).await?;
 
     Ok(Json(json!({
         "status": "success",
         "message": "Retry initiated",
         "job_id": job_id,
-        "retry_count": job.retry_count,
+        "retry_count": retry_count,
         "new_status": "RETRYING"
     })))
 }
@@ -121,11 +148,12 @@ pub async fn retry_job(
 // I-FR-32: Create new AI workflow
 pub async fn create_workflow(
     State(db_pool): State<DbPool>,
+    auth: AuthUser,
     Json(payload): Json<serde_json::Value>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let workflow_name = payload.get("workflow_name")
         .and_then(|s| s.as_str())
-        .ok_or(StatusCode::BAD_REQUEST)?;
+        .ok_or_else(|| ApiError::invalid_field("workflow_name", "is required"))?;
 
     let workflow = crate::models::workflow::WorkflowDefinition {
         workflow_id: Uuid::new_v4(),
@@ -146,12 +174,11 @@ pub async fn create_workflow(
             .map(|a| a.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
             .unwrap_or_default(),
         created_at: chrono::Utc::now(),
-        created_by: Uuid::new_v4(), // TODO: Get from auth
+        created_by: auth.id,
         is_active: true,
     };
 
-    let workflow_id = WorkflowRepository::create_workflow_definition(&db_pool, &workflow).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let workflow_id = WorkflowRepository::create_workflow_definition(&db_pool, &workflow).await?;
 
     Ok(Json(json!({
         "workflow_id": workflow_id,
@@ -162,13 +189,12 @@ pub async fn create_workflow(
 
 pub async fn list_workflows(
     State(db_pool): State<DbPool>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let workflows = sqlx::query(
         "SELECT workflow_id, workflow_name, description, ai_capabilities FROM workflow_definitions WHERE is_active = true ORDER BY created_at DESC"
     )
     .fetch_all(db_pool.as_ref())
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .await?;
 
     let result: Vec<serde_json::Value> = workflows.iter().map(|row: &sqlx::postgres::PgRow| {
         json!({
@@ -1,41 +1,119 @@
 // API routes and handlers
 // Maps to all API requirements (I-FR-23 through I-FR-33)
 
+pub mod error;
 mod routes;
 mod handlers;
 mod middleware;
 mod openapi;
 
-use axum::Router;
+use axum::{extract::FromRef, Router};
+use crate::config::RetryConfig;
 use crate::db::DbPool;
+use crate::services::oidc::OidcService;
+use crate::services::rate_limiter::RateLimiter;
+use crate::services::storage::StorageRegistry;
+use crate::services::upload_sessions::UploadSessionService;
+use std::sync::Arc;
 
-pub async fn create_router(db_pool: DbPool) -> anyhow::Result<Router> {
+/// Shared state for routes that touch stored files as well as the database
+#[derive(Clone)]
+pub struct AppState {
+    pub db_pool: DbPool,
+    pub storage: Arc<StorageRegistry>,
+    pub uploads: Arc<UploadSessionService>,
+    pub rate_limiter: Arc<RateLimiter>,
+    pub retry: RetryConfig, // I-FR-16: Configured defaults under the stored retry policy
+}
+
+impl FromRef<AppState> for DbPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.db_pool.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<StorageRegistry> {
+    fn from_ref(state: &AppState) -> Self {
+        state.storage.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<UploadSessionService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.uploads.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<RateLimiter> {
+    fn from_ref(state: &AppState) -> Self {
+        state.rate_limiter.clone()
+    }
+}
+
+impl FromRef<AppState> for RetryConfig {
+    fn from_ref(state: &AppState) -> Self {
+        state.retry.clone()
+    }
+}
+
+/// State for the sign-in routes; `oidc` is None unless a corporate provider is configured
+#[derive(Clone)]
+pub struct AuthState {
+    pub db_pool: DbPool,
+    pub oidc: Option<Arc<OidcService>>,
+}
+
+impl FromRef<AuthState> for DbPool {
+    fn from_ref(state: &AuthState) -> Self {
+        state.db_pool.clone()
+    }
+}
+
+impl FromRef<AuthState> for Option<Arc<OidcService>> {
+    fn from_ref(state: &AuthState) -> Self {
+        state.oidc.clone()
+    }
+}
+
+pub async fn create_router(
+    db_pool: DbPool,
+    storage: Arc<StorageRegistry>,
+    uploads: Arc<UploadSessionService>,
+    rate_limiter: Arc<RateLimiter>,
+    oidc: Option<Arc<OidcService>>,
+    retry: RetryConfig,
+) -> anyhow::Result<Router> {
     use tower::ServiceBuilder;
     use tower_http::{cors::CorsLayer, trace::TraceLayer};
     use utoipa::OpenApi;
     use utoipa_swagger_ui::SwaggerUi;
     use axum::middleware;
-    use std::sync::Arc;
     
     let openapi = openapi::ApiDoc::openapi();
     
     // Public routes (no auth required)
     let public_routes = Router::new()
-        .merge(routes::auth::create_auth_routes(db_pool.clone()))
+        .merge(routes::auth::create_auth_routes(AuthState { db_pool: db_pool.clone(), oidc }))
         .merge(
             SwaggerUi::new("/swagger-ui")
                 .url("/api-docs/openapi.json", openapi.clone())
         );
     
     // Protected routes (require authentication)
-    // Apply auth middleware to all protected routes
+    // Apply auth middleware to all protected routes; each route also declares its required permission
     let db_pool_for_middleware = db_pool.clone();
+    let state = AppState { db_pool: db_pool.clone(), storage, uploads, rate_limiter: rate_limiter.clone(), retry };
     let protected_routes = Router::new()
-        .merge(routes::media::create_media_routes(db_pool.clone()))
+        .merge(routes::media::create_media_routes(state.clone()))
         .merge(routes::metadata::create_metadata_routes(db_pool.clone()))
         .merge(routes::workflow::create_workflow_routes(db_pool.clone()))
         .merge(routes::graph::create_graph_routes(db_pool.clone()))
-        .merge(routes::admin::create_admin_routes(db_pool.clone()))
+        .merge(routes::saved_searches::create_saved_search_routes(db_pool.clone()))
+        .merge(routes::admin::create_admin_routes(state.clone()))
+        .merge(routes::users::create_user_routes(db_pool.clone()))
+        .merge(routes::auth::create_access_routes(state))
+        // I-FR-25: Counted per API key or user; runs after authentication below identifies the caller
+        .layer(axum::middleware::from_fn_with_state(rate_limiter, crate::middleware::rate_limit::rate_limit_middleware))
         .layer(
             axum::middleware::from_fn(move |request: axum::extract::Request, next: axum::middleware::Next| {
                 let db_pool = db_pool_for_middleware.clone();
@@ -59,6 +137,8 @@ pub async fn create_router(db_pool: DbPool) -> anyhow::Result<Router> {
     let router = Router::new()
         .merge(public_routes)
         .merge(protected_routes)
+        .fallback(|| async { error::ApiError::new(axum::http::StatusCode::NOT_FOUND, "NOT_FOUND", "No such endpoint") })
+        .layer(middleware::from_fn(crate::middleware::request_id::request_id_middleware))
         .layer(CorsLayer::permissive())
         .layer(TraceLayer::new_for_http());
 
@@ -7,9 +7,14 @@ use utoipa::{
     Modify, PartialSchema,
 };
 
-use crate::models::asset::Asset;
+use crate::models::asset::{Asset, TechnicalMetadata};
 use crate::models::metadata::{EnrichedMetadata, MetadataUpdate};
 use crate::models::workflow::ProcessingJob;
+use crate::api::error::FieldError;
+use crate::api::handlers::auth::RefreshTokenRequest;
+use crate::api::handlers::saved_searches::SavedSearchRequest;
+use crate::api::handlers::uploads::CreateUploadRequest;
+use crate::api::handlers::users::{DisableUserRequest, UpdateRoleRequest};
 
 #[derive(OpenApi)]
 #[openapi(
@@ -17,12 +22,23 @@ use crate::models::workflow::ProcessingJob;
         // Auth endpoints
         crate::api::handlers::auth::google_login,
         crate::api::handlers::auth::google_callback,
+        crate::api::handlers::auth::oidc_login,
+        crate::api::handlers::auth::oidc_callback,
+        crate::api::handlers::auth::refresh_token,
+        crate::api::handlers::auth::logout,
         crate::api::handlers::auth::generate_api_key,
+        crate::api::handlers::auth::rotate_api_key,
         // Media endpoints
         crate::api::handlers::media::submit_media,
         crate::api::handlers::media::upload_media,
+        crate::api::handlers::media::list_media,
         crate::api::handlers::media::get_media,
         crate::api::handlers::media::download_media,
+        crate::api::handlers::uploads::create_upload,
+        crate::api::handlers::uploads::get_upload_offset,
+        crate::api::handlers::uploads::append_chunk,
+        crate::api::handlers::uploads::complete_upload,
+        crate::api::handlers::uploads::cancel_upload,
         // Metadata endpoints
         crate::api::handlers::metadata::get_metadata,
         crate::api::handlers::metadata::update_metadata,
@@ -31,16 +47,30 @@ use crate::models::workflow::ProcessingJob;
         crate::api::handlers::workflow::get_workflow_status,
         // Graph endpoints
         crate::api::handlers::graph::search,
+        crate::api::handlers::graph::suggest,
+        crate::api::handlers::saved_searches::list_saved_searches,
+        crate::api::handlers::saved_searches::create_saved_search,
+        crate::api::handlers::saved_searches::get_saved_search,
+        crate::api::handlers::saved_searches::update_saved_search,
+        crate::api::handlers::saved_searches::delete_saved_search,
+        crate::api::handlers::saved_searches::get_saved_search_matches,
         // Admin endpoints
         crate::api::handlers::admin::get_controller_status,
+        crate::api::handlers::users::list_users,
+        crate::api::handlers::users::get_user,
+        crate::api::handlers::users::update_user_role,
+        crate::api::handlers::users::disable_user,
+        crate::api::handlers::users::enable_user,
     ),
     components(schemas(
         Asset,
+        TechnicalMetadata,
         EnrichedMetadata,
         MetadataUpdate,
         ProcessingJob,
         MediaSubmitResponse,
         MediaUploadResponse,
+        CreateUploadRequest,
         MetadataResponse,
         ConflictResolutionRequest,
         WorkflowStatusResponse,
@@ -49,7 +79,13 @@ use crate::models::workflow::ProcessingJob;
         ApiKeyResponse,
         GoogleLoginResponse,
         SSOCallbackResponse,
+        RefreshTokenRequest,
+        UpdateRoleRequest,
+        DisableUserRequest,
+        SavedSearchRequest,
+        TokenRefreshResponse,
         ErrorResponse,
+        FieldError,
     )),
     modifiers(&SecurityAddon),
     tags(
@@ -139,9 +175,21 @@ pub struct WorkflowStatusResponse {
 
 #[derive(utoipa::ToSchema)]
 pub struct GraphSearchResponse {
+    /// `assets` or `transcript`
+    pub mode: String,
+    /// Best match first; each has an HTML-escaped `snippet` with matches wrapped in <mark></mark> and its `rank`.
+    /// In transcript mode each has `segments` (start_time, end_time, speaker_id, snippet, deep_link) instead
     pub assets: Vec<serde_json::Value>,
-    pub graph: serde_json::Value,
-    pub total_results: i32,
+    /// All matches, not just this page
+    pub total_results: i64,
+    pub offset: i64,
+    pub max_results: i64,
+    pub next_offset: Option<i64>,
+    /// Buckets of `{value, count}` over all matches: asset_type, source_system, status, language,
+    /// keywords, topics, and `created` (`{interval, buckets}`, bucket values are start dates).
+    /// Absent in transcript mode
+    pub facets: Option<serde_json::Value>,
+    pub query: String,
 }
 
 #[derive(utoipa::ToSchema)]
@@ -154,7 +202,10 @@ pub struct ControllerStatusResponse {
 pub struct ApiKeyResponse {
     pub api_key: String,
     pub key_id: String,
-    pub expires_in: String,
+    pub key_prefix: String,
+    pub expires_at: Option<String>,
+    pub rate_limit_per_minute: Option<u32>,
+    pub rotated_from: Option<String>,
     pub warning: String,
 }
 
@@ -178,9 +229,24 @@ pub struct SSOCallbackResponse {
 }
 
 #[derive(utoipa::ToSchema)]
+pub struct TokenRefreshResponse {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+}
+
+/// Body of every error response (see `api::error::ApiError`)
+#[derive(serde::Serialize, utoipa::ToSchema)]
 pub struct ErrorResponse {
     pub error: String,
     pub message: String,
     pub status_code: i32,
+    /// Also sent as the `x-request-id` response header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Fields that failed validation
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<FieldError>,
 }
 
@@ -8,28 +8,80 @@
 // I-FR-15: Lifecycle management
 
 use axum::{
+    middleware::from_fn_with_state,
     routing::{get, post, put},
     Router,
 };
-use crate::db::DbPool;
+use crate::api::AppState;
+use crate::middleware::authorization::require_permission;
+use crate::models::permission::Permission;
 
-pub fn create_admin_routes(db_pool: DbPool) -> Router {
+pub fn create_admin_routes(state: AppState) -> Router {
     Router::new()
         // I-FR-09: Controller monitoring
-        .route("/api/controllers/status", get(crate::api::handlers::admin::get_controller_status))
-        .route("/api/controllers/metrics", get(crate::api::handlers::admin::get_controller_metrics))
+        .route(
+            "/api/controllers/status",
+            get(crate::api::handlers::admin::get_controller_status)
+                .route_layer(from_fn_with_state(Permission::ReadAudit, require_permission)),
+        )
+        .route(
+            "/api/controllers/metrics",
+            get(crate::api::handlers::admin::get_controller_metrics)
+                .route_layer(from_fn_with_state(Permission::ReadAudit, require_permission)),
+        )
         // I-FR-05: Action records
-        .route("/api/audit/actions", get(crate::api::handlers::admin::get_action_records))
-        .route("/api/audit/actions/:asset_id", get(crate::api::handlers::admin::get_asset_actions))
+        .route(
+            "/api/audit/actions",
+            get(crate::api::handlers::admin::get_action_records)
+                .route_layer(from_fn_with_state(Permission::ReadAudit, require_permission)),
+        )
+        .route(
+            "/api/audit/actions/:asset_id",
+            get(crate::api::handlers::admin::get_asset_actions)
+                .route_layer(from_fn_with_state(Permission::ReadAudit, require_permission)),
+        )
         // I-FR-13: Rollback
-        .route("/api/rollback/:asset_id/:version_id", post(crate::api::handlers::admin::rollback_asset))
+        .route(
+            "/api/rollback/:asset_id/:version_id",
+            post(crate::api::handlers::admin::rollback_asset)
+                .route_layer(from_fn_with_state(Permission::RollbackAssets, require_permission)),
+        )
         // Configuration endpoints
-        .route("/api/config/sync-interval", get(crate::api::handlers::admin::get_sync_interval))
-        .route("/api/config/sync-interval", put(crate::api::handlers::admin::update_sync_interval))
-        .route("/api/config/logging-level", put(crate::api::handlers::admin::update_logging_level))
-        .route("/api/config/retry", put(crate::api::handlers::admin::update_retry_config))
+        .route(
+            "/api/config/sync-interval",
+            get(crate::api::handlers::admin::get_sync_interval)
+                .route_layer(from_fn_with_state(Permission::ReadAudit, require_permission)),
+        )
+        .route(
+            "/api/config/sync-interval",
+            put(crate::api::handlers::admin::update_sync_interval)
+                .route_layer(from_fn_with_state(Permission::ManageConfig, require_permission)),
+        )
+        .route(
+            "/api/config/logging-level",
+            put(crate::api::handlers::admin::update_logging_level)
+                .route_layer(from_fn_with_state(Permission::ManageConfig, require_permission)),
+        )
+        .route(
+            "/api/config/retry",
+            get(crate::api::handlers::admin::get_retry_config)
+                .route_layer(from_fn_with_state(Permission::ReadAudit, require_permission)),
+        )
+        .route(
+            "/api/config/retry",
+            put(crate::api::handlers::admin::update_retry_config)
+                .route_layer(from_fn_with_state(Permission::ManageConfig, require_permission)),
+        )
         // I-FR-15: Lifecycle management
-        .route("/api/lifecycle/rules", get(crate::api::handlers::admin::get_lifecycle_rules))
-        .route("/api/lifecycle/rules", post(crate::api::handlers::admin::create_lifecycle_rule))
-        .with_state(db_pool)
+        .route(
+            "/api/lifecycle/rules",
+            get(crate::api::handlers::admin::get_lifecycle_rules)
+                .route_layer(from_fn_with_state(Permission::ReadAudit, require_permission)),
+        )
+        .route(
+            "/api/lifecycle/rules",
+            post(crate::api::handlers::admin::create_lifecycle_rule)
+                .route_layer(from_fn_with_state(Permission::ManageConfig, require_permission)),
+        )
+        .with_state(state)
 }
\ No newline at end of file
@@ -4,23 +4,64 @@
 // I-FR-25: Rate limiting configuration
 
 use axum::{
+    middleware::from_fn_with_state,
     routing::{get, post, delete, put},
     Router,
 };
-use crate::db::DbPool;
+use crate::api::{AppState, AuthState};
+use crate::middleware::authorization::require_permission;
+use crate::models::permission::Permission;
 
-pub fn create_auth_routes(db_pool: DbPool) -> Router {
+/// Sign-in routes, reachable without credentials
+pub fn create_auth_routes(state: AuthState) -> Router {
     Router::new()
         // I-FR-21: Google Sign-In
         .route("/api/auth/google/login", get(crate::api::handlers::auth::google_login))
         .route("/api/auth/google/callback", get(crate::api::handlers::auth::google_callback))
+        // I-FR-21: Corporate SSO via OpenID Connect
+        .route("/api/auth/oidc/login", get(crate::api::handlers::auth::oidc_login))
+        .route("/api/auth/oidc/callback", get(crate::api::handlers::auth::oidc_callback))
+        .route("/api/auth/refresh", post(crate::api::handlers::auth::refresh_token))
+        .with_state(state)
+}
+
+/// Access governance routes, mounted behind authentication
+pub fn create_access_routes(state: AppState) -> Router {
+    Router::new()
+        // I-FR-21: Any signed-in user may end their own session
+        .route("/api/auth/logout", post(crate::api::handlers::auth::logout))
         // I-FR-23: API key management
-        .route("/api/access/keys", post(crate::api::handlers::auth::generate_api_key))
-        .route("/api/access/keys", get(crate::api::handlers::auth::list_api_keys))
-        .route("/api/access/keys/:key_id", delete(crate::api::handlers::auth::revoke_api_key))
-        .route("/api/access/permissions/:user_id", get(crate::api::handlers::auth::get_permissions))
+        .route(
+            "/api/access/keys",
+            post(crate::api::handlers::auth::generate_api_key)
+                .get(crate::api::handlers::auth::list_api_keys)
+                .route_layer(from_fn_with_state(Permission::ManageApiKeys, require_permission)),
+        )
+        .route(
+            "/api/access/keys/:key_id",
+            delete(crate::api::handlers::auth::revoke_api_key)
+                .route_layer(from_fn_with_state(Permission::ManageApiKeys, require_permission)),
+        )
+        .route(
+            "/api/access/keys/:key_id/rotate",
+            post(crate::api::handlers::auth::rotate_api_key)
+                .route_layer(from_fn_with_state(Permission::ManageApiKeys, require_permission)),
+        )
+        .route(
+            "/api/access/permissions/:user_id",
+            get(crate::api::handlers::auth::get_permissions)
+                .route_layer(from_fn_with_state(Permission::ReadAudit, require_permission)),
+        )
         // I-FR-25: Rate limiting
-        .route("/api/ratelimit/config", get(crate::api::handlers::auth::get_rate_limit_config))
-        .route("/api/ratelimit/config", put(crate::api::handlers::auth::update_rate_limit_config))
-        .with_state(db_pool)
-}
\ No newline at end of file
+        .route(
+            "/api/ratelimit/config",
+            get(crate::api::handlers::auth::get_rate_limit_config)
+                .route_layer(from_fn_with_state(Permission::ReadAudit, require_permission)),
+        )
+        .route(
+            "/api/ratelimit/config",
+            put(crate::api::handlers::auth::update_rate_limit_config)
+                .route_layer(from_fn_with_state(Permission::ManageConfig, require_permission)),
+        )
+        .with_state(state)
+}
@@ -3,15 +3,32 @@
 // I-FR-22: User friendly search and graph exploration
 
 use axum::{
+    middleware::from_fn_with_state,
     routing::{get, post},
     Router,
 };
 use crate::db::DbPool;
+use crate::middleware::authorization::require_permission;
+use crate::models::permission::Permission;
 
 pub fn create_graph_routes(db_pool: DbPool) -> Router {
     Router::new()
         // I-FR-22: Graph-based search
-        .route("/api/graph/search", post(crate::api::handlers::graph::search))
-        .route("/api/graph/relationships", post(crate::api::handlers::graph::get_relationships))
+        .route(
+            "/api/graph/search",
+            post(crate::api::handlers::graph::search)
+                .route_layer(from_fn_with_state(Permission::ReadAssets, require_permission)),
+        )
+        // I-FR-22: Typeahead
+        .route(
+            "/api/graph/suggest",
+            get(crate::api::handlers::graph::suggest)
+                .route_layer(from_fn_with_state(Permission::ReadAssets, require_permission)),
+        )
+        .route(
+            "/api/graph/relationships",
+            post(crate::api::handlers::graph::get_relationships)
+                .route_layer(from_fn_with_state(Permission::ReadAssets, require_permission)),
+        )
         .with_state(db_pool)
 }
\ No newline at end of file
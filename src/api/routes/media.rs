@@ -3,20 +3,73 @@
 // I-FR-31: Media upload and ingestion
 
 use axum::{
-    routing::{get, post},
+    middleware::from_fn_with_state,
+    extract::DefaultBodyLimit,
+    routing::{get, patch, post},
     Router,
 };
-use crate::db::DbPool;
+use crate::api::AppState;
+use crate::middleware::authorization::require_permission;
+use crate::models::permission::Permission;
+
+// Room for multipart boundaries, headers and the form's text fields on top of the file itself
+const MULTIPART_OVERHEAD_BYTES: u64 = 1024 * 1024;
+
+pub fn create_media_routes(state: AppState) -> Router {
+    let body_limit = state.storage.max_upload_bytes().saturating_add(MULTIPART_OVERHEAD_BYTES);
+    let body_limit = usize::try_from(body_limit).unwrap_or(usize::MAX);
 
-pub fn create_media_routes(db_pool: DbPool) -> Router {
     Router::new()
         // I-FR-29: API-based media submission (technical users)
-        .route("/api/media/submit", post(crate::api::handlers::media::submit_media))
+        .route(
+            "/api/media/submit",
+            post(crate::api::handlers::media::submit_media)
+                .route_layer(from_fn_with_state(Permission::SubmitMedia, require_permission)),
+        )
         // I-FR-31: Manual upload via UI (naive users)
-        .route("/api/media/upload", post(crate::api::handlers::media::upload_media))
+        .route(
+            "/api/media/upload",
+            post(crate::api::handlers::media::upload_media)
+                .route_layer(from_fn_with_state(Permission::UploadMedia, require_permission)),
+        )
+        // List assets with filters and cursor pagination
+        .route(
+            "/api/media",
+            get(crate::api::handlers::media::list_media)
+                .route_layer(from_fn_with_state(Permission::ReadAssets, require_permission)),
+        )
         // Get asset metadata
-        .route("/api/media/:asset_id", get(crate::api::handlers::media::get_media))
+        .route(
+            "/api/media/:asset_id",
+            get(crate::api::handlers::media::get_media)
+                .route_layer(from_fn_with_state(Permission::ReadAssets, require_permission)),
+        )
         // Download/Stream actual file (Video, Audio, Image, Text)
-        .route("/api/media/:asset_id/download", get(crate::api::handlers::media::download_media))
-        .with_state(db_pool)
-}
\ No newline at end of file
+        .route(
+            "/api/media/:asset_id/download",
+            get(crate::api::handlers::media::download_media)
+                .route_layer(from_fn_with_state(Permission::ReadAssets, require_permission)),
+        )
+        // I-FR-31: Resumable uploads for large files
+        .route(
+            "/api/media/uploads",
+            post(crate::api::handlers::uploads::create_upload)
+                .route_layer(from_fn_with_state(Permission::UploadMedia, require_permission)),
+        )
+        .route(
+            "/api/media/uploads/:upload_id",
+            patch(crate::api::handlers::uploads::append_chunk)
+                .head(crate::api::handlers::uploads::get_upload_offset)
+                .delete(crate::api::handlers::uploads::cancel_upload)
+                .route_layer(from_fn_with_state(Permission::UploadMedia, require_permission)),
+        )
+        .route(
+            "/api/media/uploads/:upload_id/complete",
+            post(crate::api::handlers::uploads::complete_upload)
+                .route_layer(from_fn_with_state(Permission::UploadMedia, require_permission)),
+        )
+        // Uploads are streamed, so the body limit is sized to storage.max_upload_bytes instead of
+        // axum's 2 MB default; the file itself is also checked against it as it arrives
+        .layer(DefaultBodyLimit::max(body_limit))
+        .with_state(state)
+}
@@ -4,18 +4,33 @@
 // I-FR-19: Conflict resolution
 
 use axum::{
+    middleware::from_fn_with_state,
     routing::{get, put, post},
     Router,
 };
 use crate::db::DbPool;
+use crate::middleware::authorization::require_permission;
+use crate::models::permission::Permission;
 
 pub fn create_metadata_routes(db_pool: DbPool) -> Router {
     Router::new()
         // I-FR-24: Query metadata
-        .route("/api/metadata/:asset_id", get(crate::api::handlers::metadata::get_metadata))
+        .route(
+            "/api/metadata/:asset_id",
+            get(crate::api::handlers::metadata::get_metadata)
+                .route_layer(from_fn_with_state(Permission::ReadMetadata, require_permission)),
+        )
         // I-FR-27: Edit metadata
-        .route("/api/metadata/:asset_id", put(crate::api::handlers::metadata::update_metadata))
+        .route(
+            "/api/metadata/:asset_id",
+            put(crate::api::handlers::metadata::update_metadata)
+                .route_layer(from_fn_with_state(Permission::WriteMetadata, require_permission)),
+        )
         // I-FR-19: Conflict resolution
-        .route("/api/metadata/:asset_id/resolve-conflict", post(crate::api::handlers::metadata::resolve_conflict))
+        .route(
+            "/api/metadata/:asset_id/resolve-conflict",
+            post(crate::api::handlers::metadata::resolve_conflict)
+                .route_layer(from_fn_with_state(Permission::WriteMetadata, require_permission)),
+        )
         .with_state(db_pool)
 }
\ No newline at end of file
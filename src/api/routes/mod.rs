@@ -5,4 +5,6 @@ pub mod metadata;
 pub mod workflow;
 pub mod graph;
 pub mod admin;
+pub mod users;
 pub mod auth;
+pub mod saved_searches;
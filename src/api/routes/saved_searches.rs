@@ -0,0 +1,34 @@
+// Saved search routes
+// I-FR-22: Saved searches belong to the caller; reading assets is all they need
+
+use axum::{
+    middleware::from_fn_with_state,
+    routing::get,
+    Router,
+};
+use crate::db::DbPool;
+use crate::middleware::authorization::require_permission;
+use crate::models::permission::Permission;
+
+pub fn create_saved_search_routes(db_pool: DbPool) -> Router {
+    Router::new()
+        .route(
+            "/api/saved-searches",
+            get(crate::api::handlers::saved_searches::list_saved_searches)
+                .post(crate::api::handlers::saved_searches::create_saved_search)
+                .route_layer(from_fn_with_state(Permission::ReadAssets, require_permission)),
+        )
+        .route(
+            "/api/saved-searches/:id",
+            get(crate::api::handlers::saved_searches::get_saved_search)
+                .put(crate::api::handlers::saved_searches::update_saved_search)
+                .delete(crate::api::handlers::saved_searches::delete_saved_search)
+                .route_layer(from_fn_with_state(Permission::ReadAssets, require_permission)),
+        )
+        .route(
+            "/api/saved-searches/:id/matches",
+            get(crate::api::handlers::saved_searches::get_saved_search_matches)
+                .route_layer(from_fn_with_state(Permission::ReadAssets, require_permission)),
+        )
+        .with_state(db_pool)
+}
@@ -0,0 +1,41 @@
+// User management routes
+// I-FR-23: Roles and account lifecycle, restricted to manage:users
+
+use axum::{
+    middleware::from_fn_with_state,
+    routing::{get, post, put},
+    Router,
+};
+use crate::db::DbPool;
+use crate::middleware::authorization::require_permission;
+use crate::models::permission::Permission;
+
+pub fn create_user_routes(db_pool: DbPool) -> Router {
+    Router::new()
+        .route(
+            "/api/admin/users",
+            get(crate::api::handlers::users::list_users)
+                .route_layer(from_fn_with_state(Permission::ManageUsers, require_permission)),
+        )
+        .route(
+            "/api/admin/users/:user_id",
+            get(crate::api::handlers::users::get_user)
+                .route_layer(from_fn_with_state(Permission::ManageUsers, require_permission)),
+        )
+        .route(
+            "/api/admin/users/:user_id/role",
+            put(crate::api::handlers::users::update_user_role)
+                .route_layer(from_fn_with_state(Permission::ManageUsers, require_permission)),
+        )
+        .route(
+            "/api/admin/users/:user_id/disable",
+            post(crate::api::handlers::users::disable_user)
+                .route_layer(from_fn_with_state(Permission::ManageUsers, require_permission)),
+        )
+        .route(
+            "/api/admin/users/:user_id/enable",
+            post(crate::api::handlers::users::enable_user)
+                .route_layer(from_fn_with_state(Permission::ManageUsers, require_permission)),
+        )
+        .with_state(db_pool)
+}
@@ -3,19 +3,42 @@
 // I-FR-32: Technical UI for AI workflow creation
 
 use axum::{
+    middleware::from_fn_with_state,
     routing::{get, post, put},
     Router,
 };
 use crate::db::DbPool;
+use crate::middleware::authorization::require_permission;
+use crate::models::permission::Permission;
 
 pub fn create_workflow_routes(db_pool: DbPool) -> Router {
     Router::new()
         // I-FR-26: Get workflow status
-        .route("/api/workflow/status/:asset_id", get(crate::api::handlers::workflow::get_workflow_status))
-        .route("/api/jobs/:job_id/status", get(crate::api::handlers::workflow::get_job_status))
-        .route("/api/jobs/:job_id/retry", post(crate::api::handlers::workflow::retry_job))
+        .route(
+            "/api/workflow/status/:asset_id",
+            get(crate::api::handlers::workflow::get_workflow_status)
+                .route_layer(from_fn_with_state(Permission::ReadJobs, require_permission)),
+        )
+        .route(
+            "/api/jobs/:job_id/status",
+            get(crate::api::handlers::workflow::get_job_status)
+                .route_layer(from_fn_with_state(Permission::ReadJobs, require_permission)),
+        )
+        .route(
+            "/api/jobs/:job_id/retry",
+            post(crate::api::handlers::workflow::retry_job)
+                .route_layer(from_fn_with_state(Permission::ManageWorkflows, require_permission)),
+        )
         // I-FR-32: Create/manage workflows
-        .route("/api/workflows", post(crate::api::handlers::workflow::create_workflow))
-        .route("/api/workflows", get(crate::api::handlers::workflow::list_workflows))
+        .route(
+            "/api/workflows",
+            post(crate::api::handlers::workflow::create_workflow)
+                .route_layer(from_fn_with_state(Permission::ManageWorkflows, require_permission)),
+        )
+        .route(
+            "/api/workflows",
+            get(crate::api::handlers::workflow::list_workflows)
+                .route_layer(from_fn_with_state(Permission::ReadJobs, require_permission)),
+        )
         .with_state(db_pool)
 }
\ No newline at end of file
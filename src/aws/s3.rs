@@ -2,10 +2,18 @@
 // I-FR-02: File storage with hash-based deduplication
 
 use aws_sdk_s3::{Client as S3Client, primitives::ByteStream};
+use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
 use aws_config::SdkConfig;
+use crate::services::storage::{ObjectStat, StorageBackend, StorageReader, UploadWriter};
+use crate::utils::http_range::ByteRange;
 use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use std::path::Path;
 
+// Objects larger than one part go through multipart upload (S3 minimum part size is 5 MB)
+const MULTIPART_PART_SIZE: usize = 8 * 1024 * 1024;
+
 pub struct S3Service {
     client: S3Client,
     bucket: String,
@@ -63,3 +71,187 @@ impl S3Service {
         format!("{}/{}/{}", prefix, timestamp, filename)
     }
 }
+
+/// Buffers one part at a time; switches to multipart upload once the first part fills
+pub struct S3UploadWriter {
+    client: S3Client,
+    bucket: String,
+    key: String,
+    buffer: Vec<u8>,
+    upload_id: Option<String>,
+    parts: Vec<CompletedPart>,
+}
+
+impl S3UploadWriter {
+    fn new(client: S3Client, bucket: String, key: String) -> Self {
+        Self {
+            client,
+            bucket,
+            key,
+            buffer: Vec::with_capacity(MULTIPART_PART_SIZE),
+            upload_id: None,
+            parts: Vec::new(),
+        }
+    }
+
+    async fn upload_part(&mut self, data: Vec<u8>) -> Result<()> {
+        let upload_id = match &self.upload_id {
+            Some(id) => id.clone(),
+            None => {
+                let created = self.client
+                    .create_multipart_upload()
+                    .bucket(&self.bucket)
+                    .key(&self.key)
+                    .send()
+                    .await?;
+                let id = created.upload_id()
+                    .ok_or_else(|| anyhow::anyhow!("S3 did not return an upload id"))?
+                    .to_string();
+                self.upload_id = Some(id.clone());
+                id
+            }
+        };
+
+        let part_number = self.parts.len() as i32 + 1;
+        let response = self.client
+            .upload_part()
+            .bucket(&self.bucket)
+            .key(&self.key)
+            .upload_id(&upload_id)
+            .part_number(part_number)
+            .body(ByteStream::from(data))
+            .send()
+            .await?;
+
+        self.parts.push(
+            CompletedPart::builder()
+                .part_number(part_number)
+                .set_e_tag(response.e_tag().map(|t| t.to_string()))
+                .build(),
+        );
+        Ok(())
+    }
+
+    async fn complete(&mut self) -> Result<()> {
+        if !self.buffer.is_empty() || self.parts.is_empty() {
+            let last = std::mem::take(&mut self.buffer);
+            self.upload_part(last).await?;
+        }
+
+        let upload_id = self.upload_id.clone().unwrap_or_default();
+        self.client
+            .complete_multipart_upload()
+            .bucket(&self.bucket)
+            .key(&self.key)
+            .upload_id(upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(std::mem::take(&mut self.parts)))
+                    .build(),
+            )
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UploadWriter for S3UploadWriter {
+    async fn write(&mut self, chunk: &[u8]) -> Result<()> {
+        self.buffer.extend_from_slice(chunk);
+        while self.buffer.len() >= MULTIPART_PART_SIZE {
+            let rest = self.buffer.split_off(MULTIPART_PART_SIZE);
+            let part = std::mem::replace(&mut self.buffer, rest);
+            self.upload_part(part).await?;
+        }
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<String> {
+        if self.upload_id.is_none() {
+            // Small object: a single PUT is cheaper than a one-part multipart upload
+            let body = ByteStream::from(std::mem::take(&mut self.buffer));
+            self.client.put_object().bucket(&self.bucket).key(&self.key).body(body).send().await?;
+        } else if let Err(e) = self.complete().await {
+            let uri = format!("s3://{}/{}", self.bucket, self.key);
+            self.abort().await.ok();
+            return Err(e.context(format!("Failed to complete multipart upload of {}", uri)));
+        }
+
+        Ok(format!("s3://{}/{}", self.bucket, self.key))
+    }
+
+    async fn abort(self: Box<Self>) -> Result<()> {
+        if let Some(upload_id) = &self.upload_id {
+            self.client
+                .abort_multipart_upload()
+                .bucket(&self.bucket)
+                .key(&self.key)
+                .upload_id(upload_id)
+                .send()
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Split an `s3://bucket/key` URI into bucket and key
+fn parse_s3_uri(uri: &str) -> Result<(&str, &str)> {
+    uri.strip_prefix("s3://")
+        .and_then(|rest| rest.split_once('/'))
+        .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Invalid S3 URI: {}", uri))
+}
+
+#[async_trait]
+impl StorageBackend for S3Service {
+    fn scheme(&self) -> &'static str {
+        "s3"
+    }
+
+    async fn put(&self, category: &str, filename: &str, data: Vec<u8>) -> Result<String> {
+        let mut writer = self.writer(category, filename).await?;
+        writer.write(&data).await?;
+        writer.finish().await
+    }
+
+    // The bucket comes from the URI, so objects written under an earlier bucket setting stay readable
+    async fn get(&self, uri: &str) -> Result<Vec<u8>> {
+        let (bucket, key) = parse_s3_uri(uri)?;
+        let response = self.client.get_object().bucket(bucket).key(key).send().await?;
+        Ok(response.body.collect().await?.into_bytes().to_vec())
+    }
+
+    async fn delete(&self, uri: &str) -> Result<()> {
+        let (bucket, key) = parse_s3_uri(uri)?;
+        self.client.delete_object().bucket(bucket).key(key).send().await?;
+        Ok(())
+    }
+
+    async fn stat(&self, uri: &str) -> Result<ObjectStat> {
+        let (bucket, key) = parse_s3_uri(uri)?;
+        let response = self.client.head_object().bucket(bucket).key(key).send().await?;
+        Ok(ObjectStat {
+            size: response.content_length().unwrap_or_default().max(0) as u64,
+            last_modified: response.last_modified()
+                .and_then(|t| DateTime::<Utc>::from_timestamp(t.secs(), t.subsec_nanos())),
+        })
+    }
+
+    async fn stream(&self, uri: &str, range: Option<ByteRange>) -> Result<StorageReader> {
+        let (bucket, key) = parse_s3_uri(uri)?;
+        let response = self.client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .set_range(range.map(|r| format!("bytes={}-{}", r.start, r.end)))
+            .send()
+            .await?;
+        Ok(Box::pin(response.body.into_async_read()))
+    }
+
+    async fn writer(&self, category: &str, filename: &str) -> Result<Box<dyn UploadWriter>> {
+        let key = self.generate_key(category, filename);
+        Ok(Box::new(S3UploadWriter::new(self.client.clone(), self.bucket.clone(), key)))
+    }
+}
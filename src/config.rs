@@ -3,8 +3,12 @@
 // I-FR-12: Configurable logging levels
 // I-FR-16: User-configurable retry mechanisms
 
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
+// Up to this fraction is taken off each retry delay, so jobs that failed together spread out
+const RETRY_JITTER_RATIO: f64 = 0.1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub database: DatabaseConfig,
@@ -13,6 +17,8 @@ pub struct AppConfig {
     pub logging: LoggingConfig,
     pub retry: RetryConfig,
     pub security: SecurityConfig,
+    pub queue: QueueConfig,
+    pub storage: StorageConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +36,17 @@ pub struct AwsConfig {
     pub s3_bucket_processed: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageConfig {
+    // Where new files are written: "local" or "s3" (S3 uses aws.s3_bucket_staging)
+    pub backend: String,
+    pub local_path: String,
+    pub max_upload_bytes: u64,
+    // Resumable uploads are staged on local disk until complete
+    pub upload_staging_path: String,
+    pub upload_session_ttl_hours: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncConfig {
     // I-FR-01: Configurable sync interval (default 15 minutes)
@@ -39,6 +56,22 @@ pub struct SyncConfig {
     pub omnystudio_interval_minutes: Option<u64>,
 }
 
+impl SyncConfig {
+    // Configured interval for a controller, falling back to the default
+    pub fn interval_for(&self, controller_name: &str) -> u64 {
+        let specific = if controller_name.starts_with("Brightcove") {
+            self.brightcove_interval_minutes
+        } else if controller_name.starts_with("Cloudinary") {
+            self.cloudinary_interval_minutes
+        } else if controller_name.starts_with("Omnystudio") {
+            self.omnystudio_interval_minutes
+        } else {
+            None
+        };
+        specific.unwrap_or(self.default_interval_minutes)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoggingConfig {
     // I-FR-12: Configurable logging levels (critical, error, warning, info)
@@ -55,15 +88,171 @@ pub struct RetryConfig {
     pub backoff_multiplier: f64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueConfig {
+    // I-FR-03/I-FR-08: Durable job queue shared by all instances
+    pub worker_concurrency: usize,
+    pub lease_seconds: u64,
+    pub heartbeat_seconds: u64,
+    pub poll_interval_seconds: u64,
+}
+
+impl RetryConfig {
+    // Apply the fields present in a JSON object (per-job or stored global overrides)
+    pub fn with_overrides(&self, overrides: &serde_json::Value) -> Self {
+        let mut config = self.clone();
+        if let Some(v) = overrides.get("max_attempts").and_then(|v| v.as_u64()) {
+            config.max_attempts = v as u32;
+        }
+        if let Some(v) = overrides.get("initial_interval_seconds").and_then(|v| v.as_u64()) {
+            config.initial_interval_seconds = v;
+        }
+        if let Some(v) = overrides.get("max_interval_seconds").and_then(|v| v.as_u64()) {
+            config.max_interval_seconds = v;
+        }
+        if let Some(v) = overrides.get("backoff_multiplier").and_then(|v| v.as_f64()) {
+            config.backoff_multiplier = v;
+        }
+        config
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts == 0 {
+            return Err("max_attempts must be at least 1".to_string());
+        }
+        if self.initial_interval_seconds == 0 {
+            return Err("initial_interval_seconds must be at least 1".to_string());
+        }
+        if self.backoff_multiplier < 1.0 {
+            return Err("backoff_multiplier must be at least 1.0".to_string());
+        }
+        if self.initial_interval_seconds > self.max_interval_seconds {
+            return Err("initial_interval_seconds cannot exceed max_interval_seconds".to_string());
+        }
+        Ok(())
+    }
+
+    // Delay before retry number `attempt` (1-based), capped at max_interval_seconds, less jitter
+    pub fn delay_for_attempt(&self, attempt: u32) -> std::time::Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let secs = self.initial_interval_seconds as f64 * self.backoff_multiplier.powi(exponent);
+        let jitter = rand::thread_rng().gen_range(0.0..=RETRY_JITTER_RATIO);
+        std::time::Duration::from_secs_f64(secs.min(self.max_interval_seconds as f64) * (1.0 - jitter))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecurityConfig {
     pub jwt_secret: String,
     pub sso_provider: String,
-    pub rate_limit_per_minute: u32,
+    pub rate_limit: RateLimitConfig, // I-FR-25: Startup defaults; stored overrides apply on top
+    pub oidc: Option<OidcConfig>, // I-FR-21: Corporate SSO, enabled when OIDC_ISSUER_URL is set
+}
+
+// I-FR-25: Requests per minute per user (JWT) or per API key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    pub default_limit_per_minute: u32, // Callers whose role is unknown
+    pub per_role: RoleRateLimits,
+    pub backend: String, // "memory" (per instance) or "postgres" (one budget shared by all instances)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleRateLimits {
+    pub admin: u32,
+    pub content_manager: u32,
+    pub editor: u32,
+    pub developer: u32,
+    pub viewer: u32,
+}
+
+impl RateLimitConfig {
+    // Apply the fields present in a JSON object (stored or submitted overrides)
+    pub fn with_overrides(&self, overrides: &serde_json::Value) -> Self {
+        let mut config = self.clone();
+        let limit = |v: &serde_json::Value| v.as_u64().map(|n| n.min(u32::MAX as u64) as u32);
+        if let Some(v) = overrides.get("default_limit_per_minute").and_then(limit) {
+            config.default_limit_per_minute = v;
+        }
+        if let Some(per_role) = overrides.get("per_role") {
+            let roles = &mut config.per_role;
+            for (name, slot) in [
+                ("admin", &mut roles.admin),
+                ("content_manager", &mut roles.content_manager),
+                ("editor", &mut roles.editor),
+                ("developer", &mut roles.developer),
+                ("viewer", &mut roles.viewer),
+            ] {
+                if let Some(v) = per_role.get(name).and_then(limit) {
+                    *slot = v;
+                }
+            }
+        }
+        config
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let roles = &self.per_role;
+        let limits = [self.default_limit_per_minute, roles.admin, roles.content_manager, roles.editor, roles.developer, roles.viewer];
+        if limits.contains(&0) {
+            return Err("rate limits must be at least 1 request per minute".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcConfig {
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    pub groups_claim: String,
+    // (group, role name) pairs; the first pair whose group the user belongs to decides the role.
+    // Users in none of the groups keep their current role (new users start as viewers).
+    pub group_roles: Vec<(String, String)>,
+}
+
+impl OidcConfig {
+    fn from_env() -> Option<Self> {
+        let issuer_url = std::env::var("OIDC_ISSUER_URL").ok()?;
+        let client_id = std::env::var("OIDC_CLIENT_ID").ok()?;
+
+        Some(OidcConfig {
+            issuer_url,
+            client_id,
+            client_secret: std::env::var("OIDC_CLIENT_SECRET").ok(),
+            redirect_uri: std::env::var("OIDC_REDIRECT_URI")
+                .unwrap_or_else(|_| "http://localhost:3000/api/auth/oidc/callback".to_string()),
+            scopes: std::env::var("OIDC_SCOPES")
+                .unwrap_or_else(|_| "openid email profile".to_string())
+                .split_whitespace()
+                .map(|s| s.to_string())
+                .collect(),
+            groups_claim: std::env::var("OIDC_GROUPS_CLAIM")
+                .unwrap_or_else(|_| "groups".to_string()),
+            // e.g. OIDC_GROUP_ROLES="media-admins=Admin,media-editors=Editor"
+            group_roles: std::env::var("OIDC_GROUP_ROLES")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(group, role)| (group.trim().to_string(), role.trim().to_string()))
+                .collect(),
+        })
+    }
 }
 
 impl AppConfig {
     pub fn load() -> anyhow::Result<Self> {
+        let max_upload_mb = std::env::var("MAX_UPLOAD_SIZE_MB")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(5 * 1024); // 5 GB
+        let max_upload_bytes = max_upload_mb
+            .checked_mul(1024 * 1024)
+            .ok_or_else(|| anyhow::anyhow!("MAX_UPLOAD_SIZE_MB={} overflows a byte count", max_upload_mb))?;
+
         // Load from environment variables or config file
         Ok(AppConfig {
             database: DatabaseConfig {
@@ -87,10 +276,19 @@ impl AppConfig {
                     .unwrap_or_else(|_| "mediacorp-ai-processed".to_string()),
             },
             sync: SyncConfig {
-                default_interval_minutes: 15, // I-FR-01: Default 15 minutes
-                brightcove_interval_minutes: None,
-                cloudinary_interval_minutes: None,
-                omnystudio_interval_minutes: None,
+                default_interval_minutes: std::env::var("SYNC_DEFAULT_INTERVAL_MINUTES")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(15), // I-FR-01: Default 15 minutes
+                brightcove_interval_minutes: std::env::var("BRIGHTCOVE_SYNC_INTERVAL_MINUTES")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok()),
+                cloudinary_interval_minutes: std::env::var("CLOUDINARY_SYNC_INTERVAL_MINUTES")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok()),
+                omnystudio_interval_minutes: std::env::var("OMNYSTUDIO_SYNC_INTERVAL_MINUTES")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok()),
             },
             logging: LoggingConfig {
                 level: std::env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string()),
@@ -107,8 +305,123 @@ impl AppConfig {
                     .unwrap_or_else(|_| "change-me-in-production".to_string()),
                 sso_provider: std::env::var("SSO_PROVIDER")
                     .unwrap_or_else(|_| "mediacorp-sso".to_string()),
-                rate_limit_per_minute: 100, // I-FR-25: Rate limiting
+                rate_limit: RateLimitConfig {
+                    default_limit_per_minute: std::env::var("RATE_LIMIT_PER_MINUTE")
+                        .ok()
+                        .and_then(|v| v.parse::<u32>().ok())
+                        .unwrap_or(100),
+                    per_role: RoleRateLimits {
+                        admin: 1000,
+                        content_manager: 200,
+                        editor: 100,
+                        developer: 500,
+                        viewer: 50,
+                    },
+                    backend: std::env::var("RATE_LIMIT_BACKEND")
+                        .unwrap_or_else(|_| "memory".to_string())
+                        .to_lowercase(),
+                },
+                oidc: OidcConfig::from_env(),
+            },
+            queue: QueueConfig {
+                worker_concurrency: std::env::var("JOB_WORKER_CONCURRENCY")
+                    .ok()
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .unwrap_or(4),
+                lease_seconds: std::env::var("JOB_LEASE_SECONDS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(300),
+                heartbeat_seconds: std::env::var("JOB_HEARTBEAT_SECONDS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(30),
+                poll_interval_seconds: std::env::var("JOB_POLL_INTERVAL_SECONDS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(5),
+            },
+            storage: StorageConfig {
+                backend: std::env::var("STORAGE_BACKEND")
+                    .unwrap_or_else(|_| "local".to_string())
+                    .to_lowercase(),
+                local_path: std::env::var("LOCAL_STORAGE_PATH")
+                    .unwrap_or_else(|_| "./local_storage".to_string()),
+                max_upload_bytes,
+                upload_staging_path: std::env::var("UPLOAD_STAGING_PATH")
+                    .unwrap_or_else(|_| "./upload_staging".to_string()),
+                upload_session_ttl_hours: std::env::var("UPLOAD_SESSION_TTL_HOURS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(24),
             },
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn retry() -> RetryConfig {
+        RetryConfig {
+            max_attempts: 3,
+            initial_interval_seconds: 5,
+            max_interval_seconds: 300,
+            backoff_multiplier: 2.0,
+        }
+    }
+
+    fn assert_jittered(delay: std::time::Duration, full_secs: f64) {
+        let secs = delay.as_secs_f64();
+        assert!(
+            secs <= full_secs && secs >= full_secs * (1.0 - RETRY_JITTER_RATIO),
+            "{}s is outside the jitter bounds of {}s", secs, full_secs
+        );
+    }
+
+    #[test]
+    fn delay_grows_exponentially_within_jitter_bounds() {
+        let retry = retry();
+        for _ in 0..100 {
+            assert_jittered(retry.delay_for_attempt(1), 5.0);
+            assert_jittered(retry.delay_for_attempt(2), 10.0);
+            assert_jittered(retry.delay_for_attempt(4), 40.0);
+        }
+    }
+
+    #[test]
+    fn delay_is_capped_at_max_interval() {
+        let retry = retry();
+        for attempt in [7, 10, 64, u32::MAX] {
+            assert_jittered(retry.delay_for_attempt(attempt), 300.0);
+        }
+    }
+
+    #[test]
+    fn overrides_apply_only_the_fields_present() {
+        let retry = retry().with_overrides(&json!({ "max_attempts": 5, "backoff_multiplier": 3.0, "unknown": 1 }));
+
+        assert_eq!(retry.max_attempts, 5);
+        assert_eq!(retry.backoff_multiplier, 3.0);
+        assert_eq!(retry.initial_interval_seconds, 5);
+        assert_eq!(retry.max_interval_seconds, 300);
+        // Values of the wrong type are ignored rather than zeroed
+        assert_eq!(retry.with_overrides(&json!({ "max_attempts": "ten" })).max_attempts, 5);
+    }
+
+    #[test]
+    fn zero_and_inverted_values_are_rejected() {
+        assert!(retry().validate().is_ok());
+        for overrides in [
+            json!({ "max_attempts": 0 }),
+            json!({ "initial_interval_seconds": 0 }),
+            json!({ "max_interval_seconds": 0 }),
+            json!({ "initial_interval_seconds": 600 }),
+            json!({ "backoff_multiplier": 0.5 }),
+        ] {
+            assert!(retry().with_overrides(&overrides).validate().is_err(), "{} should be rejected", overrides);
+        }
+    }
+}
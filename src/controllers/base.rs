@@ -8,6 +8,7 @@
 // I-FR-13: Rollback mechanisms
 // I-FR-16: Retry mechanisms
 
+use crate::config::RetryConfig;
 use crate::models::action_record::ActionRecord;
 use crate::models::asset::Asset;
 use anyhow::Result;
@@ -45,13 +46,12 @@ pub struct SyncResult {
 // I-FR-16: Retry mechanism with exponential backoff
 pub async fn retry_with_backoff<F, T>(
     operation: F,
-    max_attempts: u32,
-    initial_delay: std::time::Duration,
+    retry: &RetryConfig,
 ) -> Result<T>
 where
     F: Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T>> + Send>> + Send + Sync,
 {
-    let mut delay = initial_delay;
+    let max_attempts = retry.max_attempts.max(1);
     
     for attempt in 1..=max_attempts {
         match operation().await {
@@ -66,9 +66,9 @@ where
                     error!("Operation failed after {} attempts: {}", max_attempts, e);
                     return Err(e);
                 }
+                let delay = retry.delay_for_attempt(attempt);
                 warn!("Attempt {} failed: {}. Retrying in {:?}...", attempt, e, delay);
                 tokio::time::sleep(delay).await;
-                delay = delay * 2; // Exponential backoff
             }
         }
     }
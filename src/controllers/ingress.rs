@@ -5,28 +5,280 @@
 // I-FR-04: Parallel execution
 
 use crate::controllers::base::{Controller, SyncResult};
+use crate::db::DbPool;
+use crate::db::repositories::{
+    action_repository::ActionRepository, asset_repository::AssetRepository,
+    controller_repository::ControllerRepository, workflow_repository::WorkflowRepository,
+};
+use crate::external::brightcove::BrightcoveClient;
 use crate::models::action_record::{ActionRecord, ActionStatus, ActionType, Direction};
-use crate::models::asset::{Asset, AssetStatus, AssetType, SourceSystem};
+use crate::models::asset::{Asset, AssetStatus, SourceSystem};
+use crate::models::workflow::{JobStatus, ProcessingJob};
+use crate::services::content_sniffing::{self, DetectedContent};
+use crate::services::{preprocessing_service, storage::StorageRegistry};
 use crate::utils::hash;
 use anyhow::Result;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::{error, warn};
 use uuid::Uuid;
 
+// Metadata fields kept in step with Brightcove on every sync
+const SYNCED_FIELDS: [&str; 4] = ["title", "description", "tags", "custom_fields"];
+
+/// A Brightcove source rendition streamed into storage
+struct StoredSource {
+    uri: String,
+    file_hash: String,
+    file_size: i64,
+    content: DetectedContent,
+}
+
+/// What syncing one Brightcove video did
+enum Ingested {
+    Created,
+    Updated,
+    Unchanged,
+}
+
 // Brightcove Ingress Controller
 pub struct BrightcoveIngressController {
     name: String,
     version: String,
-    api_key: String,
-    account_id: String,
+    client: BrightcoveClient,
+    db_pool: DbPool,
+    storage: Arc<StorageRegistry>,
+    page_size: usize,
 }
 
 impl BrightcoveIngressController {
-    pub fn new(api_key: String, account_id: String) -> Self {
+    pub fn new(api_key: String, account_id: String, db_pool: DbPool, storage: Arc<StorageRegistry>) -> Self {
+        Self::with_client(BrightcoveClient::new(api_key, account_id), db_pool, storage)
+    }
+
+    pub fn with_client(client: BrightcoveClient, db_pool: DbPool, storage: Arc<StorageRegistry>) -> Self {
         Self {
             name: "BrightcoveIngressController".to_string(),
             version: "v2.3.1".to_string(),
-            api_key,
-            account_id,
+            client,
+            db_pool,
+            storage,
+            page_size: 100,
+        }
+    }
+
+    /// Register a single Brightcove video, or sync the metadata of one ingested earlier.
+    /// Only videos not yet ingested are downloaded.
+    async fn ingest_video(&self, video: &Value) -> Result<Ingested> {
+        let video_id = video.get("id")
+            .and_then(|id| id.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Brightcove video without id"))?;
+        let mut metadata = Self::video_metadata(video);
+
+        // Any change to a video brings it back in the next sync; an ingested one keeps its media
+        if let Some(existing) = AssetRepository::find_by_source(&self.db_pool, &SourceSystem::Brightcove, video_id).await? {
+            return self.sync_metadata(existing, video_id, &metadata).await;
+        }
+
+        let src = self.client.get_video_download_url(video_id).await?;
+        // Brightcove also hosts audio-only media, so the type comes from the content
+        let source_name = src.split('?').next()
+            .and_then(|path| path.rsplit('/').next())
+            .unwrap_or(video_id);
+        let asset_uuid = Uuid::new_v4();
+        let source = self.store_source(asset_uuid, video_id, &src, source_name).await?;
+
+        // I-FR-02: Hash-based deduplication against media from every source
+        if self.check_duplicate(&source.file_hash).await?.is_some() {
+            if let Err(e) = self.storage.delete(&source.uri).await {
+                error!("Failed to remove duplicate download of Brightcove video {}: {}", video_id, e);
+            }
+            return Ok(Ingested::Unchanged);
+        }
+
+        let StoredSource { uri: storage_path, file_hash, file_size, content } = source;
+        content.record_mismatch(&mut metadata, source_name);
+        let filename = format!("{}.{}", video_id, content.format.to_lowercase());
+
+        let asset = Asset {
+            uuid: asset_uuid,
+            asset_type: content.asset_type.clone(),
+            asset_name: video.get("name")
+                .and_then(|n| n.as_str())
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| filename.clone()),
+            source_system: SourceSystem::Brightcove,
+            source_id: Some(video_id.to_string()),
+            file_path: storage_path,
+            file_hash,
+            file_size,
+            // Brightcove reports duration in milliseconds
+            duration: video.get("duration").and_then(|d| d.as_i64()).map(|ms| (ms / 1000) as i32),
+            format: content.format.to_string(),
+            status: AssetStatus::Queued,
+            version: 1,
+            version_id: Uuid::new_v4(),
+            enriched_metadata: metadata,
+            operational_tags: None,
+            created_at: Utc::now(),
+            updated_at: None,
+            processing_completed_at: None,
+            uploaded_by: None,
+            technical_metadata: None,
+            mime_type: Some(content.mime_type.to_string()),
+        };
+
+        AssetRepository::create(&self.db_pool, &asset).await?;
+
+        // I-FR-33: Queue the asset for AI processing
+        let workflow_name = preprocessing_service::determine_workflow(&asset)
+            .unwrap_or_else(|_| "STANDARD_WORKFLOW".to_string());
+        let job = ProcessingJob {
+            job_id: Uuid::new_v4(),
+            asset_uuid,
+            workflow_name,
+            status: JobStatus::Queued,
+            progress_percentage: 0,
+            capabilities_completed: vec![],
+            capabilities_failed: vec![],
+            error_message: None,
+            created_at: Utc::now(),
+            started_at: None,
+            completed_at: None,
+            estimated_completion: Some(Utc::now() + chrono::Duration::minutes(30)),
+            retry_count: 0,
+            retry_config: None,
+        };
+        WorkflowRepository::create_job(&self.db_pool, &job).await?;
+
+        self.log_action(self.action_record(
+            Some(asset_uuid),
+            ActionStatus::Success,
+            json!({ "video_id": video_id, "source_url": src, "job_id": job.job_id }),
+        )).await?;
+
+        Ok(Ingested::Created)
+    }
+
+    /// Stream a source rendition into storage, hashing it on the way (I-FR-02). Only the first
+    /// `SNIFF_BYTES` are buffered, to identify the content before anything is stored.
+    async fn store_source(&self, asset_uuid: Uuid, video_id: &str, src: &str, source_name: &str) -> Result<StoredSource> {
+        let mut download = std::pin::pin!(self.client.download_source(src).await?);
+
+        let mut head = Vec::with_capacity(content_sniffing::SNIFF_BYTES);
+        let mut finished = false;
+        while head.len() < content_sniffing::SNIFF_BYTES {
+            match download.next().await {
+                Some(chunk) => head.extend_from_slice(chunk?.as_ref()),
+                None => {
+                    finished = true;
+                    break;
+                }
+            }
+        }
+        let content = content_sniffing::sniff(&head[..head.len().min(content_sniffing::SNIFF_BYTES)], source_name)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported content type for Brightcove video {}", video_id))?;
+
+        // Prefix with the asset id so a re-published video never overwrites an earlier asset's file
+        let filename = format!("{}-{}.{}", asset_uuid, video_id, content.format.to_lowercase());
+        let mut writer = self.storage.writer("brightcove", &filename).await?;
+        let mut hasher = hash::StreamingHasher::new();
+        let mut file_size = 0u64;
+
+        let copied: Result<()> = async {
+            hasher.update(&head);
+            writer.write(&head).await?;
+            file_size += head.len() as u64;
+            while !finished {
+                let Some(chunk) = download.next().await else { break };
+                let chunk = chunk?;
+                hasher.update(chunk.as_ref());
+                writer.write(chunk.as_ref()).await?;
+                file_size += chunk.as_ref().len() as u64;
+            }
+            Ok(())
+        }.await;
+
+        if let Err(e) = copied {
+            if let Err(abort_error) = writer.abort().await {
+                error!("Failed to discard partial download of Brightcove video {}: {}", video_id, abort_error);
+            }
+            return Err(e);
+        }
+
+        Ok(StoredSource {
+            uri: writer.finish().await?,
+            file_hash: hasher.finalize(),
+            file_size: file_size as i64,
+            content,
+        })
+    }
+
+    /// The fields Brightcove owns, in enriched_metadata form
+    fn video_metadata(video: &Value) -> Value {
+        let mut metadata = json!({});
+        if let Some(name) = video.get("name").and_then(|n| n.as_str()) {
+            metadata["title"] = json!(name);
+        }
+        if let Some(description) = video.get("description").and_then(|d| d.as_str()) {
+            metadata["description"] = json!(description);
+        }
+        if let Some(tags) = video.get("tags").filter(|t| t.is_array()) {
+            metadata["tags"] = tags.clone();
+        }
+        if let Some(custom_fields) = video.get("custom_fields").filter(|c| c.is_object()) {
+            metadata["custom_fields"] = custom_fields.clone();
+        }
+        metadata
+    }
+
+    /// Bring the Brightcove-owned fields of an ingested asset up to date, keeping AI results
+    async fn sync_metadata(&self, asset: Asset, video_id: &str, synced: &Value) -> Result<Ingested> {
+        let mut current = asset.enriched_metadata.as_object().cloned().unwrap_or_default();
+
+        let mut changed = Vec::new();
+        for field in SYNCED_FIELDS {
+            let value = synced.get(field);
+            if current.get(field) == value {
+                continue;
+            }
+            match value {
+                Some(value) => current.insert(field.to_string(), value.clone()),
+                None => current.remove(field),
+            };
+            changed.push(field);
+        }
+        if changed.is_empty() {
+            return Ok(Ingested::Unchanged);
+        }
+
+        AssetRepository::update_source_metadata(&self.db_pool, asset.uuid, &Value::Object(current)).await?;
+        self.log_action(self.action_record(
+            Some(asset.uuid),
+            ActionStatus::Success,
+            json!({ "video_id": video_id, "updated_fields": changed }),
+        )).await?;
+
+        Ok(Ingested::Updated)
+    }
+
+    fn action_record(&self, asset_uuid: Option<Uuid>, status: ActionStatus, metadata: Value) -> ActionRecord {
+        ActionRecord {
+            record_id: Uuid::new_v4(),
+            asset_uuid,
+            action_type: ActionType::Ingress,
+            direction: Direction::Inbound,
+            controller_name: self.name.clone(),
+            controller_version: self.version.clone(),
+            source_system: Some("BRIGHTCOVE".to_string()),
+            destination_system: Some("AI_PROCESSING_PIPELINE".to_string()),
+            status,
+            timestamp: Utc::now(),
+            metadata: Some(metadata),
+            user_id: None,
         }
     }
 }
@@ -42,27 +294,66 @@ impl Controller for BrightcoveIngressController {
     }
 
     async fn sync(&self) -> Result<SyncResult> {
-        // I-FR-01: Sync metadata and media assets
-        // Implementation: Fetch videos from Brightcove API
-        // I-FR-03: Asynchronous - use async/await
-        // I-FR-04: Can run in parallel with other controllers
-        
-        todo!("Implement Brightcove API sync")
+        // I-FR-01: Sync metadata and media assets updated since the last run
+        let since = ControllerRepository::get_last_sync_at(&self.db_pool, &self.name).await?
+            .unwrap_or(DateTime::<Utc>::UNIX_EPOCH);
+
+        let mut result = SyncResult {
+            assets_processed: 0,
+            assets_created: 0,
+            assets_updated: 0,
+            assets_skipped: 0,
+            errors: Vec::new(),
+        };
+
+        let mut offset = 0;
+        loop {
+            let videos = self.client.get_videos_since(since, self.page_size, offset).await?;
+
+            for video in &videos {
+                result.assets_processed += 1;
+                match self.ingest_video(video).await {
+                    Ok(Ingested::Created) => result.assets_created += 1,
+                    Ok(Ingested::Updated) => result.assets_updated += 1,
+                    Ok(Ingested::Unchanged) => result.assets_skipped += 1,
+                    Err(e) => {
+                        let video_id = video.get("id").and_then(|id| id.as_str()).unwrap_or("unknown");
+                        let error_msg = format!("Error ingesting Brightcove video {}: {}", video_id, e);
+                        warn!("{}", error_msg);
+                        if let Err(log_error) = self.log_action(self.action_record(
+                            None,
+                            ActionStatus::Failed,
+                            json!({ "video_id": video_id, "error": e.to_string() }),
+                        )).await {
+                            error!("Failed to record the failed ingest of Brightcove video {}: {}", video_id, log_error);
+                        }
+                        result.errors.push(error_msg);
+                    }
+                }
+            }
+
+            if videos.len() < self.page_size {
+                break;
+            }
+            offset += videos.len();
+        }
+
+        Ok(result)
     }
 
     async fn check_duplicate(&self, file_hash: &str) -> Result<Option<Asset>> {
         // I-FR-02: Hash-based deduplication
-        todo!("Check database for existing asset with hash")
+        AssetRepository::find_by_hash(&self.db_pool, file_hash).await
     }
 
     async fn log_action(&self, action: ActionRecord) -> Result<()> {
         // I-FR-05: Generate action records
-        todo!("Insert action record into database")
+        ActionRepository::create(&self.db_pool, &action).await.map(|_| ())
     }
 
     async fn rollback(&self, asset_uuid: Uuid, version: i32) -> Result<()> {
         // I-FR-13: Rollback mechanisms
-        todo!("Rollback asset to specified version")
+        AssetRepository::rollback_to_version(&self.db_pool, asset_uuid, version, None).await
     }
 }
 
@@ -113,3 +404,149 @@ impl Controller for CloudinaryIngressController {
 }
 
 // Add other ingress controllers: Omnystudio, OneCMS, MissyS3, DaletS3
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::db_pool;
+    use crate::models::asset::AssetType;
+    use crate::services::local_storage::LocalStorageService;
+    use axum::{extract::{Path, State}, routing::get, Json, Router};
+    use sqlx::PgPool;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct MockCms {
+        base_url: String,
+        videos: Arc<Mutex<Vec<Value>>>,
+        downloads: Arc<AtomicUsize>,
+    }
+
+    async fn spawn_cms() -> MockCms {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let cms = MockCms {
+            base_url: format!("http://{}", listener.local_addr().unwrap()),
+            ..Default::default()
+        };
+        let app = Router::new()
+            .route("/v1/accounts/acme/videos", get(videos))
+            .route("/v1/accounts/acme/videos/:id/sources", get(sources))
+            .route("/media/:file", get(media))
+            .with_state(cms.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        cms
+    }
+
+    async fn videos(State(cms): State<MockCms>) -> Json<Vec<Value>> {
+        Json(cms.videos.lock().unwrap().clone())
+    }
+
+    // Podcasts are audio-only renditions
+    async fn sources(State(cms): State<MockCms>, Path(id): Path<String>) -> Json<Value> {
+        let file = if id.starts_with("podcast") { format!("{}.m4a", id) } else { format!("{}.mp4", id) };
+        Json(json!([{ "src": format!("{}/media/{}", cms.base_url, file) }]))
+    }
+
+    // Re-publications ("copy-of-<id>") serve the original's bytes; "long-" renditions span many chunks
+    async fn media(State(cms): State<MockCms>, Path(file): Path<String>) -> Vec<u8> {
+        cms.downloads.fetch_add(1, Ordering::SeqCst);
+        let file = file.trim_start_matches("copy-of-");
+        let brand: &[u8] = if file.ends_with(".m4a") { b"M4A " } else { b"isom" };
+        let mut data = b"\0\0\0\x18ftyp".to_vec();
+        data.extend_from_slice(brand);
+        data.extend_from_slice(&[0; 12]);
+        data.extend_from_slice(file.as_bytes());
+        if file.starts_with("long-") {
+            data.resize(4 * 1024 * 1024, 7);
+        }
+        data
+    }
+
+    fn stored_files(dir: &std::path::Path) -> usize {
+        std::fs::read_dir(dir).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .map(|path| if path.is_dir() { stored_files(&path) } else { 1 })
+            .sum()
+    }
+
+    fn controller(pool: &DbPool, cms: &MockCms, storage_dir: &std::path::Path) -> BrightcoveIngressController {
+        let storage = Arc::new(StorageRegistry::new(Arc::new(LocalStorageService::new(
+            Some(storage_dir.to_string_lossy().to_string()),
+        ))));
+        let client = BrightcoveClient::with_base_url("token".to_string(), "acme".to_string(), &cms.base_url);
+        BrightcoveIngressController::with_client(client, pool.clone(), storage)
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn syncs_new_videos_once_and_updates_changed_metadata(pool: PgPool) {
+        let pool = db_pool(pool);
+        let cms = spawn_cms().await;
+        let storage_dir = std::env::temp_dir().join(format!("brightcove-test-{}", Uuid::new_v4()));
+        let controller = controller(&pool, &cms, &storage_dir);
+
+        *cms.videos.lock().unwrap() = vec![
+            json!({ "id": "clip-1", "name": "Budget vote", "tags": ["politics"], "duration": 61000 }),
+            json!({ "id": "podcast-1", "name": "Morning briefing" }),
+        ];
+        let result = controller.sync().await.unwrap();
+        assert_eq!((result.assets_created, result.assets_updated, result.assets_skipped), (2, 0, 0), "{:?}", result.errors);
+        assert_eq!(cms.downloads.load(Ordering::SeqCst), 2);
+
+        let clip = AssetRepository::find_by_source(&pool, &SourceSystem::Brightcove, "clip-1").await.unwrap().unwrap();
+        assert!(matches!(clip.asset_type, AssetType::Video));
+        assert_eq!(clip.duration, Some(61));
+        let podcast = AssetRepository::find_by_source(&pool, &SourceSystem::Brightcove, "podcast-1").await.unwrap().unwrap();
+        assert!(matches!(podcast.asset_type, AssetType::Audio));
+        assert_eq!(podcast.format, "M4A");
+
+        // AI results stored in the meantime must survive a metadata sync
+        let mut enriched = clip.enriched_metadata.clone();
+        enriched["keywords"] = json!(["budget"]);
+        AssetRepository::update_source_metadata(&pool, clip.uuid, &enriched).await.unwrap();
+
+        *cms.videos.lock().unwrap() = vec![
+            json!({ "id": "clip-1", "name": "Budget vote, final", "tags": ["politics"], "duration": 61000 }),
+            json!({ "id": "podcast-1", "name": "Morning briefing" }),
+        ];
+        let result = controller.sync().await.unwrap();
+        assert_eq!((result.assets_created, result.assets_updated, result.assets_skipped), (0, 1, 1), "{:?}", result.errors);
+        assert_eq!(cms.downloads.load(Ordering::SeqCst), 2, "known videos are not downloaded again");
+
+        let clip = AssetRepository::get_by_uuid(&pool, clip.uuid).await.unwrap().unwrap();
+        assert_eq!(clip.enriched_metadata["title"], "Budget vote, final");
+        assert_eq!(clip.enriched_metadata["keywords"], json!(["budget"]));
+
+        std::fs::remove_dir_all(storage_dir).ok();
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn large_sources_are_streamed_and_duplicates_discarded(pool: PgPool) {
+        let pool = db_pool(pool);
+        let cms = spawn_cms().await;
+        let storage_dir = std::env::temp_dir().join(format!("brightcove-test-{}", Uuid::new_v4()));
+        let controller = controller(&pool, &cms, &storage_dir);
+
+        *cms.videos.lock().unwrap() = vec![
+            json!({ "id": "long-1", "name": "Full council session" }),
+            json!({ "id": "copy-of-long-1", "name": "Full council session (repost)" }),
+        ];
+        let result = controller.sync().await.unwrap();
+        assert_eq!((result.assets_created, result.assets_skipped), (1, 1), "{:?}", result.errors);
+
+        let asset = AssetRepository::find_by_source(&pool, &SourceSystem::Brightcove, "long-1").await.unwrap().unwrap();
+        let stored = controller.storage.get(&asset.file_path).await.unwrap();
+        assert_eq!(stored.len(), 4 * 1024 * 1024);
+        assert_eq!(asset.file_size, stored.len() as i64);
+        assert_eq!(asset.file_hash, hash::calculate_file_hash(stored.as_slice()).unwrap());
+        assert_eq!(asset.format, "MP4");
+
+        // The repost was downloaded, found to be a duplicate and removed from storage
+        assert!(AssetRepository::find_by_source(&pool, &SourceSystem::Brightcove, "copy-of-long-1").await.unwrap().is_none());
+        assert_eq!(stored_files(&storage_dir), 1);
+
+        std::fs::remove_dir_all(storage_dir).ok();
+    }
+}
@@ -3,16 +3,18 @@
 
 use crate::controllers::base::{Controller, SyncResult};
 use crate::models::action_record::{ActionRecord, ActionStatus, ActionType, Direction};
-use crate::models::asset::{Asset, AssetStatus, AssetType, SourceSystem};
+use crate::models::asset::{Asset, AssetStatus, SourceSystem};
 use crate::db::DbPool;
-use crate::db::repositories::{asset_repository::AssetRepository, action_repository::ActionRepository};
+use crate::db::repositories::{asset_repository::AssetRepository, action_repository::ActionRepository, workflow_repository::WorkflowRepository};
+use crate::models::workflow::{JobStatus, ProcessingJob};
 use crate::utils::hash;
-use crate::services::{local_storage::LocalStorageService, ai_processing::AIProcessingService};
-use anyhow::Result;
+use crate::services::{content_sniffing, preprocessing_service, storage::StorageRegistry};
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use uuid::Uuid;
 use std::path::Path;
 use std::fs;
+use std::sync::Arc;
 use chrono::Utc;
 
 pub struct LocalFileIngressController {
@@ -20,15 +22,17 @@ pub struct LocalFileIngressController {
     version: String,
     watch_directory: String,
     db_pool: DbPool,
+    storage: Arc<StorageRegistry>,
 }
 
 impl LocalFileIngressController {
-    pub fn new(watch_directory: String, db_pool: DbPool) -> Self {
+    pub fn new(watch_directory: String, db_pool: DbPool, storage: Arc<StorageRegistry>) -> Self {
         Self {
             name: "LocalFileIngressController".to_string(),
             version: "v1.0.0".to_string(),
             watch_directory,
             db_pool,
+            storage,
         }
     }
     
@@ -79,26 +83,24 @@ impl LocalFileIngressController {
             return Ok(false); // Skip duplicate
         }
         
-        // Determine asset type from extension
-        let asset_type = if let Some(ext) = file_path.extension().and_then(|e| e.to_str()) {
-            match ext.to_lowercase().as_str() {
-                "mp4" | "avi" | "mov" => AssetType::Video,
-                "mp3" | "wav" | "m4a" => AssetType::Audio,
-                "png" | "jpg" | "jpeg" | "gif" => AssetType::Image,
-                "pdf" | "txt" | "doc" | "docx" | "html" | "json" => AssetType::Text,
-                _ => AssetType::Text,
-            }
-        } else {
-            AssetType::Text
-        };
-        
-        // Move file to storage
         let filename = file_path.file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("unknown");
         
-        let local_storage = LocalStorageService::new(None);
-        let storage_path = local_storage.save_file("ingress", filename, file_data).await?;
+        // Determine asset type from the content, not the extension
+        let head = &file_data[..file_data.len().min(content_sniffing::SNIFF_BYTES)];
+        let content = content_sniffing::sniff(head, filename)
+            .ok_or_else(|| anyhow!("Unsupported content type for {}", filename))?;
+        if content.extension_mismatch {
+            tracing::warn!("{} has the extension of a different format than its {} content", filename, content.format);
+        }
+        let asset_type = content.asset_type.clone();
+        let mut enriched_metadata = serde_json::json!({});
+        content.record_mismatch(&mut enriched_metadata, filename);
+        let file_size = file_data.len() as i64;
+        
+        // Move file to storage
+        let storage_path = self.storage.put("ingress", filename, file_data).await?;
         
         // Create asset
         let asset_uuid = Uuid::new_v4();
@@ -110,21 +112,20 @@ impl LocalFileIngressController {
             source_id: Some(file_path.to_string_lossy().to_string()),
             file_path: storage_path.clone(),
             file_hash,
-            file_size: fs::metadata(file_path)?.len() as i64,
+            file_size,
             duration: None,
-            format: file_path.extension()
-                .and_then(|e| e.to_str())
-                .map(|s| s.to_uppercase())
-                .unwrap_or_else(|| "UNKNOWN".to_string()),
+            format: content.format.to_string(),
             status: AssetStatus::Queued,
             version: 1,
             version_id: Uuid::new_v4(),
-            enriched_metadata: serde_json::json!({}),
+            enriched_metadata,
             operational_tags: None,
             created_at: Utc::now(),
             updated_at: None,
             processing_completed_at: None,
             uploaded_by: None,
+            technical_metadata: None,
+            mime_type: Some(content.mime_type.to_string()),
         };
         
         AssetRepository::create(&self.db_pool, &asset).await?;
@@ -150,57 +151,26 @@ impl LocalFileIngressController {
         
         ActionRepository::create(&self.db_pool, &action).await?;
         
-        // Trigger AI processing in background
-        let db_pool_clone = self.db_pool.clone();
-        let asset_uuid_clone = asset_uuid;
-        let storage_path_clone = storage_path.clone();
-        let asset_type_str = format!("{:?}", asset_type.clone());
-        tokio::spawn(async move {
-            // Update status to processing
-            AssetRepository::update_status(
-                &db_pool_clone, 
-                asset_uuid_clone, 
-                AssetStatus::Processing,
-                None
-            ).await.ok();
-            
-            // Process with AI
-            let ai_results = AIProcessingService::process_asset(
-                &storage_path_clone,
-                &asset_type_str,
-            ).await;
-            
-            if let Ok(enriched_metadata) = ai_results {
-                // Get current asset
-                if let Ok(Some(asset)) = AssetRepository::get_by_uuid(&db_pool_clone, asset_uuid_clone).await {
-                    // Merge AI results
-                    let mut metadata = asset.enriched_metadata.clone();
-                    if let Some(obj) = enriched_metadata.as_object() {
-                        for (key, value) in obj {
-                            metadata[key] = value.clone();
-                        }
-                    }
-                    
-                    // Update asset
-                    AssetRepository::update_metadata(
-                        &db_pool_clone,
-                        asset_uuid_clone,
-                        metadata,
-                        asset.version,
-                        asset.version_id,
-                        Uuid::new_v4(),
-                    ).await.ok();
-                    
-                    // Update status with completion timestamp
-                    AssetRepository::update_status(
-                        &db_pool_clone, 
-                        asset_uuid_clone, 
-                        AssetStatus::Processed,
-                        Some(Utc::now())
-                    ).await.ok();
-                }
-            }
-        });
+        // I-FR-33: Queue for AI processing by the job worker pool
+        let workflow_name = preprocessing_service::determine_workflow(&asset)
+            .unwrap_or_else(|_| "STANDARD_WORKFLOW".to_string());
+        let job = ProcessingJob {
+            job_id: Uuid::new_v4(),
+            asset_uuid,
+            workflow_name,
+            status: JobStatus::Queued,
+            progress_percentage: 0,
+            capabilities_completed: vec![],
+            capabilities_failed: vec![],
+            error_message: None,
+            created_at: Utc::now(),
+            started_at: None,
+            completed_at: None,
+            estimated_completion: Some(Utc::now() + chrono::Duration::minutes(30)),
+            retry_count: 0,
+            retry_config: None,
+        };
+        WorkflowRepository::create_job(&self.db_pool, &job).await?;
         
         Ok(true)
     }
@@ -0,0 +1,64 @@
+// Cross-instance advisory locks
+// Session-level Postgres advisory locks held on a connection taken out of the pool for as
+// long as the lock is held. A lock that is dropped without `release` (a panic, a cancelled
+// request) closes its connection instead of returning it to the pool, which ends the database
+// session and with it the lock, so it can never be left behind on a pooled connection.
+
+use crate::db::DbPool;
+use anyhow::Result;
+use sqlx::pool::PoolConnection;
+use sqlx::{Postgres, Transaction};
+
+pub struct AdvisoryLock {
+    conn: Option<PoolConnection<Postgres>>,
+    key: String,
+}
+
+impl AdvisoryLock {
+    /// Take the lock named `key` if no other session holds it
+    pub async fn try_acquire(pool: &DbPool, key: String) -> Result<Option<Self>> {
+        let mut conn = pool.acquire().await?;
+        let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock(hashtext($1))")
+            .bind(&key)
+            .fetch_one(&mut *conn)
+            .await?;
+
+        Ok(locked.then(|| Self { conn: Some(conn), key }))
+    }
+
+    /// Take the lock named `key` for the rest of `tx` if no other session holds it. It is
+    /// released when the transaction ends, and conflicts with `try_acquire` on the same key.
+    pub async fn try_acquire_for_transaction(tx: &mut Transaction<'_, Postgres>, key: &str) -> Result<bool> {
+        let locked = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock(hashtext($1))")
+            .bind(key)
+            .fetch_one(&mut **tx)
+            .await?;
+
+        Ok(locked)
+    }
+
+    /// Unlock and hand the connection back to the pool
+    pub async fn release(mut self) -> Result<()> {
+        if let Some(mut conn) = self.conn.take() {
+            let unlocked = sqlx::query_scalar::<_, bool>("SELECT pg_advisory_unlock(hashtext($1))")
+                .bind(&self.key)
+                .fetch_one(&mut *conn)
+                .await;
+            if !matches!(unlocked, Ok(true)) {
+                // Not unlocked for sure: end the session rather than pool a connection that may hold it
+                drop(conn.detach());
+            }
+            unlocked?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for AdvisoryLock {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            tracing::warn!("Advisory lock {} dropped without release; closing its connection", self.key);
+            drop(conn.detach());
+        }
+    }
+}
@@ -2,8 +2,11 @@
 // I-FR-05: Action records storage
 // I-FR-18: Version control storage
 
+pub mod advisory_lock;
 pub mod connection;
 pub mod repositories;
+#[cfg(test)]
+pub mod test_support;
 
 pub use connection::*;
 pub use repositories::*;
@@ -4,12 +4,18 @@
 use crate::db::DbPool;
 use crate::models::action_record::ActionRecord;
 use anyhow::Result;
+use sqlx::PgExecutor;
 use uuid::Uuid;
 
 pub struct ActionRepository;
 
 impl ActionRepository {
     pub async fn create(pool: &DbPool, action: &ActionRecord) -> Result<Uuid> {
+        Self::insert(pool.as_ref(), action).await
+    }
+
+    /// `create` inside a caller's transaction
+    pub async fn insert(executor: impl PgExecutor<'_>, action: &ActionRecord) -> Result<Uuid> {
         let record_id = sqlx::query_scalar::<_, Uuid>(
             r#"
             INSERT INTO action_records (
@@ -32,7 +38,7 @@ impl ActionRepository {
         .bind(action.timestamp)
         .bind(&action.metadata)
         .bind(&action.user_id)
-        .fetch_one(pool.as_ref())
+        .fetch_one(executor)
         .await?;
 
         Ok(record_id)
@@ -4,9 +4,12 @@
 // I-FR-19: Conflict detection
 
 use crate::db::DbPool;
-use crate::models::asset::{Asset, AssetStatus, AssetType, AssetVersion, SourceSystem};
+use crate::models::asset::{
+    Asset, AssetCursor, AssetFilter, AssetSortField, AssetSortValue, AssetStatus, SourceSystem,
+    TechnicalMetadata,
+};
 use anyhow::Result;
-use sqlx::FromRow;
+use sqlx::{Postgres, QueryBuilder};
 use uuid::Uuid;
 use chrono::Utc;
 
@@ -25,6 +28,19 @@ impl AssetRepository {
         Ok(asset)
     }
 
+    // I-FR-02: The asset already ingested from an item of a source system, if any
+    pub async fn find_by_source(pool: &DbPool, source_system: &SourceSystem, source_id: &str) -> Result<Option<Asset>> {
+        let asset = sqlx::query_as::<_, Asset>(
+            "SELECT * FROM assets WHERE source_system = $1 AND source_id = $2 LIMIT 1"
+        )
+        .bind(source_system)
+        .bind(source_id)
+        .fetch_optional(pool.as_ref())
+        .await?;
+
+        Ok(asset)
+    }
+
     pub async fn create(pool: &DbPool, asset: &Asset) -> Result<Uuid> {
         let uuid = sqlx::query_scalar::<_, Uuid>(
             r#"
@@ -32,9 +48,9 @@ impl AssetRepository {
                 uuid, asset_type, asset_name, source_system, source_id,
                 file_path, file_hash, file_size, duration, format, status,
                 version, version_id, enriched_metadata, operational_tags,
-                uploaded_by, created_at
+                uploaded_by, created_at, technical_metadata, mime_type
             ) VALUES (
-                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19
             ) RETURNING uuid
             "#
         )
@@ -55,6 +71,8 @@ impl AssetRepository {
         .bind(&asset.operational_tags)
         .bind(&asset.uploaded_by)
         .bind(asset.created_at)
+        .bind(&asset.technical_metadata)
+        .bind(&asset.mime_type)
         .fetch_one(pool.as_ref())
         .await?;
 
@@ -72,6 +90,72 @@ impl AssetRepository {
         Ok(asset)
     }
 
+    /// One page of assets matching `filter`, ordered by `sort` then uuid, starting after `cursor`
+    pub async fn list(
+        pool: &DbPool,
+        filter: &AssetFilter,
+        sort: AssetSortField,
+        descending: bool,
+        cursor: Option<&AssetCursor>,
+        limit: i64,
+    ) -> Result<Vec<Asset>> {
+        let sort_key = match sort {
+            AssetSortField::CreatedAt => "created_at",
+            AssetSortField::UpdatedAt => "COALESCE(updated_at, created_at)",
+            AssetSortField::AssetName => "asset_name",
+            AssetSortField::FileSize => "file_size",
+        };
+        let direction = if descending { "DESC" } else { "ASC" };
+
+        let mut query = QueryBuilder::<Postgres>::new("SELECT * FROM assets WHERE TRUE");
+        push_any_of(&mut query, "asset_type", &filter.asset_types);
+        push_any_of(&mut query, "source_system", &filter.source_systems);
+        push_any_of(&mut query, "status", &filter.statuses);
+        if let Some(uploaded_by) = filter.uploaded_by {
+            query.push(" AND uploaded_by = ").push_bind(uploaded_by);
+        }
+        if let Some(after) = filter.created_after {
+            query.push(" AND created_at >= ").push_bind(after);
+        }
+        if let Some(before) = filter.created_before {
+            query.push(" AND created_at < ").push_bind(before);
+        }
+        if let Some(after) = filter.updated_after {
+            query.push(" AND updated_at >= ").push_bind(after);
+        }
+        if let Some(before) = filter.updated_before {
+            query.push(" AND updated_at < ").push_bind(before);
+        }
+        // I-FR-30: Containment so the GIN indexes can serve tag and metadata filters
+        for (key, value) in &filter.operational_tags {
+            query.push(" AND operational_tags @> ").push_bind(serde_json::json!({ key: value }));
+        }
+        for (key, value) in &filter.metadata {
+            query.push(" AND (enriched_metadata @> ").push_bind(serde_json::json!({ key: value }))
+                .push(" OR enriched_metadata @> ").push_bind(serde_json::json!({ key: [value] }))
+                .push(")");
+        }
+
+        if let Some(cursor) = cursor {
+            query.push(format!(" AND ({}, uuid) {} (", sort_key, if descending { "<" } else { ">" }));
+            match &cursor.value {
+                AssetSortValue::Time(time) => query.push_bind(*time),
+                AssetSortValue::Text(text) => query.push_bind(text.clone()),
+                AssetSortValue::Number(number) => query.push_bind(*number),
+            };
+            query.push(", ").push_bind(cursor.uuid).push(")");
+        }
+
+        query.push(format!(" ORDER BY {} {}, uuid {} LIMIT ", sort_key, direction, direction))
+            .push_bind(limit);
+
+        let assets = query.build_query_as::<Asset>()
+            .fetch_all(pool.as_ref())
+            .await?;
+
+        Ok(assets)
+    }
+
     // I-FR-18: Create new version
     pub async fn create_version(
         pool: &DbPool,
@@ -147,6 +231,22 @@ impl AssetRepository {
         Ok(())
     }
 
+    /// Metadata re-synced from the source system. The new version_id makes edits based on
+    /// the previous metadata conflict (I-FR-19) instead of silently overwriting it.
+    pub async fn update_source_metadata(pool: &DbPool, asset_uuid: Uuid, enriched_metadata: &serde_json::Value) -> Result<()> {
+        sqlx::query(
+            "UPDATE assets SET enriched_metadata = $1, version_id = $2, updated_at = $3 WHERE uuid = $4"
+        )
+        .bind(enriched_metadata)
+        .bind(Uuid::new_v4())
+        .bind(Utc::now())
+        .bind(asset_uuid)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn update_status(
         pool: &DbPool,
         asset_uuid: Uuid,
@@ -189,11 +289,37 @@ impl AssetRepository {
         Ok(())
     }
 
+    // Record probed container details; duration and format follow the file rather than the client
+    pub async fn update_technical_metadata(
+        pool: &DbPool,
+        asset_uuid: Uuid,
+        technical_metadata: &TechnicalMetadata,
+    ) -> Result<()> {
+        let duration = technical_metadata.duration_seconds.map(|d| d.round() as i32);
+
+        sqlx::query(
+            r#"
+            UPDATE assets
+            SET technical_metadata = $1, duration = COALESCE($2, duration), format = $3, updated_at = NOW()
+            WHERE uuid = $4
+            "#
+        )
+        .bind(sqlx::types::Json(technical_metadata))
+        .bind(duration)
+        .bind(&technical_metadata.container)
+        .bind(asset_uuid)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
     // I-FR-13: Rollback to previous version
     pub async fn rollback_to_version(
         pool: &DbPool,
         asset_uuid: Uuid,
         version: i32,
+        updated_by: Option<Uuid>, // None for controller-initiated rollbacks
     ) -> Result<()> {
         // Get version snapshot
         let version_snapshot: serde_json::Value = sqlx::query_scalar(
@@ -208,12 +334,14 @@ impl AssetRepository {
         sqlx::query(
             r#"
             UPDATE assets
-            SET enriched_metadata = $1, version = $2, version_id = uuid_generate_v4()
-            WHERE uuid = $3
+            SET enriched_metadata = $1, version = $2, version_id = uuid_generate_v4(),
+                updated_at = NOW(), updated_by = $3
+            WHERE uuid = $4
             "#
         )
         .bind(version_snapshot)
         .bind(version)
+        .bind(updated_by)
         .bind(asset_uuid)
         .execute(pool.as_ref())
         .await?;
@@ -221,3 +349,19 @@ impl AssetRepository {
         Ok(())
     }
 }
+
+// `column IN (...)`; nothing is added when no values are given
+pub(crate) fn push_any_of<'a, T>(query: &mut QueryBuilder<'a, Postgres>, column: &str, values: &'a [T])
+where
+    &'a T: sqlx::Encode<'a, Postgres> + sqlx::Type<Postgres> + Send,
+{
+    if values.is_empty() {
+        return;
+    }
+    query.push(format!(" AND {} IN (", column));
+    let mut separated = query.separated(", ");
+    for value in values {
+        separated.push_bind(value);
+    }
+    separated.push_unseparated(")");
+}
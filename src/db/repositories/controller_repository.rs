@@ -0,0 +1,69 @@
+// Controller configuration repository
+// I-FR-01: Configurable sync intervals
+// I-FR-12: Configurable logging levels
+
+use crate::db::DbPool;
+use crate::db::advisory_lock::AdvisoryLock;
+use crate::models::controller::ControllerConfig;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+pub struct ControllerRepository;
+
+impl ControllerRepository {
+    // Register a controller with its default interval (existing rows are left untouched)
+    pub async fn ensure_config(pool: &DbPool, controller_name: &str, default_interval_minutes: i32) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO controller_configs (controller_name, sync_interval_minutes, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (controller_name) DO NOTHING
+            "#
+        )
+        .bind(controller_name)
+        .bind(default_interval_minutes)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_config(pool: &DbPool, controller_name: &str) -> Result<Option<ControllerConfig>> {
+        let config = sqlx::query_as::<_, ControllerConfig>(
+            "SELECT * FROM controller_configs WHERE controller_name = $1"
+        )
+        .bind(controller_name)
+        .fetch_optional(pool.as_ref())
+        .await?;
+
+        Ok(config)
+    }
+
+    pub async fn get_last_sync_at(pool: &DbPool, controller_name: &str) -> Result<Option<DateTime<Utc>>> {
+        let last_sync_at: Option<Option<DateTime<Utc>>> = sqlx::query_scalar(
+            "SELECT last_sync_at FROM controller_configs WHERE controller_name = $1"
+        )
+        .bind(controller_name)
+        .fetch_optional(pool.as_ref())
+        .await?;
+
+        Ok(last_sync_at.flatten())
+    }
+
+    pub async fn mark_synced(pool: &DbPool, controller_name: &str, synced_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            "UPDATE controller_configs SET last_sync_at = $1 WHERE controller_name = $2"
+        )
+        .bind(synced_at)
+        .bind(controller_name)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    // Advisory lock so only one instance syncs a controller at a time
+    pub async fn try_lock(pool: &DbPool, controller_name: &str) -> Result<Option<AdvisoryLock>> {
+        AdvisoryLock::try_acquire(pool, controller_name.to_string()).await
+    }
+}
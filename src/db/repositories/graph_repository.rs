@@ -3,9 +3,170 @@
 // I-FR-22: Graph search
 
 use crate::db::DbPool;
+use crate::db::repositories::asset_repository::push_any_of;
+use crate::models::asset::{AssetStatus, AssetType, SourceSystem};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgExecutor, Postgres, QueryBuilder};
 use uuid::Uuid;
 
+// Parsed once per statement and shared by the hit and facet queries
+const SEARCH_QUERY_CTE: &str = "WITH q AS (SELECT websearch_to_tsquery('english', ";
+
+/// One full-text search result
+#[derive(Debug, Clone, FromRow)]
+pub struct AssetSearchHit {
+    pub uuid: Uuid,
+    pub asset_name: String,
+    pub asset_type: AssetType,
+    pub enriched_metadata: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub rank: f32,
+    /// Best matching fragments, HTML-escaped, with matches wrapped in <mark></mark>
+    pub snippet: Option<String>,
+}
+
+/// Narrows a search; every field that is set must match (AND).
+/// Several values of one enum or language field match any of them (OR);
+/// several keywords or topics must all be present.
+/// Serializes in the shape of the search request's `filters` (as kept by saved searches).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchFilter {
+    #[serde(rename = "asset_type", skip_serializing_if = "Vec::is_empty")]
+    pub asset_types: Vec<AssetType>,
+    #[serde(rename = "source_system", skip_serializing_if = "Vec::is_empty")]
+    pub source_systems: Vec<SourceSystem>,
+    #[serde(rename = "status", skip_serializing_if = "Vec::is_empty")]
+    pub statuses: Vec<AssetStatus>,
+    #[serde(rename = "language", skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_after: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+/// Bucket width of the created_at histogram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateInterval {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl DateInterval {
+    pub fn from_name(name: &str) -> Option<DateInterval> {
+        match name {
+            "day" => Some(DateInterval::Day),
+            "week" => Some(DateInterval::Week),
+            "month" => Some(DateInterval::Month),
+            "year" => Some(DateInterval::Year),
+            _ => None,
+        }
+    }
+
+    /// The date_trunc field name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DateInterval::Day => "day",
+            DateInterval::Week => "week",
+            DateInterval::Month => "month",
+            DateInterval::Year => "year",
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct FacetBucket {
+    pub value: String,
+    pub count: i64,
+}
+
+/// Counts over every asset matching the query and filters, not just the returned page
+#[derive(Debug, Clone, Default)]
+pub struct SearchFacets {
+    pub asset_types: Vec<FacetBucket>,
+    pub source_systems: Vec<FacetBucket>,
+    pub statuses: Vec<FacetBucket>,
+    pub languages: Vec<FacetBucket>,
+    pub keywords: Vec<FacetBucket>,
+    pub topics: Vec<FacetBucket>,
+    /// Bucket start dates (YYYY-MM-DD, UTC), oldest first
+    pub created: Vec<FacetBucket>,
+}
+
+impl SearchFacets {
+    /// Every asset has exactly one type, so this is the number of matches
+    pub fn total(&self) -> i64 {
+        self.asset_types.iter().map(|bucket| bucket.count).sum()
+    }
+}
+
+/// What a typeahead suggestion refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestionKind {
+    Keyword,
+    Topic,
+    Contributor,
+    Asset, // Matched on the asset's title, or its name when untitled
+}
+
+impl SuggestionKind {
+    pub const ALL: [SuggestionKind; 4] = [
+        SuggestionKind::Keyword,
+        SuggestionKind::Topic,
+        SuggestionKind::Contributor,
+        SuggestionKind::Asset,
+    ];
+
+    pub fn from_name(name: &str) -> Option<SuggestionKind> {
+        match name {
+            "keyword" => Some(SuggestionKind::Keyword),
+            "topic" => Some(SuggestionKind::Topic),
+            "contributor" => Some(SuggestionKind::Contributor),
+            "asset" => Some(SuggestionKind::Asset),
+            _ => None,
+        }
+    }
+
+    // graph_nodes.node_type; assets are not graph nodes
+    fn node_type(&self) -> Option<&'static str> {
+        match self {
+            SuggestionKind::Keyword => Some("KEYWORD"),
+            SuggestionKind::Topic => Some("TOPIC"),
+            SuggestionKind::Contributor => Some("CONTRIBUTOR"),
+            SuggestionKind::Asset => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct Suggestion {
+    /// keyword, topic, contributor or asset
+    pub kind: String,
+    pub text: String,
+    /// Set for asset suggestions
+    pub asset_uuid: Option<Uuid>,
+    /// Assets tagged with the node; for an asset, the assets related to it
+    pub asset_count: i64,
+}
+
+/// A computed edge from the asset being indexed
+#[derive(Debug, Clone)]
+pub struct RelatedAsset {
+    pub asset_uuid: Uuid,
+    pub relationship_type: &'static str,
+    /// Always carries a `score` in 0..=1
+    pub data: serde_json::Value,
+}
+
 pub struct GraphRepository;
 
 impl GraphRepository {
@@ -16,6 +177,22 @@ impl GraphRepository {
         keywords: &[String],
         topics: &[String],
     ) -> Result<()> {
+        // Unlink keywords and topics the asset no longer has, so rebuilt edges do not keep them
+        sqlx::query(
+            r#"
+            DELETE FROM asset_graph_nodes agn
+            USING graph_nodes gn
+            WHERE agn.node_id = gn.node_id AND agn.asset_uuid = $1
+              AND ((gn.node_type = 'KEYWORD' AND gn.node_name <> ALL($2))
+                OR (gn.node_type = 'TOPIC' AND gn.node_name <> ALL($3)))
+            "#
+        )
+        .bind(asset_uuid)
+        .bind(keywords)
+        .bind(topics)
+        .execute(pool.as_ref())
+        .await?;
+
         // Create keyword nodes and relationships
         for keyword in keywords {
             // Get or create keyword node
@@ -75,43 +252,197 @@ impl GraphRepository {
         Ok(())
     }
 
-    // I-FR-22: Search graph
+    // I-FR-22: Ranked full-text search over title, tags, description, transcript and OCR text
+    // (see migrations/015_asset_search_vector.sql). Returns one page of hits, best first.
     pub async fn search(
         pool: &DbPool,
         query: &str,
-        asset_type: Option<&str>,
+        filter: &SearchFilter,
         limit: i64,
-    ) -> Result<Vec<Uuid>> {
-        let keywords: Vec<&str> = query.split_whitespace().collect();
-        
-        let assets = sqlx::query_scalar::<_, Uuid>(
+        offset: i64,
+    ) -> Result<Vec<AssetSearchHit>> {
+        let mut sql = search_query(query);
+        sql.push(", hits AS (SELECT a.*, ts_rank(a.search_vector, q.query) AS rank FROM q CROSS JOIN assets a");
+        sql.push(" WHERE a.search_vector @@ q.query");
+        push_search_filter(&mut sql, filter);
+        sql.push(" ORDER BY rank DESC, a.created_at DESC, a.uuid LIMIT ").push_bind(limit)
+            .push(" OFFSET ").push_bind(offset);
+        sql.push(
+            r#")
+            SELECT hits.uuid, hits.asset_name, hits.asset_type, hits.enriched_metadata, hits.created_at, hits.rank,
+                   -- Highlight only the returned page; ts_headline re-parses the whole text
+                   ts_headline('english',
+                       concat_ws(' ... ',
+                           hits.enriched_metadata->>'title',
+                           hits.enriched_metadata->>'description',
+                           coalesce(hits.enriched_metadata->>'transcript',
+                                    hits.enriched_metadata#>>'{text_recognition,transcript}'),
+                           (SELECT string_agg(text, ' ') FROM jsonb_array_elements_text(
+                               CASE WHEN jsonb_typeof(hits.enriched_metadata->'ocr_results') = 'array'
+                                    THEN hits.enriched_metadata->'ocr_results' ELSE '[]'::jsonb END
+                           ) AS ocr(text)),
+                           hits.asset_name
+                       ),
+                       q.query,
+                       "#
+        )
+        .push_bind(headline_options("MaxFragments=2, MaxWords=25, MinWords=8, FragmentDelimiter=\" ... \""))
+        .push(
             r#"
-            SELECT DISTINCT a.uuid
-            FROM assets a
-            INNER JOIN asset_graph_nodes agn ON a.uuid = agn.asset_uuid
-            INNER JOIN graph_nodes gn ON agn.node_id = gn.node_id
-            WHERE (
-                gn.node_name ILIKE ANY($1) OR
-                a.asset_name ILIKE ANY($1) OR
-                a.enriched_metadata::text ILIKE ANY($1)
+                   ) AS snippet
+            FROM hits, q
+            ORDER BY hits.rank DESC, hits.created_at DESC, hits.uuid
+            "#
+        );
+
+        let mut hits = sql.build_query_as::<AssetSearchHit>()
+            .fetch_all(pool.as_ref())
+            .await?;
+        for hit in &mut hits {
+            hit.snippet = hit.snippet.as_deref().map(highlight);
+        }
+
+        Ok(hits)
+    }
+
+    /// I-FR-22: Facet counts for a search; keyword and topic facets keep the `top` most frequent.
+    /// Every facet comes from one query over a shared set of matches.
+    pub async fn search_facets(
+        pool: &DbPool,
+        query: &str,
+        filter: &SearchFilter,
+        top: i64,
+        interval: DateInterval,
+    ) -> Result<SearchFacets> {
+        let mut sql = search_query(query);
+        sql.push(
+            ", matches AS (SELECT a.uuid, a.asset_type::text AS asset_type, a.source_system::text AS source_system, \
+             a.status::text AS status, a.enriched_metadata->>'language' AS language, \
+             to_char(date_trunc("
+        )
+        .push_bind(interval.as_str())
+        .push(", a.created_at AT TIME ZONE 'UTC'), 'YYYY-MM-DD') AS created FROM q CROSS JOIN assets a WHERE a.search_vector @@ q.query");
+        push_search_filter(&mut sql, filter);
+        sql.push(
+            "), nodes AS (\
+             SELECT lower(gn.node_type) AS facet, gn.node_name AS value, COUNT(*) AS count, \
+             ROW_NUMBER() OVER (PARTITION BY gn.node_type ORDER BY COUNT(*) DESC, gn.node_name) AS rank \
+             FROM matches m JOIN asset_graph_nodes agn ON agn.asset_uuid = m.uuid \
+             JOIN graph_nodes gn ON gn.node_id = agn.node_id \
+             WHERE gn.node_type IN ('KEYWORD', 'TOPIC') GROUP BY gn.node_type, gn.node_name) "
+        );
+        // Assets without a value for a column are not counted in its facet
+        for column in ["asset_type", "source_system", "status", "language", "created"] {
+            sql.push(format!(
+                "SELECT '{0}' AS facet, {0} AS value, COUNT(*) AS count FROM matches WHERE {0} IS NOT NULL GROUP BY {0} UNION ALL ",
+                column
+            ));
+        }
+        sql.push("SELECT facet, value, count FROM nodes WHERE rank <= ").push_bind(top)
+            .push(" ORDER BY 1, 3 DESC, 2");
+
+        let rows = sql.build_query_as::<(String, String, i64)>()
+            .fetch_all(pool.as_ref())
+            .await?;
+
+        let mut facets = SearchFacets::default();
+        for (facet, value, count) in rows {
+            let buckets = match facet.as_str() {
+                "asset_type" => &mut facets.asset_types,
+                "source_system" => &mut facets.source_systems,
+                "status" => &mut facets.statuses,
+                "language" => &mut facets.languages,
+                "keyword" => &mut facets.keywords,
+                "topic" => &mut facets.topics,
+                _ => &mut facets.created,
+            };
+            buckets.push(FacetBucket { value, count });
+        }
+        facets.created.sort_by(|a, b| a.value.cmp(&b.value));
+
+        Ok(facets)
+    }
+
+    /// I-FR-22: Typeahead over graph node names and asset titles.
+    /// Prefix matches come first, then the most linked, then the closest fuzzy match.
+    pub async fn suggest(
+        pool: &DbPool,
+        partial: &str,
+        kinds: &[SuggestionKind],
+        limit: i64,
+    ) -> Result<Vec<Suggestion>> {
+        let escaped = partial.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        let node_types: Vec<&str> = kinds.iter().filter_map(|kind| kind.node_type()).collect();
+        let include_assets = kinds.contains(&SuggestionKind::Asset);
+
+        let suggestions = sqlx::query_as::<_, Suggestion>(
+            r#"
+            WITH candidates AS (
+                SELECT lower(gn.node_type) AS kind, gn.node_name AS text, NULL::uuid AS asset_uuid,
+                       COUNT(agn.asset_uuid) AS asset_count
+                FROM graph_nodes gn
+                LEFT JOIN asset_graph_nodes agn ON agn.node_id = gn.node_id
+                WHERE gn.node_type = ANY($4)
+                  AND (gn.node_name ILIKE $3 OR $1 <% gn.node_name)
+                GROUP BY gn.node_id
+                UNION ALL
+                SELECT 'asset', titled.text, titled.uuid,
+                       (SELECT COUNT(*) FROM graph_relationships gr WHERE gr.target_asset_uuid = titled.uuid)
+                FROM (
+                    SELECT a.uuid, COALESCE(a.enriched_metadata->>'title', a.asset_name) AS text FROM assets a
+                ) titled
+                WHERE $5 AND (titled.text ILIKE $3 OR $1 <% titled.text)
             )
-            AND ($2::text IS NULL OR a.asset_type::text = $2)
-            ORDER BY a.created_at DESC
-            LIMIT $3
+            SELECT kind, text, asset_uuid, asset_count FROM candidates
+            ORDER BY text ILIKE $2 DESC, asset_count DESC, word_similarity($1, text) DESC, text
+            LIMIT $6
             "#
         )
-        .bind(keywords.iter().map(|k| format!("%{}%", k)).collect::<Vec<_>>())
-        .bind(asset_type)
+        .bind(partial)
+        .bind(format!("{}%", escaped))
+        .bind(format!("%{}%", escaped))
+        .bind(node_types)
+        .bind(include_assets)
         .bind(limit)
         .fetch_all(pool.as_ref())
         .await?;
 
-        Ok(assets)
+        Ok(suggestions)
+    }
+
+    /// Which of `searches` (id, query, filter) one asset matches; used to evaluate saved searches.
+    /// All searches are checked in a single round trip.
+    pub async fn matching_searches(
+        pool: &DbPool,
+        searches: &[(Uuid, String, SearchFilter)],
+        asset_uuid: Uuid,
+    ) -> Result<Vec<Uuid>> {
+        if searches.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut sql = QueryBuilder::new("");
+        for (i, (id, query, filter)) in searches.iter().enumerate() {
+            if i > 0 {
+                sql.push(" UNION ALL ");
+            }
+            sql.push("SELECT ").push_bind(*id)
+                .push("::uuid WHERE EXISTS (SELECT 1 FROM assets a WHERE a.uuid = ").push_bind(asset_uuid)
+                .push(" AND a.search_vector @@ websearch_to_tsquery('english', ").push_bind(query.as_str()).push(")");
+            push_search_filter(&mut sql, filter);
+            sql.push(")");
+        }
+
+        let matched = sql.build_query_scalar::<Uuid>()
+            .fetch_all(pool.as_ref())
+            .await?;
+
+        Ok(matched)
     }
 
     // Create relationship between assets
     pub async fn create_relationship(
-        pool: &DbPool,
+        executor: impl PgExecutor<'_>,
         source_uuid: Uuid,
         target_uuid: Uuid,
         relationship_type: &str,
@@ -129,9 +460,429 @@ impl GraphRepository {
         .bind(target_uuid)
         .bind(relationship_type)
         .bind(relationship_data)
-        .execute(pool.as_ref())
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// I-FR-20: Swap an asset's computed edges of `relationship_types` for `edges`, atomically.
+    /// Edges are stored in both directions so either asset's relationships list them.
+    pub async fn replace_relationships(
+        pool: &DbPool,
+        asset_uuid: Uuid,
+        relationship_types: &[&str],
+        edges: &[RelatedAsset],
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM graph_relationships
+            WHERE (source_asset_uuid = $1 OR target_asset_uuid = $1) AND relationship_type = ANY($2)
+            "#
+        )
+        .bind(asset_uuid)
+        .bind(relationship_types)
+        .execute(&mut *tx)
         .await?;
 
+        for edge in edges {
+            Self::create_relationship(&mut *tx, asset_uuid, edge.asset_uuid, edge.relationship_type, Some(edge.data.clone())).await?;
+            Self::create_relationship(&mut *tx, edge.asset_uuid, asset_uuid, edge.relationship_type, Some(edge.data.clone())).await?;
+        }
+
+        tx.commit().await?;
         Ok(())
     }
+
+    /// Assets sharing graph nodes of `node_type` with `asset_uuid`, scored by Jaccard
+    /// similarity of the two node sets; best first
+    pub async fn shared_node_neighbors(
+        pool: &DbPool,
+        asset_uuid: Uuid,
+        node_type: &str,
+        min_score: f64,
+        limit: i64,
+    ) -> Result<Vec<(Uuid, f64, Vec<String>)>> {
+        let neighbors = sqlx::query_as::<_, (Uuid, f64, Vec<String>)>(
+            r#"
+            WITH mine AS (
+                SELECT agn.node_id FROM asset_graph_nodes agn
+                JOIN graph_nodes gn ON gn.node_id = agn.node_id
+                WHERE agn.asset_uuid = $1 AND gn.node_type = $2
+            ),
+            shared AS (
+                SELECT agn.asset_uuid, COUNT(*) AS shared, array_agg(gn.node_name ORDER BY gn.node_name) AS names
+                FROM asset_graph_nodes agn
+                JOIN graph_nodes gn ON gn.node_id = agn.node_id
+                WHERE agn.node_id IN (SELECT node_id FROM mine) AND agn.asset_uuid <> $1
+                GROUP BY agn.asset_uuid
+            ),
+            theirs AS (
+                SELECT agn.asset_uuid, COUNT(*) AS total
+                FROM asset_graph_nodes agn
+                JOIN graph_nodes gn ON gn.node_id = agn.node_id
+                WHERE gn.node_type = $2 AND agn.asset_uuid IN (SELECT asset_uuid FROM shared)
+                GROUP BY agn.asset_uuid
+            ),
+            scored AS (
+                SELECT s.asset_uuid,
+                       s.shared::float8 / ((SELECT COUNT(*) FROM mine) + t.total - s.shared) AS score,
+                       s.names
+                FROM shared s JOIN theirs t ON t.asset_uuid = s.asset_uuid
+            )
+            SELECT asset_uuid, score, names FROM scored
+            WHERE score >= $3
+            ORDER BY score DESC, asset_uuid
+            LIMIT $4
+            "#
+        )
+        .bind(asset_uuid)
+        .bind(node_type)
+        .bind(min_score)
+        .bind(limit)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(neighbors)
+    }
+
+    /// Assets of the same series (operational tag or metadata `series`) from the same source system,
+    /// nearest in time first
+    pub async fn same_series_neighbors(pool: &DbPool, asset_uuid: Uuid, limit: i64) -> Result<Vec<(Uuid, String)>> {
+        let neighbors = sqlx::query_as::<_, (Uuid, String)>(
+            r#"
+            WITH me AS (
+                SELECT source_system, created_at,
+                       COALESCE(operational_tags->>'series', enriched_metadata->>'series') AS series
+                FROM assets WHERE uuid = $1
+            )
+            SELECT a.uuid, me.series FROM assets a, me
+            WHERE me.series IS NOT NULL AND a.uuid <> $1
+              AND a.source_system = me.source_system
+              AND COALESCE(a.operational_tags->>'series', a.enriched_metadata->>'series') = me.series
+            ORDER BY abs(extract(epoch FROM a.created_at - me.created_at)), a.uuid
+            LIMIT $2
+            "#
+        )
+        .bind(asset_uuid)
+        .bind(limit)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(neighbors)
+    }
+
+    /// Assets created within `window_hours` of `asset_uuid`, with the hours between them; nearest first
+    pub async fn temporal_neighbors(
+        pool: &DbPool,
+        asset_uuid: Uuid,
+        window_hours: f64,
+        limit: i64,
+    ) -> Result<Vec<(Uuid, f64)>> {
+        let neighbors = sqlx::query_as::<_, (Uuid, f64)>(
+            r#"
+            WITH me AS (SELECT created_at FROM assets WHERE uuid = $1)
+            SELECT a.uuid, abs(extract(epoch FROM a.created_at - me.created_at))::float8 / 3600 AS hours_apart
+            FROM assets a, me
+            WHERE a.uuid <> $1
+              AND a.created_at BETWEEN me.created_at - make_interval(secs => $2 * 3600)
+                                   AND me.created_at + make_interval(secs => $2 * 3600)
+            ORDER BY hours_apart, a.uuid
+            LIMIT $3
+            "#
+        )
+        .bind(asset_uuid)
+        .bind(window_hours)
+        .bind(limit)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(neighbors)
+    }
+
+    /// Assets with named speakers in common (from transcript segments), scored by Jaccard similarity.
+    /// Anonymous diarization labels such as "Speaker_1" are per recording and never shared.
+    pub async fn shared_speaker_neighbors(
+        pool: &DbPool,
+        asset_uuid: Uuid,
+        min_score: f64,
+        limit: i64,
+    ) -> Result<Vec<(Uuid, f64, Vec<String>)>> {
+        let neighbors = sqlx::query_as::<_, (Uuid, f64, Vec<String>)>(
+            r#"
+            WITH mine AS (
+                SELECT DISTINCT speaker_id FROM transcript_segments
+                WHERE asset_uuid = $1 AND speaker_id IS NOT NULL AND speaker_id !~* '^(spk|speaker)[ _-]?[0-9]+$'
+            ),
+            shared AS (
+                SELECT s.asset_uuid, COUNT(DISTINCT s.speaker_id) AS shared,
+                       array_agg(DISTINCT s.speaker_id ORDER BY s.speaker_id) AS names
+                FROM transcript_segments s
+                WHERE s.speaker_id IN (SELECT speaker_id FROM mine) AND s.asset_uuid <> $1
+                GROUP BY s.asset_uuid
+            ),
+            scored AS (
+                SELECT sh.asset_uuid,
+                       sh.shared::float8 / ((SELECT COUNT(*) FROM mine) + (
+                           SELECT COUNT(DISTINCT t.speaker_id) FROM transcript_segments t
+                           WHERE t.asset_uuid = sh.asset_uuid AND t.speaker_id IS NOT NULL
+                             AND t.speaker_id !~* '^(spk|speaker)[ _-]?[0-9]+$'
+                       ) - sh.shared) AS score,
+                       sh.names
+                FROM shared sh
+            )
+            SELECT asset_uuid, score, names FROM scored
+            WHERE score >= $2
+            ORDER BY score DESC, asset_uuid
+            LIMIT $3
+            "#
+        )
+        .bind(asset_uuid)
+        .bind(min_score)
+        .bind(limit)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(neighbors)
+    }
+}
+
+// ts_headline copies source text verbatim, so matches are marked with private-use characters
+// and the text is HTML-escaped before they become <mark> tags
+const HIGHLIGHT_START: char = '\u{E000}';
+const HIGHLIGHT_STOP: char = '\u{E001}';
+
+/// ts_headline options marking matches for `highlight`, followed by `extra` options
+pub(crate) fn headline_options(extra: &str) -> String {
+    format!("StartSel={}, StopSel={}, {}", HIGHLIGHT_START, HIGHLIGHT_STOP, extra)
+}
+
+/// HTML-escape a ts_headline result and wrap its marked matches in <mark></mark>
+pub(crate) fn highlight(headline: &str) -> String {
+    let mut html = String::with_capacity(headline.len() + 32);
+    for c in headline.chars() {
+        match c {
+            HIGHLIGHT_START => html.push_str("<mark>"),
+            HIGHLIGHT_STOP => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html
+}
+
+pub(crate) fn search_query(query: &str) -> QueryBuilder<'_, Postgres> {
+    let mut sql = QueryBuilder::new(SEARCH_QUERY_CTE);
+    sql.push_bind(query).push(") AS query)");
+    sql
+}
+
+// Filter conditions on `assets a`, appended to a WHERE clause
+pub(crate) fn push_search_filter<'a>(sql: &mut QueryBuilder<'a, Postgres>, filter: &'a SearchFilter) {
+    push_any_of(sql, "a.asset_type", &filter.asset_types);
+    push_any_of(sql, "a.source_system", &filter.source_systems);
+    push_any_of(sql, "a.status", &filter.statuses);
+    push_any_of(sql, "a.enriched_metadata->>'language'", &filter.languages);
+    for (node_type, names) in [("KEYWORD", &filter.keywords), ("TOPIC", &filter.topics)] {
+        for name in names {
+            sql.push(
+                " AND EXISTS (SELECT 1 FROM asset_graph_nodes agn JOIN graph_nodes gn ON gn.node_id = agn.node_id \
+                 WHERE agn.asset_uuid = a.uuid AND gn.node_type = "
+            )
+            .push_bind(node_type)
+            .push(" AND gn.node_name = ")
+            .push_bind(name)
+            .push(")");
+        }
+    }
+    if let Some(after) = filter.created_after {
+        sql.push(" AND a.created_at >= ").push_bind(after);
+    }
+    if let Some(before) = filter.created_before {
+        sql.push(" AND a.created_at < ").push_bind(before);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{db_pool, insert_asset};
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    #[test]
+    fn highlight_escapes_source_text() {
+        let headline = format!("<img src=x onerror=alert(1)> {}budget{} & \"tax\"", HIGHLIGHT_START, HIGHLIGHT_STOP);
+        assert_eq!(
+            highlight(&headline),
+            "&lt;img src=x onerror=alert(1)&gt; <mark>budget</mark> &amp; &quot;tax&quot;"
+        );
+        assert_eq!(highlight("plain"), "plain");
+    }
+
+    fn buckets(buckets: &[FacetBucket]) -> Vec<(&str, i64)> {
+        buckets.iter().map(|b| (b.value.as_str(), b.count)).collect()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn facets_count_every_match(pool: PgPool) {
+        let pool = db_pool(pool);
+        let english = insert_asset(&pool, "a.mp4", json!({"title": "Budget vote", "language": "en"})).await;
+        let french = insert_asset(&pool, "b.mp4", json!({"title": "Budget debate", "language": "fr"})).await;
+        let unlabelled = insert_asset(&pool, "c.mp4", json!({"title": "Budget recap"})).await;
+        insert_asset(&pool, "d.mp4", json!({"title": "Weather"})).await;
+        sqlx::query("UPDATE assets SET asset_type = 'AUDIO', created_at = '2024-01-10T12:00:00Z' WHERE uuid = $1")
+            .bind(french)
+            .execute(pool.as_ref())
+            .await
+            .unwrap();
+        sqlx::query("UPDATE assets SET created_at = '2024-03-05T00:00:00Z' WHERE uuid <> $1")
+            .bind(french)
+            .execute(pool.as_ref())
+            .await
+            .unwrap();
+        GraphRepository::index_asset(&pool, english, &["tax".into(), "council".into()], &["politics".into()]).await.unwrap();
+        GraphRepository::index_asset(&pool, french, &["tax".into()], &["politics".into()]).await.unwrap();
+        GraphRepository::index_asset(&pool, unlabelled, &["recap".into()], &[]).await.unwrap();
+
+        let facets = GraphRepository::search_facets(&pool, "budget", &SearchFilter::default(), 2, DateInterval::Month)
+            .await
+            .unwrap();
+
+        assert_eq!(facets.total(), 3);
+        assert_eq!(buckets(&facets.asset_types), vec![("VIDEO", 2), ("AUDIO", 1)]);
+        assert_eq!(buckets(&facets.source_systems), vec![("USER_UPLOAD", 3)]);
+        assert_eq!(buckets(&facets.statuses), vec![("PROCESSED", 3)]);
+        assert_eq!(buckets(&facets.languages), vec![("en", 1), ("fr", 1)]);
+        // Top two only: ties broken by name
+        assert_eq!(buckets(&facets.keywords), vec![("tax", 2), ("council", 1)]);
+        assert_eq!(buckets(&facets.topics), vec![("politics", 2)]);
+        assert_eq!(buckets(&facets.created), vec![("2024-01-01", 1), ("2024-03-01", 2)]);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn facets_respect_filters(pool: PgPool) {
+        let pool = db_pool(pool);
+        let tagged = insert_asset(&pool, "a.mp4", json!({"title": "Budget vote"})).await;
+        insert_asset(&pool, "b.mp4", json!({"title": "Budget debate"})).await;
+        GraphRepository::index_asset(&pool, tagged, &["tax".into()], &[]).await.unwrap();
+
+        let filter = SearchFilter { keywords: vec!["tax".into()], ..Default::default() };
+        let facets = GraphRepository::search_facets(&pool, "budget", &filter, 10, DateInterval::Day).await.unwrap();
+        assert_eq!(facets.total(), 1);
+        assert_eq!(buckets(&facets.keywords), vec![("tax", 1)]);
+
+        let facets = GraphRepository::search_facets(&pool, "nothing", &SearchFilter::default(), 10, DateInterval::Day)
+            .await
+            .unwrap();
+        assert_eq!(facets.total(), 0);
+        assert!(facets.created.is_empty() && facets.keywords.is_empty());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn search_snippets_are_escaped(pool: PgPool) {
+        let pool = db_pool(pool);
+        insert_asset(&pool, "a.mp4", json!({"title": "Budget <script>alert(1)</script>", "description": "budget 1 <2 & 3"})).await;
+
+        let hits = GraphRepository::search(&pool, "budget", &SearchFilter::default(), 10, 0).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        let snippet = hits[0].snippet.as_deref().unwrap();
+        assert!(snippet.contains("<mark>Budget</mark>"), "{}", snippet);
+        assert!(snippet.contains("&lt;2 &amp; 3"), "{}", snippet);
+        assert!(!snippet.replace("<mark>", "").replace("</mark>", "").contains('<'), "{}", snippet);
+    }
+
+    async fn edges(pool: &DbPool) -> Vec<(Uuid, Uuid, String)> {
+        sqlx::query_as(
+            "SELECT source_asset_uuid, target_asset_uuid, relationship_type FROM graph_relationships
+             ORDER BY relationship_type, source_asset_uuid, target_asset_uuid"
+        )
+        .fetch_all(pool.as_ref())
+        .await
+        .unwrap()
+    }
+
+    fn edge(asset_uuid: Uuid, relationship_type: &'static str) -> RelatedAsset {
+        RelatedAsset { asset_uuid, relationship_type, data: json!({"score": 1.0}) }
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn replacing_relationships_drops_stale_edges_of_the_given_types(pool: PgPool) {
+        let pool = db_pool(pool);
+        let me = insert_asset(&pool, "a.mp4", json!({})).await;
+        let old = insert_asset(&pool, "b.mp4", json!({})).await;
+        let new = insert_asset(&pool, "c.mp4", json!({})).await;
+        let bystander = insert_asset(&pool, "d.mp4", json!({})).await;
+
+        GraphRepository::replace_relationships(&pool, me, &["same_speaker"], &[edge(old, "same_speaker")]).await.unwrap();
+        // Edges of other types, and edges between other assets, survive the replacement
+        GraphRepository::create_relationship(pool.as_ref(), me, old, "curated", None).await.unwrap();
+        GraphRepository::replace_relationships(&pool, old, &["same_speaker"], &[edge(bystander, "same_speaker")]).await.unwrap();
+
+        GraphRepository::replace_relationships(&pool, me, &["same_speaker"], &[edge(new, "same_speaker")]).await.unwrap();
+
+        let mut expected = vec![
+            (me, old, "curated".to_string()),
+            (me, new, "same_speaker".to_string()),
+            (new, me, "same_speaker".to_string()),
+            (old, bystander, "same_speaker".to_string()),
+            (bystander, old, "same_speaker".to_string()),
+        ];
+        expected.sort();
+        let mut actual = edges(&pool).await;
+        actual.sort();
+        assert_eq!(actual, expected);
+
+        GraphRepository::replace_relationships(&pool, me, &["same_speaker"], &[]).await.unwrap();
+        assert!(!edges(&pool).await.iter().any(|(source, target, kind)| kind == "same_speaker" && (*source == me || *target == me)));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn speaker_neighbors_score_named_speakers_only(pool: PgPool) {
+        let pool = db_pool(pool);
+        let me = insert_asset(&pool, "a.mp4", json!({})).await;
+        let both = insert_asset(&pool, "b.mp4", json!({})).await;
+        let one = insert_asset(&pool, "c.mp4", json!({})).await;
+        let anonymous = insert_asset(&pool, "d.mp4", json!({})).await;
+        for (asset, speakers) in [
+            (me, vec!["Ana", "Ben", "Ana", "Speaker_1"]),
+            (both, vec!["Ben", "Ana"]),
+            (one, vec!["Ana", "Cy", "Di"]),
+            (anonymous, vec!["Speaker_1", "spk 2"]),
+        ] {
+            for (index, speaker) in speakers.iter().enumerate() {
+                sqlx::query(
+                    "INSERT INTO transcript_segments (asset_uuid, segment_index, start_time, end_time, speaker_id, text)
+                     VALUES ($1, $2, $2, $2 + 1, $3, 'words')"
+                )
+                .bind(asset)
+                .bind(index as i32)
+                .bind(speaker)
+                .execute(pool.as_ref())
+                .await
+                .unwrap();
+            }
+        }
+
+        let neighbors = GraphRepository::shared_speaker_neighbors(&pool, me, 0.1, 10).await.unwrap();
+        assert_eq!(neighbors, vec![
+            (both, 1.0, vec!["Ana".to_string(), "Ben".to_string()]),
+            (one, 0.25, vec!["Ana".to_string()]),
+        ]);
+
+        assert!(GraphRepository::shared_speaker_neighbors(&pool, anonymous, 0.0, 10).await.unwrap().is_empty());
+        assert_eq!(GraphRepository::shared_speaker_neighbors(&pool, me, 0.5, 10).await.unwrap().len(), 1);
+    }
 }
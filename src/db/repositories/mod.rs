@@ -5,9 +5,25 @@ pub mod action_repository;
 pub mod user_repository;
 pub mod workflow_repository;
 pub mod graph_repository;
+pub mod controller_repository;
+pub mod settings_repository;
+pub mod upload_repository;
+pub mod token_repository;
+pub mod oauth_state_repository;
+pub mod rate_limit_repository;
+pub mod transcript_repository;
+pub mod saved_search_repository;
 
 pub use asset_repository::*;
 pub use action_repository::*;
 pub use user_repository::*;
 pub use workflow_repository::*;
 pub use graph_repository::*;
+pub use controller_repository::*;
+pub use settings_repository::*;
+pub use upload_repository::*;
+pub use token_repository::*;
+pub use oauth_state_repository::*;
+pub use rate_limit_repository::*;
+pub use transcript_repository::*;
+pub use saved_search_repository::*;
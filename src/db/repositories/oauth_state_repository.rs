@@ -0,0 +1,62 @@
+// OAuth state repository
+// I-FR-21: SSO sign-ins in flight between the login redirect and the provider's callback
+
+use crate::db::DbPool;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingSignIn {
+    pub state: String,
+    pub provider: String,
+    pub pkce_verifier: String,
+    pub nonce: Option<String>,
+}
+
+pub struct OAuthStateRepository;
+
+impl OAuthStateRepository {
+    pub async fn create(pool: &DbPool, pending: &PendingSignIn, expires_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO oauth_states (state, provider, pkce_verifier, nonce, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#
+        )
+        .bind(&pending.state)
+        .bind(&pending.provider)
+        .bind(&pending.pkce_verifier)
+        .bind(&pending.nonce)
+        .bind(expires_at)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Consume a state issued for `provider`; `None` if it was never issued, already used or expired
+    pub async fn take(pool: &DbPool, state: &str, provider: &str) -> Result<Option<PendingSignIn>> {
+        let pending = sqlx::query_as::<_, PendingSignIn>(
+            r#"
+            DELETE FROM oauth_states
+            WHERE state = $1 AND provider = $2 AND expires_at > NOW()
+            RETURNING state, provider, pkce_verifier, nonce
+            "#
+        )
+        .bind(state)
+        .bind(provider)
+        .fetch_optional(pool.as_ref())
+        .await?;
+
+        Ok(pending)
+    }
+
+    pub async fn purge_expired(pool: &DbPool) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM oauth_states WHERE expires_at <= NOW()")
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,38 @@
+// Rate limit repository
+// I-FR-25: Fixed-window request counters shared by all instances
+
+use crate::db::DbPool;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+pub struct RateLimitRepository;
+
+impl RateLimitRepository {
+    /// Count one request in the caller's window and return the window's total so far
+    pub async fn increment(pool: &DbPool, identifier: &str, window_start: DateTime<Utc>) -> Result<i32> {
+        let count = sqlx::query_scalar::<_, i32>(
+            r#"
+            INSERT INTO rate_limit_counters (identifier, window_start, request_count)
+            VALUES ($1, $2, 1)
+            ON CONFLICT (identifier, window_start)
+            DO UPDATE SET request_count = rate_limit_counters.request_count + 1
+            RETURNING request_count
+            "#
+        )
+        .bind(identifier)
+        .bind(window_start)
+        .fetch_one(pool.as_ref())
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn purge_before(pool: &DbPool, cutoff: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM rate_limit_counters WHERE window_start < $1")
+            .bind(cutoff)
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,264 @@
+// Saved search repository
+// I-FR-22: Saved searches and their new-match history
+
+use crate::db::DbPool;
+use crate::models::saved_search::{SavedSearch, SavedSearchMatch};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+pub struct SavedSearchRepository;
+
+impl SavedSearchRepository {
+    pub async fn create(pool: &DbPool, search: &SavedSearch) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO saved_searches (id, user_id, name, query, filters, webhook_url, last_checked_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#
+        )
+        .bind(search.id)
+        .bind(search.user_id)
+        .bind(&search.name)
+        .bind(&search.query)
+        .bind(&search.filters)
+        .bind(&search.webhook_url)
+        .bind(search.last_checked_at)
+        .bind(search.created_at)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    /// One of `user_id`'s saved searches
+    pub async fn get(pool: &DbPool, id: Uuid, user_id: Uuid) -> Result<Option<SavedSearch>> {
+        let search = sqlx::query_as::<_, SavedSearch>(
+            "SELECT * FROM saved_searches WHERE id = $1 AND user_id = $2"
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(pool.as_ref())
+        .await?;
+
+        Ok(search)
+    }
+
+    /// A user's saved searches with the number of matches since each was last checked
+    pub async fn list_for_user(pool: &DbPool, user_id: Uuid) -> Result<Vec<(SavedSearch, i64)>> {
+        let searches = sqlx::query_as::<_, SavedSearch>(
+            "SELECT * FROM saved_searches WHERE user_id = $1 ORDER BY name"
+        )
+        .bind(user_id)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        let counts: Vec<(Uuid, i64)> = sqlx::query_as(
+            r#"
+            SELECT s.id, COUNT(m.asset_uuid)
+            FROM saved_searches s
+            LEFT JOIN saved_search_matches m ON m.saved_search_id = s.id AND m.matched_at > s.last_checked_at
+            WHERE s.user_id = $1
+            GROUP BY s.id
+            "#
+        )
+        .bind(user_id)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(searches.into_iter()
+            .map(|search| {
+                let new_matches = counts.iter().find(|(id, _)| *id == search.id).map(|(_, n)| *n).unwrap_or(0);
+                (search, new_matches)
+            })
+            .collect())
+    }
+
+    /// Every saved search, for evaluating a newly processed asset
+    pub async fn list_all(pool: &DbPool) -> Result<Vec<SavedSearch>> {
+        let searches = sqlx::query_as::<_, SavedSearch>("SELECT * FROM saved_searches")
+            .fetch_all(pool.as_ref())
+            .await?;
+
+        Ok(searches)
+    }
+
+    pub async fn update(pool: &DbPool, search: &SavedSearch) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE saved_searches
+            SET name = $1, query = $2, filters = $3, webhook_url = $4, updated_at = NOW()
+            WHERE id = $5 AND user_id = $6
+            "#
+        )
+        .bind(&search.name)
+        .bind(&search.query)
+        .bind(&search.filters)
+        .bind(&search.webhook_url)
+        .bind(search.id)
+        .bind(search.user_id)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete one of `user_id`'s saved searches; false when no such search belongs to them
+    pub async fn delete(pool: &DbPool, id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM saved_searches WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record that an asset matched each of `saved_search_ids`. Returns the searches it had not
+    /// matched before, with the time of the new match.
+    pub async fn record_matches(
+        pool: &DbPool,
+        saved_search_ids: &[Uuid],
+        asset_uuid: Uuid,
+    ) -> Result<Vec<(Uuid, DateTime<Utc>)>> {
+        let recorded = sqlx::query_as::<_, (Uuid, DateTime<Utc>)>(
+            r#"
+            INSERT INTO saved_search_matches (saved_search_id, asset_uuid, matched_at)
+            SELECT id, $2, NOW() FROM UNNEST($1::uuid[]) AS id
+            ON CONFLICT DO NOTHING
+            RETURNING saved_search_id, matched_at
+            "#
+        )
+        .bind(saved_search_ids)
+        .bind(asset_uuid)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(recorded)
+    }
+
+    /// Matches recorded after `since`, oldest first
+    pub async fn matches_since(
+        pool: &DbPool,
+        saved_search_id: Uuid,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<SavedSearchMatch>> {
+        let matches = sqlx::query_as::<_, SavedSearchMatch>(
+            r#"
+            SELECT m.asset_uuid, a.asset_name, a.enriched_metadata->>'title' AS title, m.matched_at
+            FROM saved_search_matches m
+            JOIN assets a ON a.uuid = m.asset_uuid
+            WHERE m.saved_search_id = $1 AND m.matched_at > $2
+            ORDER BY m.matched_at, m.asset_uuid
+            LIMIT $3
+            "#
+        )
+        .bind(saved_search_id)
+        .bind(since)
+        .bind(limit)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(matches)
+    }
+
+    /// Move the last check forward (never back)
+    pub async fn mark_checked(pool: &DbPool, id: Uuid, checked_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            "UPDATE saved_searches SET last_checked_at = GREATEST(last_checked_at, $1) WHERE id = $2"
+        )
+        .bind(checked_at)
+        .bind(id)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{db_pool, insert_asset, insert_user};
+    use chrono::{Duration, Timelike};
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    async fn saved_search(pool: &DbPool, last_checked_at: DateTime<Utc>) -> SavedSearch {
+        let user_id = insert_user(pool, "searcher@example.com", "VIEWER").await;
+        let search = SavedSearch {
+            id: Uuid::new_v4(),
+            user_id,
+            name: "Budget".to_string(),
+            query: "budget".to_string(),
+            filters: json!({}),
+            webhook_url: None,
+            last_checked_at,
+            created_at: last_checked_at,
+            updated_at: None,
+        };
+        SavedSearchRepository::create(pool, &search).await.unwrap();
+        search
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn record_matches_only_reports_new_matches(pool: PgPool) {
+        let pool = db_pool(pool);
+        let search = saved_search(&pool, Utc::now()).await;
+        let asset = insert_asset(&pool, "budget.mp4", json!({})).await;
+
+        let first = SavedSearchRepository::record_matches(&pool, &[search.id], asset).await.unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].0, search.id);
+
+        let again = SavedSearchRepository::record_matches(&pool, &[search.id], asset).await.unwrap();
+        assert!(again.is_empty());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn matches_since_returns_newer_matches_oldest_first(pool: PgPool) {
+        let pool = db_pool(pool);
+        let search = saved_search(&pool, Utc::now() - Duration::hours(1)).await;
+        let older = insert_asset(&pool, "older.mp4", json!({"title": "Older"})).await;
+        let newer = insert_asset(&pool, "newer.mp4", json!({})).await;
+        let base = Utc::now();
+        for (asset, matched_at) in [(older, base - Duration::minutes(30)), (newer, base - Duration::minutes(10))] {
+            sqlx::query("INSERT INTO saved_search_matches (saved_search_id, asset_uuid, matched_at) VALUES ($1, $2, $3)")
+                .bind(search.id)
+                .bind(asset)
+                .bind(matched_at)
+                .execute(pool.as_ref())
+                .await
+                .unwrap();
+        }
+
+        let all = SavedSearchRepository::matches_since(&pool, search.id, base - Duration::hours(1), 10).await.unwrap();
+        assert_eq!(all.iter().map(|m| m.asset_uuid).collect::<Vec<_>>(), vec![older, newer]);
+        assert_eq!(all[0].title.as_deref(), Some("Older"));
+
+        let recent = SavedSearchRepository::matches_since(&pool, search.id, base - Duration::minutes(20), 10).await.unwrap();
+        assert_eq!(recent.iter().map(|m| m.asset_uuid).collect::<Vec<_>>(), vec![newer]);
+
+        let page = SavedSearchRepository::matches_since(&pool, search.id, base - Duration::hours(1), 1).await.unwrap();
+        assert_eq!(page.iter().map(|m| m.asset_uuid).collect::<Vec<_>>(), vec![older]);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn mark_checked_never_moves_back(pool: PgPool) {
+        let pool = db_pool(pool);
+        let checked = Utc::now().with_nanosecond(0).unwrap();
+        let search = saved_search(&pool, checked).await;
+
+        SavedSearchRepository::mark_checked(&pool, search.id, checked - Duration::hours(1)).await.unwrap();
+        let stored = SavedSearchRepository::get(&pool, search.id, search.user_id).await.unwrap().unwrap();
+        assert_eq!(stored.last_checked_at, checked);
+
+        SavedSearchRepository::mark_checked(&pool, search.id, checked + Duration::hours(1)).await.unwrap();
+        let stored = SavedSearchRepository::get(&pool, search.id, search.user_id).await.unwrap().unwrap();
+        assert_eq!(stored.last_checked_at, checked + Duration::hours(1));
+    }
+}
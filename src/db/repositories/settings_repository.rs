@@ -0,0 +1,58 @@
+// Platform settings repository
+// I-FR-16: Retry configuration persisted in the database
+// I-FR-25: Rate limits persisted in the database
+
+use crate::config::{RateLimitConfig, RetryConfig};
+use crate::db::DbPool;
+use anyhow::Result;
+use serde_json::Value;
+
+pub const RETRY_SETTINGS_KEY: &str = "retry";
+pub const RATE_LIMIT_SETTINGS_KEY: &str = "rate_limit";
+
+pub struct SettingsRepository;
+
+impl SettingsRepository {
+    pub async fn get(pool: &DbPool, key: &str) -> Result<Option<Value>> {
+        let value = sqlx::query_scalar::<_, Value>(
+            "SELECT value FROM platform_settings WHERE setting_key = $1"
+        )
+        .bind(key)
+        .fetch_optional(pool.as_ref())
+        .await?;
+
+        Ok(value)
+    }
+
+    pub async fn put(pool: &DbPool, key: &str, value: &Value) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO platform_settings (setting_key, value, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (setting_key) DO UPDATE SET value = $2, updated_at = NOW()
+            "#
+        )
+        .bind(key)
+        .bind(value)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    // Global retry policy: stored overrides on top of the startup configuration
+    pub async fn get_retry_config(pool: &DbPool, defaults: &RetryConfig) -> Result<RetryConfig> {
+        Ok(match Self::get(pool, RETRY_SETTINGS_KEY).await? {
+            Some(stored) => defaults.with_overrides(&stored),
+            None => defaults.clone(),
+        })
+    }
+
+    // Per-role limits: stored overrides on top of the startup configuration
+    pub async fn get_rate_limit_config(pool: &DbPool, defaults: &RateLimitConfig) -> Result<RateLimitConfig> {
+        Ok(match Self::get(pool, RATE_LIMIT_SETTINGS_KEY).await? {
+            Some(stored) => defaults.with_overrides(&stored),
+            None => defaults.clone(),
+        })
+    }
+}
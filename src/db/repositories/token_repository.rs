@@ -0,0 +1,172 @@
+// JWT session repository
+// I-FR-21: SSO sessions, I-FR-23: Token revocation
+
+use crate::db::DbPool;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+/// Result of presenting a refresh token
+pub enum RefreshUse {
+    /// First use; the token is now spent
+    Accepted,
+    /// Already spent: the token has leaked or been replayed
+    Reused,
+    /// Never issued, expired, or cleaned up
+    Unknown,
+}
+
+pub struct TokenRepository;
+
+impl TokenRepository {
+    pub async fn create_session(pool: &DbPool, session_id: Uuid, user_id: Uuid, expires_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO auth_sessions (session_id, user_id, expires_at) VALUES ($1, $2, $3)"
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(expires_at)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn store_refresh_token(pool: &DbPool, jti: Uuid, session_id: Uuid, expires_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO refresh_tokens (jti, session_id, expires_at) VALUES ($1, $2, $3)"
+        )
+        .bind(jti)
+        .bind(session_id)
+        .bind(expires_at)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Spend a refresh token; concurrent presentations of the same token see exactly one `Accepted`
+    pub async fn use_refresh_token(pool: &DbPool, jti: Uuid, session_id: Uuid) -> Result<RefreshUse> {
+        let spent = sqlx::query(
+            r#"
+            UPDATE refresh_tokens SET used_at = NOW()
+            WHERE jti = $1 AND session_id = $2 AND used_at IS NULL AND expires_at > NOW()
+            "#
+        )
+        .bind(jti)
+        .bind(session_id)
+        .execute(pool.as_ref())
+        .await?;
+
+        if spent.rows_affected() > 0 {
+            return Ok(RefreshUse::Accepted);
+        }
+
+        let used: Option<Option<DateTime<Utc>>> = sqlx::query_scalar(
+            "SELECT used_at FROM refresh_tokens WHERE jti = $1 AND session_id = $2"
+        )
+        .bind(jti)
+        .bind(session_id)
+        .fetch_optional(pool.as_ref())
+        .await?;
+
+        Ok(match used {
+            Some(Some(_)) => RefreshUse::Reused,
+            _ => RefreshUse::Unknown,
+        })
+    }
+
+    /// Unrevoked and unexpired session
+    pub async fn session_is_active(pool: &DbPool, session_id: Uuid) -> Result<bool> {
+        let active: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM auth_sessions
+                WHERE session_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+            )
+            "#
+        )
+        .bind(session_id)
+        .fetch_one(pool.as_ref())
+        .await?;
+
+        Ok(active)
+    }
+
+    pub async fn revoke_session(pool: &DbPool, session_id: Uuid, reason: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE auth_sessions SET revoked_at = NOW(), revoked_reason = $2
+            WHERE session_id = $1 AND revoked_at IS NULL
+            "#
+        )
+        .bind(session_id)
+        .bind(reason)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    /// End every open session of a user, e.g. when their account is disabled or their role changes
+    pub async fn revoke_user_sessions(executor: impl PgExecutor<'_>, user_id: Uuid, reason: &str) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE auth_sessions SET revoked_at = NOW(), revoked_reason = $2
+            WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+            "#
+        )
+        .bind(user_id)
+        .bind(reason)
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Add one token to the revocation list until it would expire anyway
+    pub async fn revoke_token(pool: &DbPool, jti: Uuid, expires_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO revoked_tokens (jti, expires_at) VALUES ($1, $2) ON CONFLICT (jti) DO NOTHING"
+        )
+        .bind(jti)
+        .bind(expires_at)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Whether the token itself, its session or its user's account has been revoked
+    pub async fn is_revoked(pool: &DbPool, jti: Uuid, session_id: Uuid) -> Result<bool> {
+        let revoked: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(SELECT 1 FROM revoked_tokens WHERE jti = $1)
+                OR EXISTS(
+                    SELECT 1 FROM auth_sessions s JOIN users u ON u.id = s.user_id
+                    WHERE s.session_id = $2 AND (s.revoked_at IS NOT NULL OR u.disabled_at IS NOT NULL)
+                )
+            "#
+        )
+        .bind(jti)
+        .bind(session_id)
+        .fetch_one(pool.as_ref())
+        .await?;
+
+        Ok(revoked)
+    }
+
+    /// Drop revocation entries and sessions whose tokens can no longer validate
+    pub async fn purge_expired(pool: &DbPool) -> Result<u64> {
+        let tokens = sqlx::query("DELETE FROM revoked_tokens WHERE expires_at <= NOW()")
+            .execute(pool.as_ref())
+            .await?;
+        // Refresh tokens go with their session (ON DELETE CASCADE)
+        let sessions = sqlx::query("DELETE FROM auth_sessions WHERE expires_at <= NOW()")
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(tokens.rows_affected() + sessions.rows_affected())
+    }
+}
@@ -0,0 +1,232 @@
+// Transcript repository
+// I-FR-22: Search inside time-coded transcripts
+
+use crate::db::DbPool;
+use crate::db::repositories::graph_repository::{headline_options, highlight, push_search_filter, search_query, SearchFilter};
+use crate::models::asset::AssetType;
+use crate::models::metadata::TranscriptSegment;
+use anyhow::Result;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// One matching segment, flattened with its asset; rows of one asset are adjacent
+#[derive(Debug, Clone, FromRow)]
+pub struct TranscriptMatch {
+    pub asset_uuid: Uuid,
+    pub asset_name: String,
+    pub asset_type: AssetType,
+    /// Matching segments of the asset, of which at most `segments_per_asset` are returned
+    pub segment_count: i64,
+    pub best_rank: f32,
+    pub segment_index: i32,
+    pub start_time: f64,
+    pub end_time: f64,
+    pub speaker_id: Option<String>,
+    /// HTML-escaped segment text with matches wrapped in <mark></mark>
+    pub snippet: String,
+}
+
+pub struct TranscriptRepository;
+
+impl TranscriptRepository {
+    /// Replace an asset's segments with a fresh transcript
+    pub async fn replace_segments(pool: &DbPool, asset_uuid: Uuid, segments: &[TranscriptSegment]) -> Result<()> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM transcript_segments WHERE asset_uuid = $1")
+            .bind(asset_uuid)
+            .execute(&mut *tx)
+            .await?;
+
+        for (index, segment) in segments.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO transcript_segments (asset_uuid, segment_index, start_time, end_time, speaker_id, text)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#
+            )
+            .bind(asset_uuid)
+            .bind(index as i32)
+            .bind(segment.start_time)
+            .bind(segment.end_time)
+            .bind(&segment.speaker_id)
+            .bind(&segment.text)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Assets with matching segments, best segment first; each asset brings its
+    /// `segments_per_asset` best segments, in playback order
+    pub async fn search(
+        pool: &DbPool,
+        query: &str,
+        filter: &SearchFilter,
+        segments_per_asset: i64,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<TranscriptMatch>> {
+        let mut sql = search_query(query);
+        sql.push(
+            r#", matches AS (
+                SELECT s.asset_uuid, s.segment_index, s.start_time, s.end_time, s.speaker_id, s.text,
+                       ts_rank(s.search_vector, q.query) AS rank
+                FROM q CROSS JOIN transcript_segments s
+                JOIN assets a ON a.uuid = s.asset_uuid
+                WHERE s.search_vector @@ q.query"#
+        );
+        push_search_filter(&mut sql, filter);
+        sql.push(
+            r#"),
+            ranked AS (
+                SELECT asset_uuid, MAX(rank) AS best_rank, COUNT(*) AS segment_count
+                FROM matches GROUP BY asset_uuid
+                ORDER BY best_rank DESC, asset_uuid
+                LIMIT "#
+        )
+        .push_bind(limit)
+        .push(" OFFSET ")
+        .push_bind(offset);
+        sql.push(
+            r#")
+            SELECT r.asset_uuid, a.asset_name, a.asset_type, r.segment_count, r.best_rank,
+                   m.segment_index, m.start_time, m.end_time, m.speaker_id,
+                   ts_headline('english', m.text, q.query, "#
+        )
+        .push_bind(headline_options("HighlightAll=true"))
+        .push(
+            r#") AS snippet
+            FROM q, ranked r
+            JOIN assets a ON a.uuid = r.asset_uuid
+            CROSS JOIN LATERAL (
+                SELECT * FROM matches WHERE matches.asset_uuid = r.asset_uuid
+                ORDER BY rank DESC, start_time
+                LIMIT "#
+        )
+        .push_bind(segments_per_asset)
+        .push(
+            r#") m
+            ORDER BY r.best_rank DESC, r.asset_uuid, m.start_time"#
+        );
+
+        let mut matches = sql.build_query_as::<TranscriptMatch>()
+            .fetch_all(pool.as_ref())
+            .await?;
+        for segment in &mut matches {
+            segment.snippet = highlight(&segment.snippet);
+        }
+
+        Ok(matches)
+    }
+
+    /// Number of assets with at least one matching segment
+    pub async fn count_matching_assets(pool: &DbPool, query: &str, filter: &SearchFilter) -> Result<i64> {
+        let mut sql = search_query(query);
+        sql.push(
+            r#" SELECT COUNT(DISTINCT s.asset_uuid)
+            FROM q CROSS JOIN transcript_segments s
+            JOIN assets a ON a.uuid = s.asset_uuid
+            WHERE s.search_vector @@ q.query"#
+        );
+        push_search_filter(&mut sql, filter);
+
+        let count = sql.build_query_scalar::<i64>()
+            .fetch_one(pool.as_ref())
+            .await?;
+
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{db_pool, insert_asset};
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    fn segment(start_time: f64, end_time: f64, speaker: &str, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start_time,
+            end_time,
+            speaker_id: Some(speaker.to_string()),
+            text: text.to_string(),
+        }
+    }
+
+    async fn stored(pool: &DbPool, asset_uuid: Uuid) -> Vec<TranscriptSegment> {
+        let rows: Vec<(f64, f64, Option<String>, String)> = sqlx::query_as(
+            "SELECT start_time, end_time, speaker_id, text FROM transcript_segments WHERE asset_uuid = $1 ORDER BY segment_index"
+        )
+        .bind(asset_uuid)
+        .fetch_all(pool.as_ref())
+        .await
+        .unwrap();
+
+        rows.into_iter()
+            .map(|(start_time, end_time, speaker_id, text)| TranscriptSegment { start_time, end_time, speaker_id, text })
+            .collect()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn segments_round_trip_and_are_replaced(pool: PgPool) {
+        let pool = db_pool(pool);
+        let asset = insert_asset(&pool, "interview.mp4", json!({})).await;
+        let first = vec![
+            segment(0.0, 6.5, "Speaker_1", "Welcome to the interview."),
+            segment(6.5, 14.0, "Speaker_2", "Let's talk about the budget."),
+        ];
+
+        TranscriptRepository::replace_segments(&pool, asset, &first).await.unwrap();
+        assert_eq!(stored(&pool, asset).await, first);
+
+        let second = vec![segment(1.0, 2.0, "Speaker_3", "Corrected transcript.")];
+        TranscriptRepository::replace_segments(&pool, asset, &second).await.unwrap();
+        assert_eq!(stored(&pool, asset).await, second);
+
+        TranscriptRepository::replace_segments(&pool, asset, &[]).await.unwrap();
+        assert!(stored(&pool, asset).await.is_empty());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn search_returns_time_coded_escaped_segments(pool: PgPool) {
+        let pool = db_pool(pool);
+        let asset = insert_asset(&pool, "interview.mp4", json!({})).await;
+        let other = insert_asset(&pool, "weather.mp4", json!({})).await;
+        TranscriptRepository::replace_segments(&pool, asset, &[
+            segment(0.0, 6.5, "Speaker_1", "Welcome to the interview."),
+            segment(6.5, 14.0, "Speaker_2", "<script>alert(1)</script> The budget is 2 < 3 & growing."),
+            segment(20.0, 25.0, "Speaker_1", "Back to the budget later."),
+        ]).await.unwrap();
+        TranscriptRepository::replace_segments(&pool, other, &[segment(0.0, 3.0, "Speaker_1", "Sunny skies.")])
+            .await
+            .unwrap();
+
+        let filter = SearchFilter::default();
+        let matches = TranscriptRepository::search(&pool, "budget", &filter, 5, 10, 0).await.unwrap();
+        assert_eq!(TranscriptRepository::count_matching_assets(&pool, "budget", &filter).await.unwrap(), 1);
+
+        assert_eq!(matches.len(), 2);
+        assert!(matches.iter().all(|m| m.asset_uuid == asset && m.segment_count == 2));
+        // Segments of an asset come back in playback order
+        assert_eq!((matches[0].start_time, matches[0].end_time), (6.5, 14.0));
+        assert_eq!(matches[0].speaker_id.as_deref(), Some("Speaker_2"));
+        assert_eq!(matches[1].start_time, 20.0);
+
+        // HighlightAll keeps the source markup, which must come back escaped
+        let snippet = &matches[0].snippet;
+        assert!(snippet.contains("&lt;script&gt;"), "{}", snippet);
+        assert!(snippet.contains("<mark>budget</mark>"), "{}", snippet);
+        assert!(snippet.contains("2 &lt; 3 &amp; growing"), "{}", snippet);
+        assert_eq!(snippet.replace("<mark>", "").replace("</mark>", "").find('<'), None, "{}", snippet);
+
+        let limited = TranscriptRepository::search(&pool, "budget", &filter, 1, 10, 0).await.unwrap();
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].segment_count, 2);
+    }
+}
@@ -0,0 +1,136 @@
+// Resumable upload session repository
+// I-FR-31: Media upload and ingestion
+
+use crate::db::DbPool;
+use crate::db::advisory_lock::AdvisoryLock;
+use crate::models::upload::UploadSession;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgExecutor, Postgres, Transaction};
+use uuid::Uuid;
+
+pub struct UploadRepository;
+
+impl UploadRepository {
+    pub async fn create(pool: &DbPool, session: &UploadSession) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO upload_sessions (
+                upload_id, filename, upload_length, upload_offset, metadata,
+                staging_path, created_by, created_at, updated_at, expires_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#
+        )
+        .bind(session.upload_id)
+        .bind(&session.filename)
+        .bind(session.upload_length)
+        .bind(session.upload_offset)
+        .bind(&session.metadata)
+        .bind(&session.staging_path)
+        .bind(&session.created_by)
+        .bind(session.created_at)
+        .bind(session.updated_at)
+        .bind(session.expires_at)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    /// Active (unexpired) session
+    pub async fn get(pool: &DbPool, upload_id: Uuid) -> Result<Option<UploadSession>> {
+        let session = sqlx::query_as::<_, UploadSession>(
+            "SELECT * FROM upload_sessions WHERE upload_id = $1 AND expires_at > NOW()"
+        )
+        .bind(upload_id)
+        .fetch_optional(pool.as_ref())
+        .await?;
+
+        Ok(session)
+    }
+
+    /// Offset of an active session, read inside the transaction that commits its next chunk
+    pub async fn current_offset(executor: impl PgExecutor<'_>, upload_id: Uuid) -> Result<Option<i64>> {
+        let offset = sqlx::query_scalar(
+            "SELECT upload_offset FROM upload_sessions WHERE upload_id = $1 AND expires_at > NOW()"
+        )
+        .bind(upload_id)
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(offset)
+    }
+
+    /// Move the offset from `expected` to `offset` and push the expiry out so active uploads are
+    /// not collected. Returns false if another chunk was committed first or the session is gone.
+    pub async fn advance_offset(
+        executor: impl PgExecutor<'_>,
+        upload_id: Uuid,
+        expected: i64,
+        offset: i64,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE upload_sessions
+            SET upload_offset = $3, updated_at = NOW(), expires_at = $4
+            WHERE upload_id = $1 AND upload_offset = $2 AND expires_at > NOW()
+            "#
+        )
+        .bind(upload_id)
+        .bind(expected)
+        .bind(offset)
+        .bind(expires_at)
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete(pool: &DbPool, upload_id: Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM upload_sessions WHERE upload_id = $1")
+            .bind(upload_id)
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_expired(pool: &DbPool, limit: i64) -> Result<Vec<UploadSession>> {
+        let sessions = sqlx::query_as::<_, UploadSession>(
+            "SELECT * FROM upload_sessions WHERE expires_at <= NOW() ORDER BY expires_at LIMIT $1"
+        )
+        .bind(limit)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(sessions)
+    }
+
+    // Serialise chunk commits, complete/cancel and expiry cleanup per session across instances
+    pub async fn try_lock(pool: &DbPool, upload_id: Uuid) -> Result<Option<AdvisoryLock>> {
+        AdvisoryLock::try_acquire(pool, Self::lock_key(upload_id)).await
+    }
+
+    /// The same lock as `try_lock`, held only until `tx` commits a chunk
+    pub async fn try_lock_for_commit(tx: &mut Transaction<'_, Postgres>, upload_id: Uuid) -> Result<bool> {
+        AdvisoryLock::try_acquire_for_transaction(tx, &Self::lock_key(upload_id)).await
+    }
+
+    fn lock_key(upload_id: Uuid) -> String {
+        format!("upload:{}", upload_id)
+    }
+
+    /// Whether the session has expired; checked again once the cleanup holds its lock
+    pub async fn is_expired(pool: &DbPool, upload_id: Uuid) -> Result<bool> {
+        let expired: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM upload_sessions WHERE upload_id = $1 AND expires_at <= NOW())"
+        )
+        .bind(upload_id)
+        .fetch_one(pool.as_ref())
+        .await?;
+
+        Ok(expired)
+    }
+}
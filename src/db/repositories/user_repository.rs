@@ -3,9 +3,9 @@
 // I-FR-23: API key management
 
 use crate::db::DbPool;
-use crate::models::user::{ApiKey, User};
+use crate::models::user::{ApiKey, User, UserRole};
 use anyhow::Result;
-use sqlx::Row;
+use sqlx::{PgExecutor, Row};
 use uuid::Uuid;
 
 pub struct UserRepository;
@@ -64,92 +64,341 @@ impl UserRepository {
         Ok(user)
     }
 
+    // I-FR-23: Admin user management
+    /// Filter by email/name substring, role and disabled state, newest accounts first
+    pub async fn list(
+        pool: &DbPool,
+        search: Option<&str>,
+        role: Option<&UserRole>,
+        disabled: Option<bool>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<User>> {
+        let pattern = search.map(|s| {
+            format!("%{}%", s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"))
+        });
+
+        let users = sqlx::query_as::<_, User>(
+            r#"
+            SELECT * FROM users
+            WHERE ($1::text IS NULL OR email ILIKE $1 OR name ILIKE $1)
+              AND ($2::user_role IS NULL OR role = $2)
+              AND ($3::boolean IS NULL OR (disabled_at IS NOT NULL) = $3)
+            ORDER BY created_at DESC
+            LIMIT $4 OFFSET $5
+            "#
+        )
+        .bind(pattern)
+        .bind(role)
+        .bind(disabled)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(users)
+    }
+
+    /// Profile fields that follow the identity provider on every SSO sign-in
+    pub async fn update_profile(pool: &DbPool, id: Uuid, email: &str, name: &str, role: &UserRole) -> Result<()> {
+        sqlx::query("UPDATE users SET email = $1, name = $2, role = $3 WHERE id = $4")
+            .bind(email)
+            .bind(name)
+            .bind(role)
+            .bind(id)
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_role(executor: impl PgExecutor<'_>, id: Uuid, role: &UserRole) -> Result<bool> {
+        let result = sqlx::query("UPDATE users SET role = $1 WHERE id = $2")
+            .bind(role)
+            .bind(id)
+            .execute(executor)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Lock every enabled admin account until the transaction ends and return their ids, so a
+    /// role change or disable can check that another admin remains
+    pub async fn lock_active_admins(executor: impl PgExecutor<'_>) -> Result<Vec<Uuid>> {
+        let ids = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM users WHERE role = 'ADMIN' AND disabled_at IS NULL ORDER BY id FOR UPDATE"
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// False when the user does not exist or is already disabled
+    pub async fn disable(executor: impl PgExecutor<'_>, id: Uuid, reason: Option<&str>) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE users SET disabled_at = NOW(), disabled_reason = $1 WHERE id = $2 AND disabled_at IS NULL"
+        )
+        .bind(reason)
+        .bind(id)
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// False when the user does not exist or is not disabled
+    pub async fn enable(executor: impl PgExecutor<'_>, id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE users SET disabled_at = NULL, disabled_reason = NULL WHERE id = $1 AND disabled_at IS NOT NULL"
+        )
+        .bind(id)
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     // API Key operations
     pub async fn create_api_key(pool: &DbPool, api_key: &ApiKey) -> Result<Uuid> {
+        Self::insert_api_key(pool.as_ref(), api_key).await
+    }
+
+    async fn insert_api_key<'e, E: sqlx::PgExecutor<'e>>(executor: E, api_key: &ApiKey) -> Result<Uuid> {
         let id = sqlx::query_scalar::<_, Uuid>(
             r#"
             INSERT INTO api_keys (
-                id, user_id, key_name, key_hash, permissions, created_at, status
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                id, user_id, key_name, key_hash, permissions, created_at, status,
+                key_prefix, expires_at, rate_limit_per_minute, rotated_from
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
             RETURNING id
             "#
         )
-        .bind(&api_key.id)
-        .bind(&api_key.user_id)
+        .bind(api_key.id)
+        .bind(api_key.user_id)
         .bind(&api_key.key_name)
         .bind(&api_key.key_hash)
         .bind(serde_json::to_value(&api_key.permissions)?)
         .bind(api_key.created_at)
         .bind(&api_key.status)
-        .fetch_one(pool.as_ref())
+        .bind(&api_key.key_prefix)
+        .bind(api_key.expires_at)
+        .bind(api_key.rate_limit_per_minute)
+        .bind(api_key.rotated_from)
+        .fetch_one(executor)
         .await?;
 
         Ok(id)
     }
 
+    /// Active, unexpired key matching the hash
     pub async fn get_api_key_by_hash(pool: &DbPool, key_hash: &str) -> Result<Option<ApiKey>> {
         let row = sqlx::query(
             r#"
-            SELECT id, user_id, key_name, key_hash, permissions, created_at, last_used, status
-            FROM api_keys WHERE key_hash = $1 AND status = 'ACTIVE'
+            SELECT * FROM api_keys
+            WHERE key_hash = $1 AND status = 'ACTIVE' AND (expires_at IS NULL OR expires_at > NOW())
             "#
         )
         .bind(key_hash)
         .fetch_optional(pool.as_ref())
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(ApiKey {
-                id: row.get("id"),
-                user_id: row.get("user_id"),
-                key_name: row.get("key_name"),
-                key_hash: row.get("key_hash"),
-                permissions: serde_json::from_value(row.get("permissions"))?,
-                created_at: row.get("created_at"),
-                last_used: row.get("last_used"),
-                status: row.get("status"),
-            }))
-        } else {
-            Ok(None)
-        }
+        row.map(|row| api_key_from_row(&row)).transpose()
+    }
+
+    /// One of `user_id`'s keys, in any state
+    pub async fn get_api_key(pool: &DbPool, key_id: Uuid, user_id: Uuid) -> Result<Option<ApiKey>> {
+        let row = sqlx::query("SELECT * FROM api_keys WHERE id = $1 AND user_id = $2")
+            .bind(key_id)
+            .bind(user_id)
+            .fetch_optional(pool.as_ref())
+            .await?;
+
+        row.map(|row| api_key_from_row(&row)).transpose()
     }
 
     pub async fn list_api_keys(pool: &DbPool, user_id: Uuid) -> Result<Vec<ApiKey>> {
         let rows = sqlx::query(
-            r#"
-            SELECT id, user_id, key_name, key_hash, permissions, created_at, last_used, status
-            FROM api_keys WHERE user_id = $1 ORDER BY created_at DESC
-            "#
+            "SELECT * FROM api_keys WHERE user_id = $1 ORDER BY created_at DESC"
         )
         .bind(user_id)
         .fetch_all(pool.as_ref())
         .await?;
 
-        let mut keys = Vec::new();
-        for row in rows {
-            keys.push(ApiKey {
-                id: row.get("id"),
-                user_id: row.get("user_id"),
-                key_name: row.get("key_name"),
-                key_hash: row.get("key_hash"),
-                permissions: serde_json::from_value(row.get("permissions"))?,
-                created_at: row.get("created_at"),
-                last_used: row.get("last_used"),
-                status: row.get("status"),
-            });
+        rows.iter().map(api_key_from_row).collect()
+    }
+
+    /// Store `replacement` and cut the old key's lifetime to `old_expires_at`, atomically.
+    /// False when the old key is no longer active (revoked, expired or rotated concurrently).
+    pub async fn rotate_api_key(
+        pool: &DbPool,
+        old_key_id: Uuid,
+        replacement: &ApiKey,
+        old_expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<bool> {
+        let mut tx = pool.begin().await?;
+
+        // Lock the old key first: a concurrent rotation waits here, and the statement below then
+        // runs with a fresh snapshot that sees the replacement the other rotation committed
+        let locked = sqlx::query("SELECT id FROM api_keys WHERE id = $1 AND user_id = $2 FOR UPDATE")
+            .bind(old_key_id)
+            .bind(replacement.user_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if locked.is_none() {
+            return Ok(false);
         }
 
-        Ok(keys)
+        let updated = sqlx::query(
+            r#"
+            UPDATE api_keys SET expires_at = LEAST(COALESCE(expires_at, $1), $1)
+            WHERE id = $2 AND user_id = $3 AND status = 'ACTIVE'
+              AND (expires_at IS NULL OR expires_at > NOW())
+              AND NOT EXISTS (SELECT 1 FROM api_keys WHERE rotated_from = $2)
+            "#
+        )
+        .bind(old_expires_at)
+        .bind(old_key_id)
+        .bind(replacement.user_id)
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        Self::insert_api_key(&mut *tx, replacement).await?;
+        tx.commit().await?;
+
+        Ok(true)
     }
 
-    pub async fn revoke_api_key(pool: &DbPool, key_id: Uuid) -> Result<()> {
-        sqlx::query(
-            "UPDATE api_keys SET status = 'REVOKED' WHERE id = $1"
+    /// I-FR-25: Set or clear (None) a key's own quota; false when the key does not exist
+    pub async fn set_api_key_rate_limit(pool: &DbPool, key_id: Uuid, rate_limit_per_minute: Option<i32>) -> Result<bool> {
+        let result = sqlx::query("UPDATE api_keys SET rate_limit_per_minute = $1 WHERE id = $2")
+            .bind(rate_limit_per_minute)
+            .bind(key_id)
+            .execute(pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Usable keys that have their own quota
+    pub async fn list_api_key_rate_limits(pool: &DbPool) -> Result<Vec<(Uuid, Option<String>, i32)>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, key_prefix, rate_limit_per_minute FROM api_keys
+            WHERE rate_limit_per_minute IS NOT NULL AND status = 'ACTIVE'
+              AND (expires_at IS NULL OR expires_at > NOW())
+            ORDER BY created_at
+            "#
+        )
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        Ok(rows.iter().map(|row| (row.get("id"), row.get("key_prefix"), row.get("rate_limit_per_minute"))).collect())
+    }
+
+    /// Revoke one of `user_id`'s keys; false when no such key belongs to them
+    pub async fn revoke_api_key(pool: &DbPool, key_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE api_keys SET status = 'REVOKED' WHERE id = $1 AND user_id = $2"
         )
         .bind(key_id)
+        .bind(user_id)
         .execute(pool.as_ref())
         .await?;
 
-        Ok(())
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn api_key_from_row(row: &sqlx::postgres::PgRow) -> Result<ApiKey> {
+    Ok(ApiKey {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        key_name: row.get("key_name"),
+        key_hash: row.get("key_hash"),
+        key_prefix: row.get("key_prefix"),
+        permissions: serde_json::from_value(row.get("permissions"))?,
+        created_at: row.get("created_at"),
+        last_used: row.get("last_used"),
+        status: row.get("status"),
+        expires_at: row.get("expires_at"),
+        rate_limit_per_minute: row.get("rate_limit_per_minute"),
+        rotated_from: row.get("rotated_from"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{db_pool, insert_user};
+    use crate::models::user::ApiKeyStatus;
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    fn api_key(user_id: Uuid, rotated_from: Option<Uuid>) -> ApiKey {
+        ApiKey {
+            id: Uuid::new_v4(),
+            user_id,
+            key_name: "ci".to_string(),
+            key_hash: Uuid::new_v4().to_string(),
+            key_prefix: None,
+            permissions: vec![],
+            created_at: Utc::now(),
+            last_used: None,
+            status: ApiKeyStatus::Active,
+            expires_at: None,
+            rate_limit_per_minute: None,
+            rotated_from,
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn key_is_rotated_only_once(pool: PgPool) {
+        let pool = db_pool(pool);
+        let user_id = insert_user(&pool, "dev@example.com", "DEVELOPER").await;
+        let old = api_key(user_id, None);
+        UserRepository::create_api_key(&pool, &old).await.unwrap();
+        let grace = Utc::now() + Duration::hours(1);
+
+        let first = api_key(user_id, Some(old.id));
+        assert!(UserRepository::rotate_api_key(&pool, old.id, &first, grace).await.unwrap());
+        let second = api_key(user_id, Some(old.id));
+        assert!(!UserRepository::rotate_api_key(&pool, old.id, &second, grace).await.unwrap());
+
+        let stored = UserRepository::get_api_key(&pool, old.id, user_id).await.unwrap().unwrap();
+        assert!(stored.expires_at.is_some());
+        assert!(UserRepository::get_api_key(&pool, second.id, user_id).await.unwrap().is_none());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn concurrent_rotations_issue_one_replacement(pool: PgPool) {
+        let pool = db_pool(pool);
+        let user_id = insert_user(&pool, "dev@example.com", "DEVELOPER").await;
+        let old = api_key(user_id, None);
+        UserRepository::create_api_key(&pool, &old).await.unwrap();
+        let grace = Utc::now() + Duration::hours(1);
+
+        let rotations = (0..8).map(|_| {
+            let pool = pool.clone();
+            let replacement = api_key(user_id, Some(old.id));
+            tokio::spawn(async move { UserRepository::rotate_api_key(&pool, old.id, &replacement, grace).await.unwrap() })
+        });
+        let mut succeeded = 0;
+        for rotation in rotations.collect::<Vec<_>>() {
+            succeeded += rotation.await.unwrap() as usize;
+        }
+        assert_eq!(succeeded, 1);
+
+        let replacements: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM api_keys WHERE rotated_from = $1")
+            .bind(old.id)
+            .fetch_one(pool.as_ref())
+            .await
+            .unwrap();
+        assert_eq!(replacements, 1);
     }
 }
@@ -5,12 +5,32 @@
 use crate::db::DbPool;
 use crate::models::workflow::{ProcessingJob, WorkflowDefinition};
 use anyhow::Result;
+use sqlx::postgres::PgRow;
 use sqlx::Row;
 use uuid::Uuid;
 
 pub struct WorkflowRepository;
 
 impl WorkflowRepository {
+    fn job_from_row(row: &PgRow) -> Result<ProcessingJob> {
+        Ok(ProcessingJob {
+            job_id: row.get("job_id"),
+            asset_uuid: row.get("asset_uuid"),
+            workflow_name: row.get("workflow_name"),
+            status: row.get("status"),
+            progress_percentage: row.get::<Option<i32>, _>("progress_percentage").unwrap_or(0),
+            capabilities_completed: serde_json::from_value(row.get("capabilities_completed"))?,
+            capabilities_failed: serde_json::from_value(row.get("capabilities_failed"))?,
+            error_message: row.get("error_message"),
+            created_at: row.get("created_at"),
+            started_at: row.get("started_at"),
+            completed_at: row.get("completed_at"),
+            estimated_completion: row.get("estimated_completion"),
+            retry_count: row.get::<Option<i32>, _>("retry_count").unwrap_or(0),
+            retry_config: row.get::<Option<serde_json::Value>, _>("retry_config"),
+        })
+    }
+
     pub async fn create_job(pool: &DbPool, job: &ProcessingJob) -> Result<Uuid> {
         let job_id = sqlx::query_scalar::<_, Uuid>(
             r#"
@@ -54,26 +74,7 @@ impl WorkflowRepository {
         .fetch_optional(pool.as_ref())
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(ProcessingJob {
-                job_id: row.get("job_id"),
-                asset_uuid: row.get("asset_uuid"),
-                workflow_name: row.get("workflow_name"),
-                status: row.get("status"),
-                progress_percentage: row.get("progress_percentage"),
-                capabilities_completed: serde_json::from_value(row.get("capabilities_completed"))?,
-                capabilities_failed: serde_json::from_value(row.get("capabilities_failed"))?,
-                error_message: row.get("error_message"),
-                created_at: row.get("created_at"),
-                started_at: row.get("started_at"),
-                completed_at: row.get("completed_at"),
-                estimated_completion: row.get("estimated_completion"),
-                retry_count: row.get("retry_count"),
-                retry_config: row.get::<Option<serde_json::Value>, _>("retry_config"),
-            }))
-        } else {
-            Ok(None)
-        }
+        row.map(|row| Self::job_from_row(&row)).transpose()
     }
 
     pub async fn get_job(pool: &DbPool, job_id: Uuid) -> Result<Option<ProcessingJob>> {
@@ -90,26 +91,7 @@ impl WorkflowRepository {
         .fetch_optional(pool.as_ref())
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(ProcessingJob {
-                job_id: row.get("job_id"),
-                asset_uuid: row.get("asset_uuid"),
-                workflow_name: row.get("workflow_name"),
-                status: row.get("status"),
-                progress_percentage: row.get("progress_percentage"),
-                capabilities_completed: serde_json::from_value(row.get("capabilities_completed"))?,
-                capabilities_failed: serde_json::from_value(row.get("capabilities_failed"))?,
-                error_message: row.get("error_message"),
-                created_at: row.get("created_at"),
-                started_at: row.get("started_at"),
-                completed_at: row.get("completed_at"),
-                estimated_completion: row.get("estimated_completion"),
-                retry_count: row.get("retry_count"),
-                retry_config: row.get::<Option<serde_json::Value>, _>("retry_config"),
-            }))
-        } else {
-            Ok(None)
-        }
+        row.map(|row| Self::job_from_row(&row)).transpose()
     }
 
     pub async fn update_job_status(
@@ -165,4 +147,353 @@ impl WorkflowRepository {
 
         Ok(workflow_id)
     }
+
+    // I-FR-03: Claim the oldest runnable job. Retries become runnable once their backoff has elapsed,
+    // and jobs whose lease expired (crashed worker) are reclaimed. The crashed run counts as an
+    // attempt, so a reclaim is only made while the job has attempts left (see `fail_expired_jobs`).
+    // `max_attempts` is the global policy, overridden by the job's own retry_config.
+    pub async fn claim_next_job(
+        pool: &DbPool,
+        worker_id: &str,
+        lease_seconds: u64,
+        max_attempts: u32,
+    ) -> Result<Option<ProcessingJob>> {
+        let row = sqlx::query(
+            r#"
+            UPDATE processing_jobs
+            SET status = 'PROCESSING', started_at = NOW(), completed_at = NULL,
+                retry_count = CASE WHEN status = 'PROCESSING' THEN COALESCE(retry_count, 0) + 1 ELSE retry_count END,
+                locked_by = $1, heartbeat_at = NOW(),
+                lease_expires_at = NOW() + make_interval(secs => $2)
+            WHERE job_id = (
+                SELECT job_id FROM processing_jobs
+                WHERE status = 'QUEUED'
+                   OR (status = 'RETRYING' AND (next_attempt_at IS NULL OR next_attempt_at <= NOW()))
+                   OR (status = 'PROCESSING' AND (lease_expires_at IS NULL OR lease_expires_at < NOW())
+                       AND COALESCE(retry_count, 0) + 1 < COALESCE(
+                           CASE WHEN jsonb_typeof(retry_config->'max_attempts') = 'number'
+                                THEN (retry_config->>'max_attempts')::numeric END,
+                           $3))
+                ORDER BY COALESCE(next_attempt_at, created_at)
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING job_id, asset_uuid, workflow_name, status, progress_percentage,
+                      capabilities_completed, capabilities_failed, error_message,
+                      created_at, started_at, completed_at, estimated_completion,
+                      retry_count, retry_config
+            "#
+        )
+        .bind(worker_id)
+        .bind(lease_seconds as f64)
+        .bind(max_attempts as i64)
+        .fetch_optional(pool.as_ref())
+        .await?;
+
+        row.map(|row| Self::job_from_row(&row)).transpose()
+    }
+
+    // I-FR-16: Fail jobs whose worker died during their last allowed attempt, which
+    // `claim_next_job` will no longer reclaim. Returns the jobs, with the crashed attempt counted.
+    pub async fn fail_expired_jobs(pool: &DbPool, max_attempts: u32) -> Result<Vec<ProcessingJob>> {
+        let rows = sqlx::query(
+            r#"
+            UPDATE processing_jobs
+            SET status = 'FAILED', completed_at = NOW(), retry_count = COALESCE(retry_count, 0) + 1,
+                error_message = 'Worker lease expired on the final attempt',
+                locked_by = NULL, lease_expires_at = NULL
+            WHERE job_id IN (
+                SELECT job_id FROM processing_jobs
+                WHERE status = 'PROCESSING' AND (lease_expires_at IS NULL OR lease_expires_at < NOW())
+                  AND COALESCE(retry_count, 0) + 1 >= COALESCE(
+                      CASE WHEN jsonb_typeof(retry_config->'max_attempts') = 'number'
+                           THEN (retry_config->>'max_attempts')::numeric END,
+                      $1)
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING job_id, asset_uuid, workflow_name, status, progress_percentage,
+                      capabilities_completed, capabilities_failed, error_message,
+                      created_at, started_at, completed_at, estimated_completion,
+                      retry_count, retry_config
+            "#
+        )
+        .bind(max_attempts as i64)
+        .fetch_all(pool.as_ref())
+        .await?;
+
+        rows.iter().map(Self::job_from_row).collect()
+    }
+
+    // Extend the lease; returns false if the job is no longer held by this worker
+    pub async fn heartbeat(
+        pool: &DbPool,
+        job_id: Uuid,
+        worker_id: &str,
+        lease_seconds: u64,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE processing_jobs
+            SET heartbeat_at = NOW(), lease_expires_at = NOW() + make_interval(secs => $3)
+            WHERE job_id = $1 AND locked_by = $2 AND status = 'PROCESSING'
+            "#
+        )
+        .bind(job_id)
+        .bind(worker_id)
+        .bind(lease_seconds as f64)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn complete_job(
+        pool: &DbPool,
+        job_id: Uuid,
+        worker_id: &str,
+        capabilities_completed: &[String],
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE processing_jobs
+            SET status = 'COMPLETED', progress_percentage = 100, completed_at = NOW(),
+                capabilities_completed = $3, error_message = NULL,
+                locked_by = NULL, lease_expires_at = NULL
+            WHERE job_id = $1 AND locked_by = $2
+            "#
+        )
+        .bind(job_id)
+        .bind(worker_id)
+        .bind(serde_json::to_value(capabilities_completed)?)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn fail_job(
+        pool: &DbPool,
+        job_id: Uuid,
+        worker_id: &str,
+        error_message: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE processing_jobs
+            SET status = 'FAILED', completed_at = NOW(), error_message = $3,
+                locked_by = NULL, lease_expires_at = NULL
+            WHERE job_id = $1 AND locked_by = $2
+            "#
+        )
+        .bind(job_id)
+        .bind(worker_id)
+        .bind(error_message)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // I-FR-16: Put a job back on the queue after a backoff delay.
+    // `worker_id` guards against releasing a job another worker has reclaimed.
+    pub async fn schedule_retry(
+        pool: &DbPool,
+        job_id: Uuid,
+        worker_id: Option<&str>,
+        retry_count: i32,
+        next_attempt_at: chrono::DateTime<chrono::Utc>,
+        error_message: Option<&str>,
+        retry_config: Option<&serde_json::Value>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE processing_jobs
+            SET status = 'RETRYING', retry_count = $3, next_attempt_at = $4,
+                error_message = COALESCE($5, error_message),
+                retry_config = COALESCE($6, retry_config),
+                progress_percentage = 0, completed_at = NULL,
+                locked_by = NULL, lease_expires_at = NULL
+            WHERE job_id = $1 AND ($2::text IS NULL OR locked_by = $2)
+            "#
+        )
+        .bind(job_id)
+        .bind(worker_id)
+        .bind(retry_count)
+        .bind(next_attempt_at)
+        .bind(error_message)
+        .bind(retry_config)
+        .execute(pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{db_pool, insert_asset};
+    use crate::models::workflow::JobStatus;
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    const LEASE_SECONDS: u64 = 300;
+    const MAX_ATTEMPTS: u32 = 3;
+
+    async fn queue_job(pool: &DbPool, name: &str, retry_config: Option<serde_json::Value>) -> Uuid {
+        let asset_uuid = insert_asset(pool, name, json!({})).await;
+        WorkflowRepository::create_job(pool, &ProcessingJob {
+            job_id: Uuid::new_v4(),
+            asset_uuid,
+            workflow_name: "STANDARD_WORKFLOW".to_string(),
+            status: JobStatus::Queued,
+            progress_percentage: 0,
+            capabilities_completed: vec![],
+            capabilities_failed: vec![],
+            error_message: None,
+            created_at: chrono::Utc::now(),
+            started_at: None,
+            completed_at: None,
+            estimated_completion: None,
+            retry_count: 0,
+            retry_config,
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn claim(pool: &DbPool, worker_id: &str) -> Option<ProcessingJob> {
+        WorkflowRepository::claim_next_job(pool, worker_id, LEASE_SECONDS, MAX_ATTEMPTS).await.unwrap()
+    }
+
+    // As if the worker holding the job died a minute after its last heartbeat
+    async fn expire_lease(pool: &DbPool, job_id: Uuid) {
+        sqlx::query("UPDATE processing_jobs SET lease_expires_at = NOW() - INTERVAL '1 minute' WHERE job_id = $1")
+            .bind(job_id)
+            .execute(pool.as_ref())
+            .await
+            .unwrap();
+    }
+
+    async fn job(pool: &DbPool, job_id: Uuid) -> ProcessingJob {
+        WorkflowRepository::get_job(pool, job_id).await.unwrap().unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn concurrent_workers_claim_different_jobs(pool: PgPool) {
+        let pool = db_pool(pool);
+        let first = queue_job(&pool, "a.mp4", None).await;
+        let second = queue_job(&pool, "b.mp4", None).await;
+
+        let (a, b) = tokio::join!(claim(&pool, "worker-a"), claim(&pool, "worker-b"));
+        let mut claimed = vec![a.unwrap().job_id, b.unwrap().job_id];
+        claimed.sort();
+        let mut queued = vec![first, second];
+        queued.sort();
+        assert_eq!(claimed, queued);
+        assert!(claim(&pool, "worker-c").await.is_none());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn claim_skips_a_job_locked_by_another_transaction(pool: PgPool) {
+        let pool = db_pool(pool);
+        let locked = queue_job(&pool, "a.mp4", None).await;
+        let free = queue_job(&pool, "b.mp4", None).await;
+
+        // Another worker is mid-claim on the oldest job
+        let mut tx = pool.begin().await.unwrap();
+        sqlx::query("SELECT job_id FROM processing_jobs WHERE job_id = $1 FOR UPDATE")
+            .bind(locked)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        assert_eq!(claim(&pool, "worker-b").await.unwrap().job_id, free);
+        assert!(claim(&pool, "worker-c").await.is_none());
+        tx.rollback().await.unwrap();
+        assert_eq!(claim(&pool, "worker-c").await.unwrap().job_id, locked);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn expired_lease_is_reclaimed_as_a_new_attempt(pool: PgPool) {
+        let pool = db_pool(pool);
+        let job_id = queue_job(&pool, "a.mp4", None).await;
+        assert_eq!(claim(&pool, "worker-a").await.unwrap().retry_count, 0);
+
+        // A live lease is not reclaimed
+        assert!(claim(&pool, "worker-b").await.is_none());
+
+        expire_lease(&pool, job_id).await;
+        let reclaimed = claim(&pool, "worker-b").await.unwrap();
+        assert_eq!(reclaimed.job_id, job_id);
+        assert_eq!(reclaimed.retry_count, 1);
+
+        // The crashed worker has lost the job
+        assert!(!WorkflowRepository::heartbeat(&pool, job_id, "worker-a", LEASE_SECONDS).await.unwrap());
+        assert!(WorkflowRepository::heartbeat(&pool, job_id, "worker-b", LEASE_SECONDS).await.unwrap());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn job_whose_worker_dies_on_every_attempt_ends_failed(pool: PgPool) {
+        let pool = db_pool(pool);
+        let job_id = queue_job(&pool, "a.mp4", None).await;
+
+        for attempt in 0..MAX_ATTEMPTS as i32 {
+            assert!(WorkflowRepository::fail_expired_jobs(&pool, MAX_ATTEMPTS).await.unwrap().is_empty());
+            assert_eq!(claim(&pool, "worker").await.unwrap().retry_count, attempt);
+            expire_lease(&pool, job_id).await;
+        }
+
+        assert!(claim(&pool, "worker").await.is_none());
+        let failed = WorkflowRepository::fail_expired_jobs(&pool, MAX_ATTEMPTS).await.unwrap();
+        assert_eq!(failed.len(), 1);
+        let job = job(&pool, job_id).await;
+        assert!(matches!(job.status, JobStatus::Failed));
+        assert_eq!(job.retry_count, MAX_ATTEMPTS as i32);
+        assert!(claim(&pool, "worker").await.is_none());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn job_retry_config_overrides_max_attempts_on_reclaim(pool: PgPool) {
+        let pool = db_pool(pool);
+        let single = queue_job(&pool, "a.mp4", Some(json!({ "max_attempts": 1 }))).await;
+        let unparsable = queue_job(&pool, "b.mp4", Some(json!({ "max_attempts": "many" }))).await;
+        for _ in 0..2 {
+            claim(&pool, "worker").await.unwrap();
+        }
+        expire_lease(&pool, single).await;
+        expire_lease(&pool, unparsable).await;
+
+        // The unparsable override falls back to the global policy and is reclaimed
+        assert_eq!(claim(&pool, "worker").await.unwrap().job_id, unparsable);
+        let failed = WorkflowRepository::fail_expired_jobs(&pool, MAX_ATTEMPTS).await.unwrap();
+        assert_eq!(failed.iter().map(|job| job.job_id).collect::<Vec<_>>(), vec![single]);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn only_the_lease_holder_completes_or_fails_a_job(pool: PgPool) {
+        let pool = db_pool(pool);
+        let completed = queue_job(&pool, "a.mp4", None).await;
+        let failed = queue_job(&pool, "b.mp4", None).await;
+        claim(&pool, "worker-a").await.unwrap();
+        claim(&pool, "worker-a").await.unwrap();
+
+        assert!(!WorkflowRepository::complete_job(&pool, completed, "worker-b", &["ocr".to_string()]).await.unwrap());
+        assert!(!WorkflowRepository::fail_job(&pool, failed, "worker-b", "boom").await.unwrap());
+        assert!(matches!(job(&pool, completed).await.status, JobStatus::Processing));
+        assert!(matches!(job(&pool, failed).await.status, JobStatus::Processing));
+
+        assert!(WorkflowRepository::complete_job(&pool, completed, "worker-a", &["ocr".to_string()]).await.unwrap());
+        assert!(WorkflowRepository::fail_job(&pool, failed, "worker-a", "boom").await.unwrap());
+        assert!(matches!(job(&pool, completed).await.status, JobStatus::Completed));
+        let failed = job(&pool, failed).await;
+        assert!(matches!(failed.status, JobStatus::Failed));
+        assert_eq!(failed.error_message.as_deref(), Some("boom"));
+    }
 }
@@ -0,0 +1,43 @@
+// Fixtures for database tests
+// Tests using these run under `#[sqlx::test]`, which creates a fresh, migrated database per test.
+// They are ignored by default since they need a Postgres server:
+// `DATABASE_URL=postgres://... cargo test -- --include-ignored`.
+
+use crate::db::DbPool;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub fn db_pool(pool: PgPool) -> DbPool {
+    Arc::new(pool)
+}
+
+pub async fn insert_user(pool: &DbPool, email: &str, role: &str) -> Uuid {
+    sqlx::query_scalar(
+        "INSERT INTO users (email, name, role) VALUES ($1, $1, $2::user_role) RETURNING id"
+    )
+    .bind(email)
+    .bind(role)
+    .fetch_one(pool.as_ref())
+    .await
+    .expect("insert user")
+}
+
+/// A processed video asset with the given name and enriched metadata. Its `file://` path points
+/// into the temp directory like the local backend's URIs, but no file is written there
+pub async fn insert_asset(pool: &DbPool, name: &str, metadata: serde_json::Value) -> Uuid {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO assets (asset_type, asset_name, source_system, file_path, file_hash, file_size, format, status, enriched_metadata)
+        VALUES ('VIDEO', $1, 'USER_UPLOAD', $2, $3, 1, 'MP4', 'PROCESSED', $4)
+        RETURNING uuid
+        "#
+    )
+    .bind(name)
+    .bind(format!("file://{}", std::env::temp_dir().join(name).display()))
+    .bind(Uuid::new_v4().simple().to_string())
+    .bind(metadata)
+    .fetch_one(pool.as_ref())
+    .await
+    .expect("insert asset")
+}
@@ -3,6 +3,7 @@
 
 use reqwest::Client;
 use anyhow::Result;
+use futures_util::{Stream, StreamExt};
 use serde_json::Value;
 
 pub struct BrightcoveClient {
@@ -14,25 +15,41 @@ pub struct BrightcoveClient {
 
 impl BrightcoveClient {
     pub fn new(api_key: String, account_id: String) -> Self {
+        Self::with_base_url(api_key, account_id, "https://cms.api.brightcove.com")
+    }
+
+    // Point the client at a different CMS host (e.g. a local mock server)
+    pub fn with_base_url(api_key: String, account_id: String, cms_host: &str) -> Self {
         Self {
             client: Client::new(),
             api_key,
             account_id: account_id.clone(),
-            base_url: format!("https://cms.api.brightcove.com/v1/accounts/{}", account_id),
+            base_url: format!("{}/v1/accounts/{}", cms_host.trim_end_matches('/'), account_id),
         }
     }
 
-    // Get videos since timestamp (I-FR-01: Sync with interval)
-    pub async fn get_videos_since(&self, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<Value>> {
+    // Get one page of videos updated since timestamp (I-FR-01: Sync with interval)
+    pub async fn get_videos_since(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Value>> {
         let url = format!("{}/videos", self.base_url);
-        let since_str = since.to_rfc3339();
+        let since_str = since.format("%Y-%m-%dT%H:%M:%SZ").to_string();
         
         let response = self.client
             .get(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
-            .query(&[("since", since_str)])
+            .query(&[
+                ("q", format!("updated_at:{}..", since_str)),
+                ("sort", "updated_at".to_string()),
+                ("limit", limit.to_string()),
+                ("offset", offset.to_string()),
+            ])
             .send()
-            .await?;
+            .await?
+            .error_for_status()?;
 
         let videos: Vec<Value> = response.json().await?;
         Ok(videos)
@@ -66,13 +83,28 @@ impl BrightcoveClient {
             .send()
             .await?;
 
-        let sources: Vec<Value> = response.json().await?;
-        if let Some(source) = sources.first() {
-            if let Some(src) = source.get("src").and_then(|s| s.as_str()) {
-                return Ok(src.to_string());
-            }
+        let sources: Vec<Value> = response.error_for_status()?.json().await?;
+
+        // Prefer a progressive MP4 rendition over streaming manifests
+        let src = sources.iter()
+            .filter(|s| s.get("container").and_then(|c| c.as_str()) == Some("MP4"))
+            .chain(sources.iter())
+            .find_map(|s| s.get("src").and_then(|s| s.as_str()));
+
+        match src {
+            Some(src) => Ok(src.to_string()),
+            None => anyhow::bail!("No download URL found for video {}", video_id),
         }
+    }
+
+    // Download a video source file as a stream of chunks, so a rendition is never held in memory whole
+    pub async fn download_source(&self, src: &str) -> Result<impl Stream<Item = Result<impl AsRef<[u8]>>>> {
+        let response = self.client
+            .get(src)
+            .send()
+            .await?
+            .error_for_status()?;
 
-        anyhow::bail!("No download URL found for video {}", video_id)
+        Ok(response.bytes_stream().map(|chunk| chunk.map_err(anyhow::Error::from)))
     }
 }
@@ -39,8 +39,49 @@ async fn main() -> anyhow::Result<()> {
     db::connection::run_migrations(db_pool.as_ref()).await?;
     info!("Database migrations completed");
 
+    // Storage backend is chosen once here and shared by ingestion and the API
+    let storage = std::sync::Arc::new(services::storage::StorageRegistry::from_config(&config).await?);
+
+    // I-FR-31: Resumable upload sessions, with expired ones collected every 15 minutes
+    let uploads = std::sync::Arc::new(services::upload_sessions::UploadSessionService::new(&config.storage)?);
+    uploads.clone().start_cleanup(db_pool.clone(), std::time::Duration::from_secs(15 * 60));
+
+    // I-FR-21: Corporate SSO; discovery happens on first sign-in so a down IdP does not block startup
+    let oidc = config.security.oidc.clone()
+        .map(|oidc_config| services::oidc::OidcService::new(&config.security.sso_provider, oidc_config))
+        .transpose()?
+        .map(std::sync::Arc::new);
+    if oidc.is_some() {
+        info!("OIDC sign-in enabled for {}", config.security.sso_provider);
+    }
+
+    // I-FR-25: Limits stored via /api/ratelimit/config apply on startup and are re-read every minute
+    let rate_limiter = std::sync::Arc::new(services::rate_limiter::RateLimiter::from_config(&config.security.rate_limit, db_pool.clone())?);
+    if let Err(e) = rate_limiter.reload(&db_pool).await {
+        tracing::warn!("Using configured rate limits; stored limits unavailable: {}", e);
+    }
+    rate_limiter.clone().start_maintenance(db_pool.clone(), std::time::Duration::from_secs(60));
+    info!("Rate limiting counted {}", if config.security.rate_limit.backend == "postgres" { "in Postgres across instances" } else { "in memory per instance" });
+
+    // I-FR-23: Hourly purge of revocation entries, sessions and abandoned sign-ins past their expiry
+    services::session_cleanup::SessionCleanup::start_cleanup(db_pool.clone(), std::time::Duration::from_secs(60 * 60));
+
+    // I-FR-01: Start background sync for every registered controller
+    services::sync_scheduler::SyncScheduler::new(db_pool.clone(), config.sync.clone(), storage.clone())
+        .with_env_controllers()
+        .start()
+        .await?;
+
+    // I-FR-03: Start durable processing job workers
+    services::job_worker::JobWorkerPool::new(
+        db_pool.clone(),
+        config.queue.clone(),
+        config.retry.clone(),
+        storage.clone(),
+    ).start();
+
     // Build application router with all API endpoints
-    let app = api::create_router(db_pool).await?;
+    let app = api::create_router(db_pool, storage, uploads, rate_limiter, oidc, config.retry.clone()).await?;
 
     // Start server
     let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
@@ -3,15 +3,21 @@
 // I-FR-23: API key validation
 
 use axum::{
-    extract::Request,
-    http::{HeaderMap, StatusCode},
+    async_trait,
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, HeaderMap},
     middleware::Next,
     response::Response,
 };
+use crate::api::error::ApiError;
 use crate::db::DbPool;
-use crate::db::repositories::user_repository::UserRepository;
+use crate::db::repositories::{token_repository::TokenRepository, user_repository::UserRepository};
+use crate::middleware::rate_limit::RateLimitSubject;
+use crate::models::permission::Grants;
+use crate::models::user::UserRole;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
@@ -19,27 +25,64 @@ pub struct Claims {
     pub email: String,
     pub role: String,
     pub exp: usize,
+    pub token_type: TokenType,
+    pub jti: String, // Unique token id, used for revocation
+    pub sid: String, // Session the token was issued under
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenType {
+    Access,
+    Refresh,
+}
+
+/// The caller of a protected route, built from the `Claims` inserted by `authenticate`
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub id: Uuid,
+    pub email: String,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let claims = parts.extensions.get::<Claims>().ok_or_else(|| {
+            tracing::error!("AuthUser used on a route without the authentication middleware");
+            ApiError::unauthorized("Authentication required")
+        })?;
+        let id = Uuid::parse_str(&claims.user_id).map_err(|_| {
+            tracing::warn!("Token user_id {} is not a UUID", claims.user_id);
+            ApiError::unauthorized("Invalid token subject")
+        })?;
+
+        Ok(Self {
+            id,
+            email: claims.email.clone(),
+        })
+    }
 }
 
 pub async fn authenticate(
     headers: HeaderMap,
     mut request: Request,
     next: Next,
-) -> Result<Response, StatusCode> {
+) -> Result<Response, ApiError> {
     // Check for Authorization header
     let auth_header = headers.get("Authorization")
         .and_then(|h| h.to_str().ok())
         .ok_or_else(|| {
             tracing::warn!("Missing Authorization header");
-            StatusCode::UNAUTHORIZED
+            ApiError::unauthorized("Missing Authorization header")
         })?;
 
     // Get database pool from extensions (set by state)
     let db_pool = request.extensions()
         .get::<Arc<DbPool>>()
         .ok_or_else(|| {
-            tracing::error!("Database pool not found in request extensions");
-            StatusCode::INTERNAL_SERVER_ERROR
+            ApiError::internal("Database pool not found in request extensions")
         })?;
 
     // Check if it's a Bearer token (SSO/JWT) or API key
@@ -49,15 +92,43 @@ pub async fn authenticate(
         // Validate JWT token using JWT service
         use crate::utils::jwt::JWTService;
         
-        match JWTService::validate_token(token) {
+        match JWTService::validate_token(token, TokenType::Access) {
             Ok(claims) => {
-                // Add user info to request extensions
+                // Revocation list: the token itself (logout), its whole session (logout, refresh reuse,
+                // role change) or its user (account disabled)
+                let (Ok(jti), Ok(session_id)) = (Uuid::parse_str(&claims.jti), Uuid::parse_str(&claims.sid)) else {
+                    tracing::warn!("Token without valid jti/sid");
+                    return Err(ApiError::unauthorized("Invalid token"));
+                };
+                match TokenRepository::is_revoked(db_pool, jti, session_id).await {
+                    Ok(false) => {}
+                    Ok(true) => {
+                        tracing::warn!("Rejected revoked token {} for user {}", jti, claims.user_id);
+                        return Err(ApiError::unauthorized("Token has been revoked"));
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+
+                // Add user info, the role's permissions and its quota to request extensions
+                let role = UserRole::from_claim(&claims.role);
+                let grants = role.as_ref()
+                    .map(Grants::for_role)
+                    .unwrap_or_else(|| {
+                        tracing::warn!("Unknown role {} in token; granting no permissions", claims.role);
+                        Grants::default()
+                    });
+                request.extensions_mut().insert(RateLimitSubject {
+                    identifier: format!("user:{}", claims.user_id),
+                    role,
+                    key_limit: None,
+                });
                 request.extensions_mut().insert(claims);
+                request.extensions_mut().insert(grants);
                 Ok(next.run(request).await)
             }
             Err(e) => {
                 tracing::warn!("JWT validation failed: {:?}", e);
-                Err(StatusCode::UNAUTHORIZED)
+                Err(ApiError::unauthorized("Invalid or expired token"))
             }
         }
     } else if auth_header.starts_with("ApiKey ") {
@@ -82,37 +153,48 @@ pub async fn authenticate(
                 
                 // Get user and add to request extensions
                 match UserRepository::get_by_id(&db_pool, api_key_record.user_id).await {
+                    Ok(Some(user)) if user.disabled_at.is_some() => {
+                        tracing::warn!("Rejected API key of disabled user {}", user.id);
+                        Err(ApiError::unauthorized("Account is disabled"))
+                    }
                     Ok(Some(user)) => {
                         let claims = Claims {
                             user_id: user.id.to_string(),
                             email: user.email.clone(),
                             role: format!("{:?}", user.role),
                             exp: (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp() as usize,
+                            token_type: TokenType::Access,
+                            jti: Uuid::new_v4().to_string(),
+                            // API keys have no session; the key itself is what gets revoked
+                            sid: api_key_record.id.to_string(),
                         };
+                        // I-FR-23: Key scopes are capped by the owner's role
+                        let grants = Grants::for_api_key(&user.role, &api_key_record.permissions);
+                        // I-FR-25: Each key has its own quota, never above its owner's role
+                        request.extensions_mut().insert(RateLimitSubject {
+                            identifier: format!("api_key:{}", api_key_record.id),
+                            role: Some(user.role.clone()),
+                            key_limit: api_key_record.rate_limit_per_minute.map(|limit| limit.max(1) as u32),
+                        });
                         request.extensions_mut().insert(claims);
+                        request.extensions_mut().insert(grants);
                         Ok(next.run(request).await)
                     }
                     Ok(None) => {
                         tracing::warn!("User not found for API key");
-                        Err(StatusCode::UNAUTHORIZED)
-                    }
-                    Err(e) => {
-                        tracing::error!("Database error: {:?}", e);
-                        Err(StatusCode::INTERNAL_SERVER_ERROR)
+                        Err(ApiError::unauthorized("API key owner not found"))
                     }
+                    Err(e) => Err(e.into()),
                 }
             }
             Ok(None) => {
                 tracing::warn!("Invalid API key");
-                Err(StatusCode::UNAUTHORIZED)
-            }
-            Err(e) => {
-                tracing::error!("Database error: {:?}", e);
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
+                Err(ApiError::unauthorized("Invalid API key"))
             }
+            Err(e) => Err(e.into()),
         }
     } else {
         tracing::warn!("Invalid Authorization header format");
-        Err(StatusCode::UNAUTHORIZED)
+        Err(ApiError::unauthorized("Authorization must be a Bearer token or ApiKey"))
     }
 }
@@ -0,0 +1,88 @@
+// Authorization middleware
+// I-FR-23: Secure API and access governance
+// Runs after `authenticate`; each protected route declares the permission it needs.
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use crate::api::error::ApiError;
+use crate::models::permission::{Grants, Permission};
+
+/// Route layer: `.route_layer(from_fn_with_state(Permission::WriteMetadata, require_permission))`
+pub async fn require_permission(
+    State(permission): State<Permission>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let allowed = request.extensions()
+        .get::<Grants>()
+        .map(|grants| grants.allows(permission))
+        .unwrap_or(false);
+
+    if !allowed {
+        tracing::warn!("Denied {} {}: missing permission {}", request.method(), request.uri().path(), permission);
+        return forbidden(permission);
+    }
+
+    next.run(request).await
+}
+
+fn forbidden(permission: Permission) -> Response {
+    ApiError::forbidden(format!("Missing required permission: {}", permission))
+        .with_detail("permission", permission.as_str())
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::user::UserRole;
+    use axum::{body::Body, http::StatusCode, middleware::from_fn_with_state, routing::get, Router};
+    use tower::Service;
+
+    async fn call(grants: Option<Grants>, required: Permission) -> (StatusCode, serde_json::Value) {
+        let mut app = Router::new().route(
+            "/protected",
+            get(|| async { "ok" }).route_layer(from_fn_with_state(required, require_permission)),
+        );
+        let mut request = Request::builder().uri("/protected").body(Body::empty()).unwrap();
+        if let Some(grants) = grants {
+            request.extensions_mut().insert(grants);
+        }
+
+        // Router is always ready, so it can be called without polling readiness first
+        let response = app.call(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+    }
+
+    #[tokio::test]
+    async fn viewer_is_forbidden_from_rollback_with_scope_named() {
+        let (status, body) = call(Some(Grants::for_role(&UserRole::Viewer)), Permission::RollbackAssets).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"], "FORBIDDEN");
+        assert_eq!(body["status_code"], 403);
+        assert_eq!(body["details"][0]["field"], "permission");
+        assert_eq!(body["details"][0]["message"], "rollback:assets");
+    }
+
+    #[tokio::test]
+    async fn each_role_reaches_only_its_permitted_routes() {
+        for role in [UserRole::Admin, UserRole::ContentManager, UserRole::Editor, UserRole::Developer, UserRole::Viewer] {
+            for permission in Permission::ALL {
+                let (status, _) = call(Some(Grants::for_role(&role)), *permission).await;
+                let expected = if role.permissions().contains(permission) { StatusCode::OK } else { StatusCode::FORBIDDEN };
+                assert_eq!(status, expected, "{:?} calling a route that requires {}", role, permission);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn request_without_grants_is_forbidden() {
+        let (status, _) = call(None, Permission::ReadAssets).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+}
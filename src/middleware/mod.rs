@@ -1,10 +1,15 @@
 // Middleware modules
 // I-FR-21: SSO authentication
-// I-FR-23: API key validation
+// I-FR-23: API key validation and route permissions
 // I-FR-25: Rate limiting
+// I-FR-12: Request ids for logs and error responses
 
 pub mod auth;
+pub mod authorization;
 pub mod rate_limit;
+pub mod request_id;
 
 pub use auth::*;
+pub use authorization::*;
 pub use rate_limit::*;
+pub use request_id::*;
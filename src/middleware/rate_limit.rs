@@ -1,76 +1,143 @@
 // Rate limiting middleware
 // I-FR-25: Rate limiting and access throttling
+// Runs after `authenticate`, which records who the request counts against: an API key, or the user for JWTs.
 
+use crate::api::error::ApiError;
+use crate::models::user::UserRole;
+use crate::services::rate_limiter::{RateLimitDecision, RateLimiter};
 use axum::{
-    extract::Request,
-    http::StatusCode,
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::sync::Arc;
 
-#[derive(Clone)]
-struct RateLimitState {
-    requests: Arc<Mutex<HashMap<String, Vec<Instant>>>>,
-}
-
-impl RateLimitState {
-    fn new() -> Self {
-        Self {
-            requests: Arc::new(Mutex::new(HashMap::new())),
-        }
-    }
+const RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+const RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+const RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
 
-    fn check_rate_limit(&self, key: &str, limit: u32, window: Duration) -> bool {
-        let mut requests = self.requests.lock().unwrap();
-        let now = Instant::now();
-        
-        // Clean old requests outside the window
-        if let Some(timestamps) = requests.get_mut(key) {
-            timestamps.retain(|&time| now.duration_since(time) < window);
-            
-            if timestamps.len() >= limit as usize {
-                return false; // Rate limit exceeded
-            }
-            
-            timestamps.push(now);
-        } else {
-            requests.insert(key.to_string(), vec![now]);
-        }
-        
-        true
-    }
+/// Who a request counts against, inserted by `authenticate`
+#[derive(Debug, Clone)]
+pub struct RateLimitSubject {
+    pub identifier: String,
+    pub role: Option<UserRole>,
+    /// The API key's own quota, if it has one
+    pub key_limit: Option<u32>,
 }
 
 pub async fn rate_limit_middleware(
+    State(limiter): State<Arc<RateLimiter>>,
     request: Request,
     next: Next,
-) -> Result<Response, StatusCode> {
-    // Get rate limit config (default: 100 requests per minute)
-    let limit = 100u32;
-    let window = Duration::from_secs(60);
-    
-    // Get identifier (user ID from auth, or IP address)
-    let identifier = request.headers()
-        .get("x-user-id")
-        .and_then(|h| h.to_str().ok())
-        .unwrap_or_else(|| {
-            request.headers()
-                .get("x-forwarded-for")
-                .and_then(|h| h.to_str().ok())
-                .unwrap_or("unknown")
-        });
-    
-    // TODO: Use shared state or Redis for distributed rate limiting
-    // For now, using in-memory (not suitable for production with multiple instances)
-    static RATE_LIMIT_STATE: std::sync::OnceLock<RateLimitState> = std::sync::OnceLock::new();
-    let state = RATE_LIMIT_STATE.get_or_init(|| RateLimitState::new());
-    
-    if !state.check_rate_limit(identifier, limit, window) {
-        return Err(StatusCode::TOO_MANY_REQUESTS);
+) -> Response {
+    // Requests that reach here without a subject were not authenticated and are rejected by auth
+    let Some(subject) = request.extensions().get::<RateLimitSubject>().cloned() else {
+        return next.run(request).await;
+    };
+
+    let limit = limiter.limit_for(subject.role.as_ref(), subject.key_limit).await;
+    let decision = limiter.check(&subject.identifier, limit).await;
+
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        tracing::warn!("Rate limit of {}/min exceeded by {}", limit, subject.identifier);
+        too_many_requests(&decision)
+    };
+    set_rate_limit_headers(&mut response, &decision);
+    response
+}
+
+fn reset_seconds(decision: &RateLimitDecision) -> u64 {
+    decision.reset.as_secs().max(1)
+}
+
+fn set_rate_limit_headers(response: &mut Response, decision: &RateLimitDecision) {
+    let headers = response.headers_mut();
+    headers.insert(RATE_LIMIT_LIMIT, HeaderValue::from(decision.limit));
+    headers.insert(RATE_LIMIT_REMAINING, HeaderValue::from(decision.remaining));
+    headers.insert(RATE_LIMIT_RESET, HeaderValue::from(reset_seconds(decision)));
+}
+
+fn too_many_requests(decision: &RateLimitDecision) -> Response {
+    let mut response = ApiError::new(
+        StatusCode::TOO_MANY_REQUESTS,
+        "RATE_LIMITED",
+        format!("Rate limit of {} requests per minute exceeded", decision.limit),
+    )
+    .into_response();
+    response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(reset_seconds(decision)));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{RateLimitConfig, RoleRateLimits};
+    use axum::{body::Body, routing::get, Router};
+    use tower::Service;
+
+    fn limiter() -> Arc<RateLimiter> {
+        Arc::new(RateLimiter::in_memory(RateLimitConfig {
+            default_limit_per_minute: 2,
+            per_role: RoleRateLimits { admin: 5, content_manager: 2, editor: 2, developer: 3, viewer: 1 },
+            backend: "memory".to_string(),
+        }))
+    }
+
+    async fn call(limiter: &Arc<RateLimiter>, subject: &RateLimitSubject) -> Response {
+        let mut app = Router::new()
+            .route("/limited", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(limiter.clone(), rate_limit_middleware));
+        let mut request = Request::builder().uri("/limited").body(Body::empty()).unwrap();
+        request.extensions_mut().insert(subject.clone());
+        app.call(request).await.unwrap()
+    }
+
+    fn key(role: UserRole, key_limit: Option<u32>) -> RateLimitSubject {
+        RateLimitSubject { identifier: format!("api_key:{}", uuid::Uuid::new_v4()), role: Some(role), key_limit }
+    }
+
+    #[tokio::test]
+    async fn each_key_is_held_to_its_own_quota() {
+        let limiter = limiter();
+        let key = key(UserRole::Developer, Some(2));
+        let other = self::key(UserRole::Developer, Some(2));
+
+        assert_eq!(call(&limiter, &key).await.status(), StatusCode::OK);
+        assert_eq!(call(&limiter, &key).await.status(), StatusCode::OK);
+        let limited = call(&limiter, &key).await;
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(limited.headers().contains_key(header::RETRY_AFTER));
+        assert_eq!(limited.headers()[RATE_LIMIT_REMAINING], "0");
+        let body = axum::body::to_bytes(limited.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], "RATE_LIMITED");
+        assert_eq!(body["status_code"], 429);
+        assert!(body.get("details").is_none());
+        assert_eq!(call(&limiter, &other).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn key_quota_is_capped_at_role_limit_and_reported_in_headers() {
+        let limiter = limiter();
+        let response = call(&limiter, &key(UserRole::Viewer, Some(10))).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[RATE_LIMIT_LIMIT], "1");
+        assert_eq!(response.headers()[RATE_LIMIT_REMAINING], "0");
+        assert!(response.headers().contains_key(RATE_LIMIT_RESET));
+    }
+
+    #[tokio::test]
+    async fn updated_role_limits_apply_immediately() {
+        let limiter = limiter();
+        let mut config = limiter.config().await;
+        config.per_role.viewer = 3;
+        limiter.set_config(config).await;
+
+        let response = call(&limiter, &key(UserRole::Viewer, None)).await;
+        assert_eq!(response.headers()[RATE_LIMIT_LIMIT], "3");
     }
-    
-    Ok(next.run(request).await)
 }
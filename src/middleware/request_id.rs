@@ -0,0 +1,148 @@
+// Request id middleware
+// I-FR-12: Request logging
+// Tags every request with an id (the caller's `x-request-id` if usable, otherwise a new one),
+// echoes it in the response and makes it available to error responses and logs.
+// Error responses that reach here without a JSON body (extractor rejections, unknown routes)
+// are rewritten into the standard error body.
+
+use crate::api::error::ApiError;
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+const MAX_REQUEST_ID_LEN: usize = 128;
+// Rejection messages are short; anything longer is not worth echoing
+const MAX_BARE_BODY_BYTES: usize = 4096;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Id of the request being handled, if called from within one
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+pub async fn request_id_middleware(request: Request, next: Next) -> Response {
+    let request_id = request.headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+        .filter(|id| id.chars().all(|c| c.is_ascii_alphanumeric() || "-_.:".contains(c)))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let mut response = REQUEST_ID
+        .scope(request_id.clone(), async move {
+            let response = next.run(request).await;
+            if is_bare_error(&response) {
+                into_error_body(response).await
+            } else {
+                response
+            }
+        })
+        .await;
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+fn is_bare_error(response: &Response) -> bool {
+    let status = response.status();
+    let is_json = response.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|content_type| content_type.starts_with("application/json"))
+        .unwrap_or(false);
+    (status.is_client_error() || status.is_server_error()) && !is_json
+}
+
+// Keep the status and headers (Allow, WWW-Authenticate, ...), replace the body
+async fn into_error_body(response: Response) -> Response {
+    let (mut parts, body) = response.into_parts();
+    let text = axum::body::to_bytes(body, MAX_BARE_BODY_BYTES).await
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes.to_vec()).ok())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty());
+    let message = text
+        .or_else(|| parts.status.canonical_reason().map(str::to_string))
+        .unwrap_or_else(|| "Request failed".to_string());
+
+    let error = ApiError::new(parts.status, status_code_name(parts.status), message).into_response();
+    let (error_parts, error_body) = error.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(header::CONTENT_TYPE, error_parts.headers[header::CONTENT_TYPE].clone());
+    Response::from_parts(parts, Body::new(error_body))
+}
+
+fn status_code_name(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST => "BAD_REQUEST",
+        StatusCode::UNAUTHORIZED => "UNAUTHORIZED",
+        StatusCode::FORBIDDEN => "FORBIDDEN",
+        StatusCode::NOT_FOUND => "NOT_FOUND",
+        StatusCode::METHOD_NOT_ALLOWED => "METHOD_NOT_ALLOWED",
+        StatusCode::CONFLICT => "CONFLICT",
+        StatusCode::PAYLOAD_TOO_LARGE => "PAYLOAD_TOO_LARGE",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "UNSUPPORTED_MEDIA_TYPE",
+        StatusCode::UNPROCESSABLE_ENTITY => "VALIDATION_FAILED",
+        StatusCode::TOO_MANY_REQUESTS => "RATE_LIMITED",
+        StatusCode::SERVICE_UNAVAILABLE => "SERVICE_UNAVAILABLE",
+        status if status.is_server_error() => "INTERNAL_ERROR",
+        _ => "REQUEST_FAILED",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use tower::Service;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/missing", get(|| async { ApiError::not_found("Asset", 7) }))
+            .route("/bare", get(|| async { (StatusCode::UNPROCESSABLE_ENTITY, "Failed to parse the request body") }))
+            .layer(axum::middleware::from_fn(request_id_middleware))
+    }
+
+    async fn json_body(response: Response) -> serde_json::Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn error_body_and_header_carry_the_callers_request_id() {
+        let request = Request::builder()
+            .uri("/missing")
+            .header(&REQUEST_ID_HEADER, "trace-123")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().call(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[&REQUEST_ID_HEADER], "trace-123");
+        assert_eq!(json_body(response).await["request_id"], "trace-123");
+    }
+
+    #[tokio::test]
+    async fn bare_rejections_become_json_errors() {
+        let request = Request::builder().uri("/bare").body(Body::empty()).unwrap();
+        let response = app().call(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = json_body(response).await;
+        assert_eq!(body["error"], "VALIDATION_FAILED");
+        assert_eq!(body["message"], "Failed to parse the request body");
+        assert!(body["request_id"].is_string());
+    }
+}
@@ -22,7 +22,7 @@ pub struct ActionRecord {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
-#[sqlx(type_name = "action_type", rename_all = "UPPERCASE")]
+#[sqlx(type_name = "action_type", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ActionType {
     Ingress,
     Egress,
@@ -32,6 +32,9 @@ pub enum ActionType {
     ConflictResolved,
     JobRetry,
     Rollback,
+    UserRoleChanged,
+    UserDisabled,
+    UserEnabled,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
@@ -43,7 +46,7 @@ pub enum Direction {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
-#[sqlx(type_name = "action_status", rename_all = "UPPERCASE")]
+#[sqlx(type_name = "action_status", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ActionStatus {
     Success,
     Failed,
@@ -30,6 +30,39 @@ pub struct Asset {
     pub updated_at: Option<DateTime<Utc>>,
     pub processing_completed_at: Option<DateTime<Utc>>,
     pub uploaded_by: Option<Uuid>,
+    #[schema(value_type = Option<TechnicalMetadata>)]
+    pub technical_metadata: Option<sqlx::types::Json<TechnicalMetadata>>, // Probed from the file itself
+    pub mime_type: Option<String>, // Sniffed from the file content at ingest
+}
+
+/// Properties read from the media container (see services::media_probe)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct TechnicalMetadata {
+    pub container: String, // MP4, MOV, WAV, FLAC, MP3, PNG, JPEG, GIF, WEBP, PDF
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_seconds: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitrate: Option<u64>, // bits per second
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_codec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_codec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channels: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bits_per_sample: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_count: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
@@ -42,13 +75,16 @@ pub enum AssetType {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
-#[sqlx(type_name = "source_system", rename_all = "UPPERCASE")]
+#[sqlx(type_name = "source_system", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum SourceSystem {
     Brightcove,
     Cloudinary,
     Omnystudio,
+    #[sqlx(rename = "ONECMS")]
     OneCms,
+    #[sqlx(rename = "MISSYS3")]
     MissyS3,
+    #[sqlx(rename = "DALETS3")]
     DaletS3,
     UserUpload,    // I-FR-31: Manual upload
     ApiSubmission, // I-FR-29: API submission
@@ -65,6 +101,123 @@ pub enum AssetStatus {
     Archived, // I-FR-15: Lifecycle management
 }
 
+impl AssetType {
+    /// Accepts the API name ("Video") or the database label ("VIDEO"), in any case
+    pub fn from_name(name: &str) -> Option<AssetType> {
+        match name.to_ascii_uppercase().as_str() {
+            "VIDEO" => Some(AssetType::Video),
+            "IMAGE" => Some(AssetType::Image),
+            "AUDIO" => Some(AssetType::Audio),
+            "TEXT" => Some(AssetType::Text),
+            _ => None,
+        }
+    }
+}
+
+impl SourceSystem {
+    /// Accepts the API name ("UserUpload") or the database label ("USER_UPLOAD"), in any case
+    pub fn from_name(name: &str) -> Option<SourceSystem> {
+        match name.to_ascii_uppercase().replace('_', "").as_str() {
+            "BRIGHTCOVE" => Some(SourceSystem::Brightcove),
+            "CLOUDINARY" => Some(SourceSystem::Cloudinary),
+            "OMNYSTUDIO" => Some(SourceSystem::Omnystudio),
+            "ONECMS" => Some(SourceSystem::OneCms),
+            "MISSYS3" => Some(SourceSystem::MissyS3),
+            "DALETS3" => Some(SourceSystem::DaletS3),
+            "USERUPLOAD" => Some(SourceSystem::UserUpload),
+            "APISUBMISSION" => Some(SourceSystem::ApiSubmission),
+            _ => None,
+        }
+    }
+}
+
+impl AssetStatus {
+    /// Accepts the API name ("Processed") or the database label ("PROCESSED"), in any case
+    pub fn from_name(name: &str) -> Option<AssetStatus> {
+        match name.to_ascii_uppercase().as_str() {
+            "STAGED" => Some(AssetStatus::Staged),
+            "QUEUED" => Some(AssetStatus::Queued),
+            "PROCESSING" => Some(AssetStatus::Processing),
+            "PROCESSED" => Some(AssetStatus::Processed),
+            "FAILED" => Some(AssetStatus::Failed),
+            "ARCHIVED" => Some(AssetStatus::Archived),
+            _ => None,
+        }
+    }
+}
+
+/// Asset listing filters; every field that is set must match (AND).
+/// Multiple values of one field match any of them (OR).
+#[derive(Debug, Clone, Default)]
+pub struct AssetFilter {
+    pub asset_types: Vec<AssetType>,
+    pub source_systems: Vec<SourceSystem>,
+    pub statuses: Vec<AssetStatus>,
+    pub uploaded_by: Option<Uuid>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+    /// I-FR-30: Operational tags that must be present with these values
+    pub operational_tags: Vec<(String, String)>,
+    /// Top-level enriched metadata fields; array fields match if they contain the value
+    pub metadata: Vec<(String, String)>,
+}
+
+/// Columns assets can be listed by; ties are broken by uuid so pages never overlap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetSortField {
+    CreatedAt,
+    UpdatedAt, // Never-edited assets sort by created_at
+    AssetName,
+    FileSize,
+}
+
+impl AssetSortField {
+    pub fn from_name(name: &str) -> Option<AssetSortField> {
+        match name {
+            "created_at" => Some(AssetSortField::CreatedAt),
+            "updated_at" => Some(AssetSortField::UpdatedAt),
+            "asset_name" => Some(AssetSortField::AssetName),
+            "file_size" => Some(AssetSortField::FileSize),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssetSortField::CreatedAt => "created_at",
+            AssetSortField::UpdatedAt => "updated_at",
+            AssetSortField::AssetName => "asset_name",
+            AssetSortField::FileSize => "file_size",
+        }
+    }
+
+    /// The value of this column for `asset`, as stored in a cursor
+    pub fn value_of(&self, asset: &Asset) -> AssetSortValue {
+        match self {
+            AssetSortField::CreatedAt => AssetSortValue::Time(asset.created_at),
+            AssetSortField::UpdatedAt => AssetSortValue::Time(asset.updated_at.unwrap_or(asset.created_at)),
+            AssetSortField::AssetName => AssetSortValue::Text(asset.asset_name.clone()),
+            AssetSortField::FileSize => AssetSortValue::Number(asset.file_size),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssetSortValue {
+    Time(DateTime<Utc>),
+    Text(String),
+    Number(i64),
+}
+
+/// Position after the last asset of a page: its sort value and uuid
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetCursor {
+    pub value: AssetSortValue,
+    pub uuid: Uuid,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssetVersion {
     pub asset_uuid: Uuid,
@@ -0,0 +1,16 @@
+// Controller configuration model
+// I-FR-01: Configurable sync intervals
+// I-FR-12: Configurable logging levels
+
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ControllerConfig {
+    pub controller_name: String,
+    pub sync_interval_minutes: Option<i32>,
+    pub logging_level: Option<String>,
+    pub last_sync_at: Option<DateTime<Utc>>,
+    pub is_enabled: Option<bool>,
+    pub updated_at: DateTime<Utc>,
+}
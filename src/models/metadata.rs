@@ -48,6 +48,15 @@ pub struct TextRecognitionMetadata {
     pub confidence: f64,
 }
 
+/// A time-coded stretch of speech; times are seconds from the start of the media
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TranscriptSegment {
+    pub start_time: f64,
+    pub end_time: f64,
+    pub speaker_id: Option<String>,
+    pub text: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SentimentMetadata {
     pub overall: String,
@@ -6,9 +6,17 @@ pub mod action_record;
 pub mod workflow;
 pub mod metadata;
 pub mod user;
+pub mod controller;
+pub mod upload;
+pub mod permission;
+pub mod saved_search;
 
 pub use asset::*;
 pub use action_record::*;
 pub use workflow::*;
 pub use metadata::*;
 pub use user::*;
+pub use controller::*;
+pub use upload::*;
+pub use permission::*;
+pub use saved_search::*;
@@ -0,0 +1,215 @@
+// Permission model
+// I-FR-23: Secure API and access governance
+// Scopes granted to JWT roles and API keys, and required per route (see api/routes)
+
+use crate::models::user::UserRole;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Permission {
+    #[serde(rename = "read:assets")]
+    ReadAssets,
+    #[serde(rename = "read:metadata")]
+    ReadMetadata,
+    #[serde(rename = "write:metadata")]
+    WriteMetadata,
+    #[serde(rename = "upload:media")]
+    UploadMedia,
+    #[serde(rename = "submit:media")]
+    SubmitMedia,
+    #[serde(rename = "read:jobs")]
+    ReadJobs,
+    #[serde(rename = "manage:workflows")]
+    ManageWorkflows,
+    #[serde(rename = "read:audit")]
+    ReadAudit,
+    #[serde(rename = "manage:config")]
+    ManageConfig,
+    #[serde(rename = "rollback:assets")]
+    RollbackAssets,
+    #[serde(rename = "manage:api-keys")]
+    ManageApiKeys,
+    #[serde(rename = "manage:users")]
+    ManageUsers,
+}
+
+impl Permission {
+    pub const ALL: &'static [Permission] = &[
+        Permission::ReadAssets,
+        Permission::ReadMetadata,
+        Permission::WriteMetadata,
+        Permission::UploadMedia,
+        Permission::SubmitMedia,
+        Permission::ReadJobs,
+        Permission::ManageWorkflows,
+        Permission::ReadAudit,
+        Permission::ManageConfig,
+        Permission::RollbackAssets,
+        Permission::ManageApiKeys,
+        Permission::ManageUsers,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ReadAssets => "read:assets",
+            Permission::ReadMetadata => "read:metadata",
+            Permission::WriteMetadata => "write:metadata",
+            Permission::UploadMedia => "upload:media",
+            Permission::SubmitMedia => "submit:media",
+            Permission::ReadJobs => "read:jobs",
+            Permission::ManageWorkflows => "manage:workflows",
+            Permission::ReadAudit => "read:audit",
+            Permission::ManageConfig => "manage:config",
+            Permission::RollbackAssets => "rollback:assets",
+            Permission::ManageApiKeys => "manage:api-keys",
+            Permission::ManageUsers => "manage:users",
+        }
+    }
+
+    /// Expand a scope string; `*` stands for every permission, unknown scopes for none
+    pub fn parse_scope(scope: &str) -> Vec<Permission> {
+        if scope.trim() == "*" {
+            return Permission::ALL.to_vec();
+        }
+        Permission::ALL.iter().copied().filter(|p| p.as_str() == scope.trim()).collect()
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl UserRole {
+    pub fn permissions(&self) -> &'static [Permission] {
+        use Permission::*;
+        match self {
+            UserRole::Admin => Permission::ALL,
+            UserRole::ContentManager => &[ReadAssets, ReadMetadata, WriteMetadata, UploadMedia, ReadJobs, ManageWorkflows],
+            UserRole::Editor => &[ReadAssets, ReadMetadata, WriteMetadata, ReadJobs],
+            UserRole::Developer => &[ReadAssets, ReadMetadata, SubmitMedia, ReadJobs],
+            UserRole::Viewer => &[ReadAssets, ReadMetadata],
+        }
+    }
+
+    /// Whether a key owned by this role may be issued `scope`: it must be known and within the role
+    pub fn can_grant(&self, scope: &str) -> bool {
+        let requested = Permission::parse_scope(scope);
+        !requested.is_empty() && requested.iter().all(|p| self.permissions().contains(p))
+    }
+
+    /// Parse the role name carried in JWT claims (`format!("{:?}", role)`)
+    pub fn from_claim(role: &str) -> Option<UserRole> {
+        match role {
+            "Admin" => Some(UserRole::Admin),
+            "ContentManager" => Some(UserRole::ContentManager),
+            "Editor" => Some(UserRole::Editor),
+            "Developer" => Some(UserRole::Developer),
+            "Viewer" => Some(UserRole::Viewer),
+            _ => None,
+        }
+    }
+}
+
+/// Effective permissions of an authenticated request
+#[derive(Debug, Clone, Default)]
+pub struct Grants {
+    permissions: Vec<Permission>,
+}
+
+impl Grants {
+    pub fn for_role(role: &UserRole) -> Self {
+        Self { permissions: role.permissions().to_vec() }
+    }
+
+    /// An API key is limited to the scopes it was issued with and never exceeds its owner's role.
+    /// Keys issued without scopes act with the owner's full role.
+    pub fn for_api_key(role: &UserRole, scopes: &[String]) -> Self {
+        if scopes.is_empty() {
+            return Self::for_role(role);
+        }
+        let requested: Vec<Permission> = scopes.iter().flat_map(|s| Permission::parse_scope(s)).collect();
+        Self {
+            permissions: role.permissions().iter().copied().filter(|p| requested.contains(p)).collect(),
+        }
+    }
+
+    pub fn allows(&self, permission: Permission) -> bool {
+        self.permissions.contains(&permission)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn granted(grants: &Grants) -> Vec<&'static str> {
+        Permission::ALL.iter().filter(|p| grants.allows(**p)).map(|p| p.as_str()).collect()
+    }
+
+    fn allowed(role: UserRole) -> Vec<&'static str> {
+        granted(&Grants::for_role(&role))
+    }
+
+    #[test]
+    fn admin_has_every_permission() {
+        assert_eq!(allowed(UserRole::Admin).len(), Permission::ALL.len());
+    }
+
+    #[test]
+    fn content_manager_uploads_and_edits_but_cannot_administer() {
+        assert_eq!(
+            allowed(UserRole::ContentManager),
+            ["read:assets", "read:metadata", "write:metadata", "upload:media", "read:jobs", "manage:workflows"]
+        );
+    }
+
+    #[test]
+    fn editor_edits_metadata_only() {
+        assert_eq!(allowed(UserRole::Editor), ["read:assets", "read:metadata", "write:metadata", "read:jobs"]);
+    }
+
+    #[test]
+    fn developer_submits_media_but_cannot_edit() {
+        assert_eq!(allowed(UserRole::Developer), ["read:assets", "read:metadata", "submit:media", "read:jobs"]);
+    }
+
+    #[test]
+    fn viewer_is_read_only() {
+        let grants = Grants::for_role(&UserRole::Viewer);
+        assert_eq!(allowed(UserRole::Viewer), ["read:assets", "read:metadata"]);
+        assert!(!grants.allows(Permission::RollbackAssets));
+        assert!(!grants.allows(Permission::ManageConfig));
+    }
+
+    #[test]
+    fn api_key_scopes_cannot_exceed_owner_role() {
+        let scopes = vec!["*".to_string()];
+        let grants = Grants::for_api_key(&UserRole::Viewer, &scopes);
+        assert_eq!(granted(&grants), allowed(UserRole::Viewer));
+
+        let scopes = vec!["submit:media".to_string(), "unknown:scope".to_string()];
+        let grants = Grants::for_api_key(&UserRole::Developer, &scopes);
+        assert_eq!(granted(&grants), ["submit:media"]);
+    }
+
+    #[test]
+    fn api_keys_can_only_be_issued_scopes_within_the_owner_role() {
+        assert!(UserRole::Developer.can_grant("submit:media"));
+        assert!(!UserRole::Developer.can_grant("write:metadata"));
+        assert!(!UserRole::Developer.can_grant("*"));
+        assert!(!UserRole::Developer.can_grant("unknown:scope"));
+        assert!(UserRole::Admin.can_grant("*"));
+    }
+
+    #[test]
+    fn role_names_round_trip_through_claims() {
+        for role in [UserRole::Admin, UserRole::ContentManager, UserRole::Editor, UserRole::Developer, UserRole::Viewer] {
+            let parsed = UserRole::from_claim(&format!("{:?}", role)).unwrap();
+            assert_eq!(parsed.permissions(), role.permissions());
+        }
+        assert!(UserRole::from_claim("Superuser").is_none());
+    }
+}
@@ -0,0 +1,28 @@
+// Saved search model
+// I-FR-22: Named searches re-evaluated as new assets finish processing
+
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SavedSearch {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub query: String,
+    pub filters: serde_json::Value, // Serialized SearchFilter
+    pub webhook_url: Option<String>,
+    pub last_checked_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// An asset that started matching a saved search
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SavedSearchMatch {
+    pub asset_uuid: Uuid,
+    pub asset_name: String,
+    pub title: Option<String>,
+    pub matched_at: DateTime<Utc>,
+}
@@ -0,0 +1,26 @@
+// Resumable upload session model
+// I-FR-31: Media upload and ingestion
+
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UploadSession {
+    pub upload_id: Uuid,
+    pub filename: String,
+    pub upload_length: i64,
+    pub upload_offset: i64,
+    pub metadata: serde_json::Value,
+    pub staging_path: String,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl UploadSession {
+    pub fn is_complete(&self) -> bool {
+        self.upload_offset >= self.upload_length
+    }
+}
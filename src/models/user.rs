@@ -15,10 +15,12 @@ pub struct User {
     pub sso_provider_id: Option<String>, // I-FR-21: SSO integration
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub last_login: Option<chrono::DateTime<chrono::Utc>>,
+    pub disabled_at: Option<chrono::DateTime<chrono::Utc>>, // I-FR-23: Set while an admin has disabled the account
+    pub disabled_reason: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
-#[sqlx(type_name = "user_role", rename_all = "UPPERCASE")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "user_role", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum UserRole {
     Admin,
     ContentManager,
@@ -33,10 +35,20 @@ pub struct ApiKey {
     pub user_id: Uuid,
     pub key_name: String,
     pub key_hash: String, // Stored as hash, never plaintext
+    pub key_prefix: Option<String>, // Shown to identify the key; None for keys issued before prefixes
     pub permissions: Vec<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub last_used: Option<chrono::DateTime<chrono::Utc>>,
     pub status: ApiKeyStatus,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>, // None never expires
+    pub rate_limit_per_minute: Option<i32>, // I-FR-25: None uses the owner's role default
+    pub rotated_from: Option<Uuid>,
+}
+
+impl ApiKey {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.map(|at| at <= chrono::Utc::now()).unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
@@ -0,0 +1,368 @@
+// Content sniffing
+// Determines AssetType and MIME type from the file's leading bytes rather than its extension.
+// Executables, archives, unrecognised binaries and markup a browser would run as a document
+// (HTML, SVG) are rejected outright.
+
+use crate::models::asset::AssetType;
+use crate::services::storage::StorageRegistry;
+use crate::utils::http_range::ByteRange;
+use anyhow::Result;
+use tokio::io::AsyncReadExt;
+
+/// Leading bytes needed to recognise every format below
+pub const SNIFF_BYTES: usize = 8 * 1024;
+
+/// What the content turned out to be
+#[derive(Debug, Clone)]
+pub struct DetectedContent {
+    pub asset_type: AssetType,
+    pub mime_type: &'static str,
+    pub format: &'static str,
+    /// The filename's extension is not one this format is normally saved with
+    pub extension_mismatch: bool,
+}
+
+impl DetectedContent {
+    /// Note an extension/content mismatch in the asset's metadata so it can be reviewed
+    pub fn record_mismatch(&self, metadata: &mut serde_json::Value, filename: &str) {
+        if self.extension_mismatch && metadata.is_object() {
+            metadata["content_mismatch"] = serde_json::json!({
+                "extension": extension(filename),
+                "detected_format": self.format,
+                "detected_mime_type": self.mime_type,
+            });
+        }
+    }
+}
+
+struct Kind {
+    asset_type: AssetType,
+    mime_type: &'static str,
+    format: &'static str,
+    extensions: &'static [&'static str],
+}
+
+const fn kind(
+    asset_type: AssetType,
+    mime_type: &'static str,
+    format: &'static str,
+    extensions: &'static [&'static str],
+) -> Kind {
+    Kind { asset_type, mime_type, format, extensions }
+}
+
+// Video
+const MP4: Kind = kind(AssetType::Video, "video/mp4", "MP4", &["mp4", "m4v"]);
+const MOV: Kind = kind(AssetType::Video, "video/quicktime", "MOV", &["mov", "qt"]);
+const THREE_GP: Kind = kind(AssetType::Video, "video/3gpp", "3GP", &["3gp", "3g2"]);
+const AVI: Kind = kind(AssetType::Video, "video/x-msvideo", "AVI", &["avi"]);
+const WEBM: Kind = kind(AssetType::Video, "video/webm", "WEBM", &["webm"]);
+const MKV: Kind = kind(AssetType::Video, "video/x-matroska", "MKV", &["mkv", "mka"]);
+const FLV: Kind = kind(AssetType::Video, "video/x-flv", "FLV", &["flv"]);
+const WMV: Kind = kind(AssetType::Video, "video/x-ms-wmv", "WMV", &["wmv", "asf"]);
+// Audio
+const M4A: Kind = kind(AssetType::Audio, "audio/mp4", "M4A", &["m4a", "m4b"]);
+const WAV: Kind = kind(AssetType::Audio, "audio/wav", "WAV", &["wav"]);
+const FLAC: Kind = kind(AssetType::Audio, "audio/flac", "FLAC", &["flac"]);
+const OGG: Kind = kind(AssetType::Audio, "audio/ogg", "OGG", &["ogg", "oga", "opus"]);
+const MP3: Kind = kind(AssetType::Audio, "audio/mpeg", "MP3", &["mp3", "mp2", "mpga"]);
+const AAC: Kind = kind(AssetType::Audio, "audio/aac", "AAC", &["aac"]);
+const AMR: Kind = kind(AssetType::Audio, "audio/amr", "AMR", &["amr"]);
+const WMA: Kind = kind(AssetType::Audio, "audio/x-ms-wma", "WMA", &["wma"]);
+// Images
+const PNG: Kind = kind(AssetType::Image, "image/png", "PNG", &["png"]);
+const JPEG: Kind = kind(AssetType::Image, "image/jpeg", "JPEG", &["jpg", "jpeg", "jpe"]);
+const GIF: Kind = kind(AssetType::Image, "image/gif", "GIF", &["gif"]);
+const WEBP: Kind = kind(AssetType::Image, "image/webp", "WEBP", &["webp"]);
+const BMP: Kind = kind(AssetType::Image, "image/bmp", "BMP", &["bmp"]);
+const TIFF: Kind = kind(AssetType::Image, "image/tiff", "TIFF", &["tif", "tiff"]);
+const ICO: Kind = kind(AssetType::Image, "image/x-icon", "ICO", &["ico"]);
+// Documents
+const PDF: Kind = kind(AssetType::Text, "application/pdf", "PDF", &["pdf"]);
+const DOC: Kind = kind(AssetType::Text, "application/msword", "DOC", &["doc"]);
+const DOCX: Kind = kind(
+    AssetType::Text,
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "DOCX",
+    &["docx"],
+);
+const ODT: Kind = kind(AssetType::Text, "application/vnd.oasis.opendocument.text", "ODT", &["odt"]);
+const RTF: Kind = kind(AssetType::Text, "application/rtf", "RTF", &["rtf"]);
+const XML: Kind = kind(AssetType::Text, "application/xml", "XML", &["xml"]);
+const JSON: Kind = kind(AssetType::Text, "application/json", "JSON", &["json"]);
+const CSV: Kind = kind(AssetType::Text, "text/csv", "CSV", &["csv"]);
+const MARKDOWN: Kind = kind(AssetType::Text, "text/markdown", "MD", &["md", "markdown"]);
+const PLAIN_TEXT: Kind = kind(AssetType::Text, "text/plain", "TXT", &["txt", "text", "log"]);
+
+/// Identify `head` (the first `SNIFF_BYTES` of the file, or all of it if shorter).
+/// Returns `None` for content we do not accept as an asset.
+pub fn sniff(head: &[u8], filename: &str) -> Option<DetectedContent> {
+    let extension = extension(filename);
+    let kind = detect(head, &extension)?;
+
+    Some(DetectedContent {
+        asset_type: kind.asset_type.clone(),
+        mime_type: kind.mime_type,
+        format: kind.format,
+        extension_mismatch: !extension.is_empty() && !kind.extensions.contains(&extension.as_str()),
+    })
+}
+
+/// Sniff an object already in storage by fetching only its leading bytes
+pub async fn sniff_stored(storage: &StorageRegistry, uri: &str, filename: &str) -> Result<Option<DetectedContent>> {
+    let size = storage.stat(uri).await?.size;
+    let mut head = Vec::new();
+    if size > 0 {
+        let end = size.min(SNIFF_BYTES as u64) - 1;
+        let mut reader = storage.stream(uri, Some(ByteRange { start: 0, end })).await?;
+        reader.read_to_end(&mut head).await?;
+    }
+    Ok(sniff(&head, filename))
+}
+
+/// MIME types a browser renders as a document able to run script. Anything stored under one of
+/// these (assets ingested before sniffing, or typed by an upstream provider) is only ever served
+/// as an attachment.
+pub fn is_active_content(mime_type: &str) -> bool {
+    let essence = mime_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    matches!(
+        essence.as_str(),
+        "text/html" | "application/xhtml+xml" | "image/svg+xml" | "text/xml" | "application/xml"
+            | "text/javascript" | "application/javascript"
+    )
+}
+
+fn extension(filename: &str) -> String {
+    match filename.rsplit_once('.') {
+        Some((_, ext)) => ext.to_lowercase(),
+        None => String::new(),
+    }
+}
+
+fn detect(head: &[u8], extension: &str) -> Option<&'static Kind> {
+    let at = |offset: usize, magic: &[u8]| head.get(offset..offset + magic.len()) == Some(magic);
+
+    // ISO base media: the major brand separates QuickTime, audio-only MPEG-4 and 3GPP
+    if at(4, b"ftyp") {
+        return Some(match head.get(8..12)? {
+            b"qt  " => &MOV,
+            b"M4A " | b"M4B " => &M4A,
+            brand if brand.starts_with(b"3g") => &THREE_GP,
+            _ => &MP4,
+        });
+    }
+
+    if at(0, b"RIFF") {
+        return match head.get(8..12)? {
+            b"WAVE" => Some(&WAV),
+            b"AVI " => Some(&AVI),
+            b"WEBP" => Some(&WEBP),
+            _ => None,
+        };
+    }
+
+    let kind = match head {
+        // Executables (PE, ELF, Mach-O) are never assets
+        [b'M', b'Z', ..] | [0x7F, b'E', b'L', b'F', ..] => return None,
+        [0xFE, 0xED, 0xFA, 0xCE | 0xCF, ..] | [0xCE | 0xCF, 0xFA, 0xED, 0xFE, ..] | [0xCA, 0xFE, 0xBA, 0xBE, ..] => return None,
+        // Matroska and WebM share EBML; the DocType names which one
+        h if h.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) => {
+            if contains(h, b"webm") { &WEBM } else { &MKV }
+        }
+        // ASF carries both WMV and WMA
+        [0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, ..] => {
+            if extension == "wma" { &WMA } else { &WMV }
+        }
+        h if h.starts_with(b"FLV\x01") => &FLV,
+        h if h.starts_with(b"fLaC") => &FLAC,
+        h if h.starts_with(b"OggS") => &OGG,
+        h if h.starts_with(b"#!AMR") => &AMR,
+        h if h.starts_with(b"ID3") => &MP3,
+        // ADTS (layer bits 00) before MPEG audio frame sync
+        [0xFF, b1, ..] if b1 & 0xF6 == 0xF0 => &AAC,
+        [0xFF, b1, ..] if b1 & 0xE0 == 0xE0 && b1 & 0x06 != 0 => &MP3,
+        h if h.starts_with(b"\x89PNG\r\n\x1a\n") => &PNG,
+        [0xFF, 0xD8, 0xFF, ..] => &JPEG,
+        h if h.starts_with(b"GIF87a") || h.starts_with(b"GIF89a") => &GIF,
+        // "BM" alone is too common; require a known DIB header size as well
+        h if h.starts_with(b"BM") && matches!(h.get(14..18), Some([12 | 40 | 52 | 56 | 108 | 124, 0, 0, 0])) => &BMP,
+        h if h.starts_with(b"II*\0") || h.starts_with(b"MM\0*") => &TIFF,
+        // Icon directory: reserved 0, type 1, at least one image whose reserved byte is 0
+        h if h.starts_with(&[0x00, 0x00, 0x01, 0x00]) && matches!(h.get(4..10), Some([n, _, _, _, _, 0]) if *n > 0) => &ICO,
+        h if h.starts_with(b"%PDF-") => &PDF,
+        h if h.starts_with(b"{\\rtf") => &RTF,
+        // OLE compound file (legacy Office)
+        [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1, ..] => &DOC,
+        // ZIP is only accepted as an office document, never as a bare archive
+        h if h.starts_with(b"PK\x03\x04") => {
+            if contains(h, b"application/vnd.oasis.opendocument.text") {
+                &ODT
+            } else if contains(h, b"word/") {
+                &DOCX
+            } else {
+                return None;
+            }
+        }
+        h => return detect_text(h, extension),
+    };
+
+    Some(kind)
+}
+
+/// Text formats have no magic bytes; anything that is valid UTF-8 without binary control bytes is text
+fn detect_text(head: &[u8], extension: &str) -> Option<&'static Kind> {
+    if head.iter().any(|b| (b.is_ascii_control() && !b.is_ascii_whitespace() && *b != 0x1B) || *b == 0x7F) {
+        return None;
+    }
+    // A multi-byte character may be cut off at the end of the head
+    let text = match std::str::from_utf8(head) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&head[..e.valid_up_to()]).ok()?,
+        Err(_) => return None,
+    };
+    let trimmed = text.trim_start_matches('\u{feff}').trim_start();
+    let lower = trimmed.chars().take(512).collect::<String>().to_lowercase();
+
+    // Scripts are not assets, whatever they are named
+    if trimmed.starts_with("#!") {
+        return None;
+    }
+    // Nor is markup that would execute script when served from our origin
+    if lower.starts_with("<svg")
+        || lower.starts_with("<!doctype html")
+        || lower.starts_with("<html")
+        || (lower.starts_with("<?xml") && (lower.contains("<svg") || lower.contains("<html")))
+        || matches!(extension, "html" | "htm" | "xhtml" | "svg")
+    {
+        return None;
+    }
+
+    let kind = if lower.starts_with("<?xml") {
+        &XML
+    } else if (trimmed.starts_with('{') || trimmed.starts_with('[')) && extension != "txt" {
+        &JSON
+    } else {
+        // Remaining text formats are indistinguishable by content
+        match extension {
+            "csv" => &CSV,
+            "md" | "markdown" => &MARKDOWN,
+            "xml" => &XML,
+            _ => &PLAIN_TEXT,
+        }
+    };
+
+    Some(kind)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(head: &[u8], filename: &str) -> Option<&'static str> {
+        sniff(head, filename).map(|content| content.format)
+    }
+
+    #[test]
+    fn recognises_magic_bytes() {
+        let cases: &[(&[u8], &str)] = &[
+            (b"\0\0\0\x18ftypisom\0\0\0\0", "MP4"),
+            (b"\0\0\0\x14ftypqt  \0\0\0\0", "MOV"),
+            (b"\0\0\0\x18ftypM4A \0\0\0\0", "M4A"),
+            (b"\0\0\0\x18ftyp3gp4\0\0\0\0", "3GP"),
+            (b"RIFF\0\0\0\0WAVEfmt ", "WAV"),
+            (b"RIFF\0\0\0\0AVI LIST", "AVI"),
+            (b"RIFF\0\0\0\0WEBPVP8 ", "WEBP"),
+            (b"\x1a\x45\xdf\xa3\x42\x82\x84webm", "WEBM"),
+            (b"\x1a\x45\xdf\xa3\x42\x82\x88matroska", "MKV"),
+            (b"FLV\x01\x05", "FLV"),
+            (b"fLaC\0\0\0\x22", "FLAC"),
+            (b"OggS\0\x02", "OGG"),
+            (b"#!AMR\n", "AMR"),
+            (b"ID3\x04\0", "MP3"),
+            (&[0xFF, 0xFB, 0x90, 0x00], "MP3"),
+            (&[0xFF, 0xF1, 0x50, 0x80], "AAC"),
+            (b"\x89PNG\r\n\x1a\n\0\0", "PNG"),
+            (&[0xFF, 0xD8, 0xFF, 0xE0], "JPEG"),
+            (b"GIF89a\x01\0", "GIF"),
+            (b"II*\0\x08\0", "TIFF"),
+            (b"%PDF-1.7\n", "PDF"),
+            (b"{\\rtf1\\ansi", "RTF"),
+            (&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1, 0x00], "DOC"),
+            (b"PK\x03\x04\x14\0word/document.xml", "DOCX"),
+        ];
+        for (head, expected) in cases {
+            assert_eq!(format(head, "file"), Some(*expected), "{:?}", head);
+        }
+    }
+
+    #[test]
+    fn rejects_executables_and_archives() {
+        assert!(sniff(b"MZ\x90\0\x03", "setup.exe").is_none());
+        assert!(sniff(b"\x7fELF\x02\x01", "video.mp4").is_none());
+        assert!(sniff(&[0xCF, 0xFA, 0xED, 0xFE, 0x07], "a.out").is_none());
+        assert!(sniff(b"PK\x03\x04\x14\0data.bin", "archive.zip").is_none());
+        assert!(sniff(b"#!/bin/sh\necho hi\n", "notes.txt").is_none());
+        assert!(sniff(&[0x00, 0x01, 0x02, 0x03, 0x04], "blob.bin").is_none());
+    }
+
+    #[test]
+    fn rejects_active_markup() {
+        assert!(sniff(b"<svg xmlns=\"http://www.w3.org/2000/svg\"><script>alert(1)</script></svg>", "logo.png").is_none());
+        assert!(sniff(b"<?xml version=\"1.0\"?>\n<svg onload=\"alert(1)\"/>", "image.svg").is_none());
+        assert!(sniff(b"<!DOCTYPE html><html><script>alert(1)</script>", "page.txt").is_none());
+        assert!(sniff(b"  <HTML><body>", "notes").is_none());
+        assert!(sniff(b"just some words", "page.html").is_none());
+        assert!(sniff(b"just some words", "drawing.svg").is_none());
+    }
+
+    #[test]
+    fn text_formats_follow_extension() {
+        assert_eq!(format(b"a,b,c\n1,2,3\n", "data.csv"), Some("CSV"));
+        assert_eq!(format(b"# Title\n", "readme.md"), Some("MD"));
+        assert_eq!(format(b"{\"a\": 1}", "data.json"), Some("JSON"));
+        assert_eq!(format(b"{not json}", "notes.txt"), Some("TXT"));
+        assert_eq!(format(b"<?xml version=\"1.0\"?><feed/>", "feed.xml"), Some("XML"));
+        assert_eq!(format("caf\u{e9}".as_bytes(), "menu.txt"), Some("TXT"));
+    }
+
+    #[test]
+    fn flags_extension_mismatch() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0";
+        assert!(!sniff(png, "photo.png").unwrap().extension_mismatch);
+        assert!(!sniff(png, "photo.PNG").unwrap().extension_mismatch);
+        assert!(!sniff(png, "photo").unwrap().extension_mismatch);
+
+        let content = sniff(png, "photo.jpg").unwrap();
+        assert!(content.extension_mismatch);
+        assert!(matches!(content.asset_type, AssetType::Image));
+
+        let mut metadata = serde_json::json!({});
+        content.record_mismatch(&mut metadata, "photo.jpg");
+        assert_eq!(metadata["content_mismatch"]["extension"], "jpg");
+        assert_eq!(metadata["content_mismatch"]["detected_format"], "PNG");
+        assert_eq!(metadata["content_mismatch"]["detected_mime_type"], "image/png");
+    }
+
+    #[test]
+    fn no_mismatch_recorded_for_matching_extension() {
+        let content = sniff(b"%PDF-1.4\n", "report.pdf").unwrap();
+        let mut metadata = serde_json::json!({});
+        content.record_mismatch(&mut metadata, "report.pdf");
+        assert!(metadata.get("content_mismatch").is_none());
+    }
+
+    #[test]
+    fn active_content_types() {
+        assert!(is_active_content("text/html"));
+        assert!(is_active_content("text/html; charset=utf-8"));
+        assert!(is_active_content("Image/SVG+XML"));
+        assert!(is_active_content("application/xml"));
+        assert!(!is_active_content("image/png"));
+        assert!(!is_active_content("text/plain"));
+        assert!(!is_active_content("video/mp4"));
+    }
+}
@@ -6,6 +6,9 @@ use oauth2::{
     AuthUrl,
     ClientId,
     ClientSecret,
+    CsrfToken,
+    PkceCodeChallenge,
+    PkceCodeVerifier,
     RedirectUrl,
     Scope,
     TokenResponse,
@@ -52,24 +55,29 @@ impl GoogleOAuthService {
     }
     
     /// Generate Google OAuth authorization URL
-    pub fn get_authorization_url(&self) -> (url::Url, oauth2::CsrfToken) {
+    /// The CSRF state and PKCE verifier must be kept until the callback (see OAuthStateRepository)
+    pub fn get_authorization_url(&self) -> (url::Url, CsrfToken, PkceCodeVerifier) {
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
         let mut request = self.client
-            .authorize_url(oauth2::CsrfToken::new_random);
+            .authorize_url(CsrfToken::new_random)
+            .set_pkce_challenge(pkce_challenge);
         
         // Request scopes
         request = request.add_scope(Scope::new("openid".to_string()));
         request = request.add_scope(Scope::new("email".to_string()));
         request = request.add_scope(Scope::new("profile".to_string()));
         
-        request.url()
+        let (url, csrf_token) = request.url();
+        (url, csrf_token, pkce_verifier)
     }
     
     /// Exchange authorization code for access token
-    pub async fn exchange_code(&self, code: String) -> Result<String> {
+    pub async fn exchange_code(&self, code: String, pkce_verifier: String) -> Result<String> {
         let code = AuthorizationCode::new(code);
         
         let token_result = self.client
             .exchange_code(code)
+            .set_pkce_verifier(PkceCodeVerifier::new(pkce_verifier))
             .request_async(async_http_client)
             .await?;
         
@@ -3,18 +3,51 @@
 // I-FR-22: Graph-based search
 
 use anyhow::Result;
+use serde_json::json;
 use uuid::Uuid;
+use crate::db::DbPool;
+use crate::db::repositories::{
+    asset_repository::AssetRepository,
+    graph_repository::{GraphRepository, RelatedAsset},
+};
+
+// Relationship types computed here; edges of other types are left alone on re-index
+pub const SHARED_KEYWORD: &str = "shared_keyword";
+pub const SHARED_TOPIC: &str = "shared_topic";
+pub const SAME_SERIES: &str = "same_series";
+pub const TEMPORAL: &str = "temporal";
+pub const SAME_SPEAKER: &str = "same_speaker";
+const COMPUTED_RELATIONSHIPS: [&str; 5] = [SHARED_KEYWORD, SHARED_TOPIC, SAME_SERIES, TEMPORAL, SAME_SPEAKER];
+
+// Below this Jaccard overlap two assets are not considered related
+const MIN_OVERLAP_SCORE: f64 = 0.1;
+const TEMPORAL_WINDOW_HOURS: f64 = 24.0;
+// Keeps hub assets (a common keyword, a busy day) from linking to everything
+const MAX_EDGES_PER_TYPE: i64 = 25;
 
 pub struct GraphService;
 
 impl GraphService {
     // I-FR-20: Index asset in graph database
-    pub async fn index_asset(asset_uuid: Uuid) -> Result<()> {
-        // TODO: 
-        // - Create asset node in Neptune/Neo4j
-        // - Create keyword/topic nodes
-        // - Create relationships
-        todo!()
+    // Links the asset to its keyword (tags and AI keywords) and topic nodes, then recomputes its relationships
+    pub async fn index_asset(db_pool: &DbPool, asset_uuid: Uuid) -> Result<()> {
+        let asset = AssetRepository::get_by_uuid(db_pool, asset_uuid).await?
+            .ok_or_else(|| anyhow::anyhow!("Asset {} not found", asset_uuid))?;
+
+        let strings = |field: &str| -> Vec<String> {
+            asset.enriched_metadata.get(field)
+                .and_then(|v| v.as_array())
+                .map(|values| values.iter().filter_map(|v| v.as_str()).map(|s| s.to_string()).collect())
+                .unwrap_or_default()
+        };
+        let mut keywords = strings("tags");
+        keywords.extend(strings("keywords"));
+        keywords.sort();
+        keywords.dedup();
+        let topics = strings("topics");
+
+        GraphRepository::index_asset(db_pool, asset_uuid, &keywords, &topics).await?;
+        Self::rebuild_relationships(db_pool, asset_uuid).await
     }
     
     // I-FR-22: Search graph database
@@ -27,8 +60,119 @@ impl GraphService {
     }
     
     // Update graph after metadata change
-    pub async fn sync_metadata_update(asset_uuid: Uuid) -> Result<()> {
-        // TODO: Update graph database when metadata changes
-        todo!()
+    pub async fn sync_metadata_update(db_pool: &DbPool, asset_uuid: Uuid) -> Result<()> {
+        Self::index_asset(db_pool, asset_uuid).await
+    }
+
+    /// I-FR-20: Recompute the asset's edges from its current graph nodes, series, creation time
+    /// and speakers; edges that no longer hold are removed
+    pub async fn rebuild_relationships(db_pool: &DbPool, asset_uuid: Uuid) -> Result<()> {
+        let mut edges = Vec::new();
+
+        for (node_type, relationship_type) in [("KEYWORD", SHARED_KEYWORD), ("TOPIC", SHARED_TOPIC)] {
+            let neighbors = GraphRepository::shared_node_neighbors(
+                db_pool, asset_uuid, node_type, MIN_OVERLAP_SCORE, MAX_EDGES_PER_TYPE,
+            ).await?;
+            edges.extend(neighbors.into_iter().map(|(uuid, score, shared)| RelatedAsset {
+                asset_uuid: uuid,
+                relationship_type,
+                data: json!({ "score": score, "method": "jaccard", "shared": shared }),
+            }));
+        }
+
+        let series = GraphRepository::same_series_neighbors(db_pool, asset_uuid, MAX_EDGES_PER_TYPE).await?;
+        edges.extend(series.into_iter().map(|(uuid, series)| RelatedAsset {
+            asset_uuid: uuid,
+            relationship_type: SAME_SERIES,
+            data: json!({ "score": 1.0, "series": series }),
+        }));
+
+        // Score falls linearly from 1 (same moment) to 0 (edge of the window)
+        let temporal = GraphRepository::temporal_neighbors(
+            db_pool, asset_uuid, TEMPORAL_WINDOW_HOURS, MAX_EDGES_PER_TYPE,
+        ).await?;
+        edges.extend(temporal.into_iter().map(|(uuid, hours_apart)| RelatedAsset {
+            asset_uuid: uuid,
+            relationship_type: TEMPORAL,
+            data: json!({
+                "score": (1.0 - hours_apart / TEMPORAL_WINDOW_HOURS).max(0.0),
+                "hours_apart": hours_apart,
+            }),
+        }));
+
+        let speakers = GraphRepository::shared_speaker_neighbors(
+            db_pool, asset_uuid, MIN_OVERLAP_SCORE, MAX_EDGES_PER_TYPE,
+        ).await?;
+        edges.extend(speakers.into_iter().map(|(uuid, score, shared)| RelatedAsset {
+            asset_uuid: uuid,
+            relationship_type: SAME_SPEAKER,
+            data: json!({ "score": score, "method": "jaccard", "shared": shared }),
+        }));
+
+        GraphRepository::replace_relationships(db_pool, asset_uuid, &COMPUTED_RELATIONSHIPS, &edges).await?;
+        tracing::info!("Rebuilt {} relationships of asset {}", edges.len(), asset_uuid);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::repositories::transcript_repository::TranscriptRepository;
+    use crate::db::test_support::{db_pool, insert_asset};
+    use crate::models::metadata::TranscriptSegment;
+    use sqlx::PgPool;
+
+    fn spoken_by(speakers: &[&str]) -> Vec<TranscriptSegment> {
+        speakers.iter().enumerate().map(|(index, speaker)| TranscriptSegment {
+            start_time: index as f64,
+            end_time: index as f64 + 1.0,
+            speaker_id: Some(speaker.to_string()),
+            text: "words".to_string(),
+        }).collect()
+    }
+
+    async fn related(pool: &DbPool, asset_uuid: Uuid, relationship_type: &str) -> Vec<Uuid> {
+        sqlx::query_scalar(
+            "SELECT target_asset_uuid FROM graph_relationships
+             WHERE source_asset_uuid = $1 AND relationship_type = $2 ORDER BY target_asset_uuid"
+        )
+        .bind(asset_uuid)
+        .bind(relationship_type)
+        .fetch_all(pool.as_ref())
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn reindexing_replaces_stale_edges(pool: PgPool) {
+        let pool = db_pool(pool);
+        let me = insert_asset(&pool, "a.mp4", json!({"keywords": ["budget"]})).await;
+        let other = insert_asset(&pool, "b.mp4", json!({"keywords": ["budget"]})).await;
+        TranscriptRepository::replace_segments(&pool, me, &spoken_by(&["Ana Ruiz"])).await.unwrap();
+        TranscriptRepository::replace_segments(&pool, other, &spoken_by(&["Ana Ruiz"])).await.unwrap();
+        GraphService::index_asset(&pool, other).await.unwrap();
+        GraphService::index_asset(&pool, me).await.unwrap();
+
+        assert_eq!(related(&pool, me, SAME_SPEAKER).await, vec![other]);
+        assert_eq!(related(&pool, other, SAME_SPEAKER).await, vec![me]);
+        assert_eq!(related(&pool, me, SHARED_KEYWORD).await, vec![other]);
+
+        // The other asset is re-transcribed and retagged; re-indexing it drops both edges in both directions
+        TranscriptRepository::replace_segments(&pool, other, &spoken_by(&["Ben Ode"])).await.unwrap();
+        sqlx::query("UPDATE assets SET enriched_metadata = '{\"keywords\": [\"weather\"]}' WHERE uuid = $1")
+            .bind(other)
+            .execute(pool.as_ref())
+            .await
+            .unwrap();
+        GraphService::index_asset(&pool, other).await.unwrap();
+
+        for (asset, neighbor) in [(me, other), (other, me)] {
+            assert!(!related(&pool, asset, SAME_SPEAKER).await.contains(&neighbor));
+            assert!(!related(&pool, asset, SHARED_KEYWORD).await.contains(&neighbor));
+        }
+        // Edges that still hold are kept
+        assert_eq!(related(&pool, me, TEMPORAL).await, vec![other]);
     }
 }
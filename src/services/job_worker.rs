@@ -0,0 +1,306 @@
+// Job worker pool
+// I-FR-03: Asynchronous execution
+// I-FR-04: Parallel execution
+// I-FR-08: Scalability - several instances share one Postgres-backed queue
+
+use crate::config::{QueueConfig, RetryConfig};
+use crate::db::DbPool;
+use crate::db::repositories::{
+    action_repository::ActionRepository, asset_repository::AssetRepository,
+    settings_repository::SettingsRepository, transcript_repository::TranscriptRepository,
+    workflow_repository::WorkflowRepository,
+};
+use crate::models::action_record::{ActionRecord, ActionStatus, ActionType, Direction};
+use crate::models::asset::AssetStatus;
+use crate::models::workflow::ProcessingJob;
+use crate::services::ai_processing::AIProcessingService;
+use crate::services::graph_service::GraphService;
+use crate::services::media_probe;
+use crate::services::saved_search_service;
+use crate::services::storage::StorageRegistry;
+use anyhow::Result;
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+const WORKER_NAME: &str = "JobWorker";
+const WORKER_VERSION: &str = "v1.0.0";
+
+pub struct JobWorkerPool {
+    db_pool: DbPool,
+    config: QueueConfig,
+    retry: RetryConfig,
+    storage: Arc<StorageRegistry>,
+    instance_id: String,
+}
+
+impl JobWorkerPool {
+    pub fn new(db_pool: DbPool, config: QueueConfig, retry: RetryConfig, storage: Arc<StorageRegistry>) -> Self {
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "worker".to_string());
+        Self {
+            db_pool,
+            config,
+            retry,
+            storage,
+            instance_id: format!("{}-{}-{}", host, std::process::id(), &Uuid::new_v4().to_string()[..8]),
+        }
+    }
+
+    /// Spawn `worker_concurrency` workers polling the queue
+    pub fn start(self) -> Vec<JoinHandle<()>> {
+        info!(
+            "Starting {} job workers as {}",
+            self.config.worker_concurrency, self.instance_id
+        );
+
+        (0..self.config.worker_concurrency.max(1))
+            .map(|n| {
+                let worker_id = format!("{}/{}", self.instance_id, n);
+                tokio::spawn(run_worker(
+                    self.db_pool.clone(),
+                    self.storage.clone(),
+                    self.config.clone(),
+                    self.retry.clone(),
+                    worker_id,
+                ))
+            })
+            .collect()
+    }
+}
+
+async fn run_worker(
+    db_pool: DbPool,
+    storage: Arc<StorageRegistry>,
+    config: QueueConfig,
+    retry: RetryConfig,
+    worker_id: String,
+) {
+    let poll_interval = Duration::from_secs(config.poll_interval_seconds.max(1));
+
+    loop {
+        let policy = retry_policy(&db_pool, &retry).await;
+        if let Err(e) = fail_expired_jobs(&db_pool, &policy).await {
+            error!("Worker {} failed to fail jobs with expired leases: {}", worker_id, e);
+        }
+
+        match WorkflowRepository::claim_next_job(&db_pool, &worker_id, config.lease_seconds, policy.max_attempts).await {
+            Ok(Some(job)) => {
+                info!("Worker {} claimed job {} ({})", worker_id, job.job_id, job.workflow_name);
+                process_job(&db_pool, &storage, &config, &retry, &worker_id, job).await;
+            }
+            Ok(None) => tokio::time::sleep(poll_interval).await,
+            Err(e) => {
+                error!("Worker {} failed to claim job: {}", worker_id, e);
+                tokio::time::sleep(poll_interval).await;
+            }
+        }
+    }
+}
+
+async fn process_job(
+    db_pool: &DbPool,
+    storage: &StorageRegistry,
+    config: &QueueConfig,
+    default_retry: &RetryConfig,
+    worker_id: &str,
+    job: ProcessingJob,
+) {
+    // Keep the lease alive while the job runs; stops once another worker has taken over
+    let heartbeat = {
+        let db_pool = db_pool.clone();
+        let worker_id = worker_id.to_string();
+        let lease_seconds = config.lease_seconds;
+        let interval = Duration::from_secs(config.heartbeat_seconds.max(1));
+        let job_id = job.job_id;
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                match WorkflowRepository::heartbeat(&db_pool, job_id, &worker_id, lease_seconds).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        warn!("Worker {} lost lease on job {}", worker_id, job_id);
+                        break;
+                    }
+                    Err(e) => warn!("Heartbeat for job {} failed: {}", job_id, e),
+                }
+            }
+        })
+    };
+
+    let outcome = execute_job(db_pool, storage, &job).await;
+    heartbeat.abort();
+
+    match outcome {
+        Ok(capabilities) => {
+            match WorkflowRepository::complete_job(db_pool, job.job_id, worker_id, &capabilities).await {
+                Ok(true) => {
+                    info!("Job {} completed", job.job_id);
+                    // I-FR-20: Index before evaluating saved searches, which can filter on keywords
+                    if let Err(e) = GraphService::index_asset(db_pool, job.asset_uuid).await {
+                        warn!("Failed to index asset {} in the graph: {}", job.asset_uuid, e);
+                    }
+                    saved_search_service::spawn_evaluation(db_pool.clone(), job.asset_uuid);
+                }
+                Ok(false) => warn!("Job {} finished after its lease was reclaimed", job.job_id),
+                Err(e) => error!("Failed to mark job {} completed: {}", job.job_id, e),
+            }
+        }
+        Err(e) => {
+            error!("Job {} failed: {}", job.job_id, e);
+            if let Err(e) = handle_failure(db_pool, default_retry, worker_id, &job, &e.to_string()).await {
+                error!("Failed to record failure of job {}: {}", job.job_id, e);
+            }
+        }
+    }
+}
+
+/// The global retry policy stored in platform_settings, or the startup defaults if it cannot be read
+async fn retry_policy(db_pool: &DbPool, default_retry: &RetryConfig) -> RetryConfig {
+    match SettingsRepository::get_retry_config(db_pool, default_retry).await {
+        Ok(config) => config,
+        Err(e) => {
+            warn!("Could not load the stored retry policy, using the defaults: {:#}", e);
+            default_retry.clone()
+        }
+    }
+}
+
+// I-FR-16: A worker that died on a job's last attempt used it up; fail the job instead of reclaiming it
+async fn fail_expired_jobs(db_pool: &DbPool, policy: &RetryConfig) -> Result<()> {
+    for job in WorkflowRepository::fail_expired_jobs(db_pool, policy.max_attempts).await? {
+        AssetRepository::update_status(db_pool, job.asset_uuid, AssetStatus::Failed, None).await?;
+        error!("Job {} failed permanently: its worker stopped during attempt {}", job.job_id, job.retry_count);
+
+        ActionRepository::create(db_pool, &ActionRecord {
+            record_id: Uuid::new_v4(),
+            asset_uuid: Some(job.asset_uuid),
+            action_type: ActionType::JobRetry,
+            direction: Direction::Internal,
+            controller_name: WORKER_NAME.to_string(),
+            controller_version: WORKER_VERSION.to_string(),
+            source_system: None,
+            destination_system: None,
+            status: ActionStatus::Failed,
+            timestamp: Utc::now(),
+            metadata: Some(serde_json::json!({
+                "job_id": job.job_id,
+                "attempt": job.retry_count,
+                "error": job.error_message,
+            })),
+            user_id: None,
+        }).await?;
+    }
+    Ok(())
+}
+
+// I-FR-16: Re-queue with exponential backoff until max_attempts, then fail terminally
+async fn handle_failure(
+    db_pool: &DbPool,
+    default_retry: &RetryConfig,
+    worker_id: &str,
+    job: &ProcessingJob,
+    error_message: &str,
+) -> Result<()> {
+    // Per-job retry_config overrides the global policy stored in platform_settings
+    let global = retry_policy(db_pool, default_retry).await;
+    let retry = match &job.retry_config {
+        Some(overrides) => global.with_overrides(overrides),
+        None => global,
+    };
+
+    let attempt = job.retry_count.max(0) as u32 + 1;
+    let will_retry = attempt < retry.max_attempts;
+
+    let mut details = serde_json::json!({
+        "job_id": job.job_id,
+        "attempt": attempt,
+        "max_attempts": retry.max_attempts,
+        "error": error_message,
+    });
+
+    if will_retry {
+        let delay = retry.delay_for_attempt(attempt);
+        let next_attempt_at = Utc::now() + chrono::Duration::from_std(delay)?;
+        details["delay_seconds"] = serde_json::json!(delay.as_secs_f64());
+        details["next_attempt_at"] = serde_json::json!(next_attempt_at);
+
+        WorkflowRepository::schedule_retry(
+            db_pool,
+            job.job_id,
+            Some(worker_id),
+            attempt as i32,
+            next_attempt_at,
+            Some(error_message),
+            None,
+        ).await?;
+        AssetRepository::update_status(db_pool, job.asset_uuid, AssetStatus::Queued, None).await?;
+        warn!("Job {} attempt {} failed, retrying in {:?}", job.job_id, attempt, delay);
+    } else {
+        WorkflowRepository::fail_job(db_pool, job.job_id, worker_id, error_message).await?;
+        AssetRepository::update_status(db_pool, job.asset_uuid, AssetStatus::Failed, None).await?;
+        error!("Job {} failed permanently after {} attempts", job.job_id, attempt);
+    }
+
+    ActionRepository::create(db_pool, &ActionRecord {
+        record_id: Uuid::new_v4(),
+        asset_uuid: Some(job.asset_uuid),
+        action_type: ActionType::JobRetry,
+        direction: Direction::Internal,
+        controller_name: WORKER_NAME.to_string(),
+        controller_version: WORKER_VERSION.to_string(),
+        source_system: None,
+        destination_system: None,
+        status: if will_retry { ActionStatus::Initiated } else { ActionStatus::Failed },
+        timestamp: Utc::now(),
+        metadata: Some(details),
+        user_id: None,
+    }).await?;
+
+    Ok(())
+}
+
+/// Run the AI workflow for a job and return the capabilities that produced output
+async fn execute_job(db_pool: &DbPool, storage: &StorageRegistry, job: &ProcessingJob) -> Result<Vec<String>> {
+    let mut asset = AssetRepository::get_by_uuid(db_pool, job.asset_uuid).await?
+        .ok_or_else(|| anyhow::anyhow!("Asset {} not found", job.asset_uuid))?;
+
+    AssetRepository::update_status(db_pool, asset.uuid, AssetStatus::Processing, None).await?;
+
+    // Probe once per asset; an unreadable container should not block AI processing
+    if asset.technical_metadata.is_none() {
+        match media_probe::probe_stored(storage, &asset.file_path).await {
+            Ok(Some(technical)) => {
+                AssetRepository::update_technical_metadata(db_pool, asset.uuid, &technical).await?;
+                info!("Probed asset {}: {}", asset.uuid, technical.container);
+            }
+            Ok(None) => info!("No probe available for asset {} ({})", asset.uuid, asset.format),
+            Err(e) => warn!("Failed to probe asset {}: {}", asset.uuid, e),
+        }
+    }
+
+    let asset_type = format!("{:?}", asset.asset_type).to_uppercase();
+    let enriched_metadata = AIProcessingService::process_asset(&asset.file_path, &asset_type).await?;
+
+    // Merge AI results with existing metadata
+    let mut capabilities = Vec::new();
+    if let (Some(current), Some(ai_obj)) = (asset.enriched_metadata.as_object_mut(), enriched_metadata.as_object()) {
+        for (key, value) in ai_obj {
+            current.insert(key.clone(), value.clone());
+            capabilities.push(key.clone());
+        }
+    }
+
+    // I-FR-22: Segments are also kept as rows so search can point inside the media.
+    // Always replaced, so reprocessing into an untranscribed result drops the old segments.
+    let segments = AIProcessingService::transcript_segments(&enriched_metadata)?;
+    TranscriptRepository::replace_segments(db_pool, asset.uuid, &segments).await?;
+
+    asset.status = AssetStatus::Processed;
+    asset.processing_completed_at = Some(Utc::now());
+    AssetRepository::update(db_pool, &asset).await?;
+
+    Ok(capabilities)
+}
@@ -1,10 +1,14 @@
 // Local file storage service
 // For local testing without S3 dependency
 
+use crate::services::storage::{ObjectStat, StorageBackend, StorageReader, UploadWriter};
 use anyhow::Result;
-use chrono::Datelike;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc};
 use std::fs;
 use std::path::{Path, PathBuf};
+use crate::utils::http_range::ByteRange;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 
 pub struct LocalStorageService {
     base_path: PathBuf,
@@ -68,3 +72,85 @@ impl LocalStorageService {
     }
 }
 
+
+/// Streams an upload straight into its final location on disk
+pub struct LocalUploadWriter {
+    path: PathBuf,
+    file: tokio::fs::File,
+}
+
+#[async_trait]
+impl UploadWriter for LocalUploadWriter {
+    async fn write(&mut self, chunk: &[u8]) -> Result<()> {
+        self.file.write_all(chunk).await?;
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<String> {
+        self.file.flush().await?;
+        let absolute = tokio::fs::canonicalize(&self.path).await?;
+        Ok(format!("file://{}", absolute.to_string_lossy()))
+    }
+
+    async fn abort(self: Box<Self>) -> Result<()> {
+        drop(self.file);
+        tokio::fs::remove_file(&self.path).await?;
+        Ok(())
+    }
+}
+
+/// Filesystem path behind a `file://` URI (bare legacy paths pass through)
+fn uri_to_path(uri: &str) -> &str {
+    uri.strip_prefix("file://").unwrap_or(uri)
+}
+
+#[async_trait]
+impl StorageBackend for LocalStorageService {
+    fn scheme(&self) -> &'static str {
+        "file"
+    }
+
+    async fn put(&self, category: &str, filename: &str, data: Vec<u8>) -> Result<String> {
+        let saved = self.save_file(category, filename, data).await?;
+        // Absolute so the URI stays valid regardless of the working directory
+        let absolute = fs::canonicalize(&saved)?;
+        Ok(format!("file://{}", absolute.to_string_lossy()))
+    }
+
+    async fn get(&self, uri: &str) -> Result<Vec<u8>> {
+        Ok(tokio::fs::read(uri_to_path(uri)).await?)
+    }
+
+    async fn delete(&self, uri: &str) -> Result<()> {
+        tokio::fs::remove_file(uri_to_path(uri)).await?;
+        Ok(())
+    }
+
+    async fn stat(&self, uri: &str) -> Result<ObjectStat> {
+        let metadata = tokio::fs::metadata(uri_to_path(uri)).await?;
+        Ok(ObjectStat {
+            size: metadata.len(),
+            last_modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+        })
+    }
+
+    async fn stream(&self, uri: &str, range: Option<ByteRange>) -> Result<StorageReader> {
+        let mut file = tokio::fs::File::open(uri_to_path(uri)).await?;
+        match range {
+            Some(range) => {
+                file.seek(std::io::SeekFrom::Start(range.start)).await?;
+                Ok(Box::pin(file.take(range.length())))
+            }
+            None => Ok(Box::pin(file)),
+        }
+    }
+
+    async fn writer(&self, category: &str, filename: &str) -> Result<Box<dyn UploadWriter>> {
+        let path = self.generate_path(category, filename);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let file = tokio::fs::File::create(&path).await?;
+        Ok(Box::new(LocalUploadWriter { path, file }))
+    }
+}
@@ -0,0 +1,329 @@
+// Audio containers: WAV (RIFF), FLAC (STREAMINFO) and MPEG audio (MP3/MP2 frame headers)
+
+use super::{be_u32, le_u16, le_u32};
+use crate::models::asset::TechnicalMetadata;
+
+pub(super) fn probe_wav(head: &[u8], size: u64) -> Option<TechnicalMetadata> {
+    let mut metadata = TechnicalMetadata {
+        container: "WAV".to_string(),
+        ..Default::default()
+    };
+    let mut byte_rate = None;
+    let mut data_size = None;
+    let mut offset = 12usize;
+
+    while let (Some(id), Some(len)) = (head.get(offset..offset + 4), le_u32(head, offset + 4)) {
+        let body = offset + 8;
+        match id {
+            b"fmt " => {
+                let format_tag = le_u16(head, body)?;
+                metadata.audio_codec = Some(match format_tag {
+                    1 | 0xFFFE => "PCM".to_string(),
+                    3 => "IEEE Float".to_string(),
+                    6 => "A-law".to_string(),
+                    7 => "mu-law".to_string(),
+                    0x55 => "MP3".to_string(),
+                    other => format!("0x{:04X}", other),
+                });
+                metadata.channels = le_u16(head, body + 2);
+                metadata.sample_rate = le_u32(head, body + 4);
+                byte_rate = le_u32(head, body + 8).filter(|r| *r > 0);
+                metadata.bits_per_sample = le_u16(head, body + 14);
+            }
+            b"data" => {
+                data_size = Some(len as u64);
+                break;
+            }
+            _ => {}
+        }
+        // Chunks are word aligned
+        offset = body + len as usize + (len as usize & 1);
+    }
+
+    if let Some(byte_rate) = byte_rate {
+        // Without a data chunk in the head, assume everything after the header is samples
+        let samples = data_size.unwrap_or_else(|| size.saturating_sub(offset as u64));
+        metadata.duration_seconds = Some(samples as f64 / byte_rate as f64);
+        metadata.bitrate = Some(byte_rate as u64 * 8);
+    }
+
+    Some(metadata)
+}
+
+pub(super) fn probe_flac(head: &[u8], size: u64) -> Option<TechnicalMetadata> {
+    // First metadata block is always STREAMINFO (type 0, 34 bytes)
+    if head.get(4).map(|b| b & 0x7F) != Some(0) {
+        return None;
+    }
+    let info = head.get(8..8 + 34)?;
+    let b = &info[10..18];
+
+    let sample_rate = ((b[0] as u32) << 12) | ((b[1] as u32) << 4) | ((b[2] as u32) >> 4);
+    let channels = ((b[2] >> 1) & 0x07) as u16 + 1;
+    let bits_per_sample = ((((b[2] & 0x01) << 4) | (b[3] >> 4)) as u16) + 1;
+    let total_samples = (((b[3] & 0x0F) as u64) << 32) | be_u32(b, 4)? as u64;
+
+    let duration = (sample_rate > 0 && total_samples > 0).then(|| total_samples as f64 / sample_rate as f64);
+
+    Some(TechnicalMetadata {
+        container: "FLAC".to_string(),
+        audio_codec: Some("FLAC".to_string()),
+        duration_seconds: duration,
+        bitrate: duration.map(|d| (size as f64 * 8.0 / d).round() as u64),
+        sample_rate: Some(sample_rate).filter(|r| *r > 0),
+        channels: Some(channels),
+        bits_per_sample: Some(bits_per_sample),
+        ..Default::default()
+    })
+}
+
+struct FrameHeader {
+    mpeg1: bool,
+    layer: u8,
+    bitrate_kbps: u32,
+    sample_rate: u32,
+    mono: bool,
+}
+
+impl FrameHeader {
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        let (b1, b2, b3) = (*bytes.get(1)?, *bytes.get(2)?, *bytes.get(3)?);
+        if bytes[0] != 0xFF || b1 & 0xE0 != 0xE0 {
+            return None;
+        }
+
+        let version = (b1 >> 3) & 0x03; // 3 = MPEG-1, 2 = MPEG-2, 0 = MPEG-2.5
+        let layer = match (b1 >> 1) & 0x03 {
+            3 => 1,
+            2 => 2,
+            1 => 3,
+            _ => return None,
+        };
+        let bitrate_index = (b2 >> 4) as usize;
+        let rate_index = ((b2 >> 2) & 0x03) as usize;
+        if version == 1 || bitrate_index == 0 || bitrate_index == 15 || rate_index == 3 {
+            return None;
+        }
+
+        let mpeg1 = version == 3;
+        let bitrates: [u32; 15] = match (mpeg1, layer) {
+            (true, 1) => [0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448],
+            (true, 2) => [0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384],
+            (true, _) => [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320],
+            (false, 1) => [0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256],
+            (false, _) => [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+        };
+        let base_rate = [44_100, 48_000, 32_000][rate_index];
+        let sample_rate = match version {
+            3 => base_rate,
+            2 => base_rate / 2,
+            _ => base_rate / 4,
+        };
+
+        Some(Self {
+            mpeg1,
+            layer,
+            bitrate_kbps: bitrates[bitrate_index],
+            sample_rate,
+            mono: b3 >> 6 == 3,
+        })
+    }
+
+    fn samples_per_frame(&self) -> u32 {
+        match (self.layer, self.mpeg1) {
+            (1, _) => 384,
+            (2, _) | (3, true) => 1152,
+            _ => 576,
+        }
+    }
+
+    /// Offset of a Xing/Info VBR header, which follows the Layer III side information
+    fn xing_offset(&self) -> usize {
+        4 + match (self.mpeg1, self.mono) {
+            (true, false) => 32,
+            (true, true) | (false, false) => 17,
+            (false, true) => 9,
+        }
+    }
+}
+
+pub(super) fn probe_mp3(head: &[u8], size: u64) -> Option<TechnicalMetadata> {
+    // Skip an ID3v2 tag (syncsafe size, optional footer)
+    let mut start = 0usize;
+    if head.starts_with(b"ID3") {
+        let tag_size = head.get(6..10)?
+            .iter()
+            .fold(0usize, |acc, b| (acc << 7) | (*b & 0x7F) as usize);
+        let footer = if head.get(5)? & 0x10 != 0 { 10 } else { 0 };
+        start = 10 + tag_size + footer;
+    } else if FrameHeader::parse(head).is_none() {
+        return None;
+    }
+
+    // Allow a little padding between the tag and the first frame
+    let frame_start = (start..head.len().saturating_sub(4).min(start + 4096))
+        .find(|&i| FrameHeader::parse(&head[i..]).is_some())?;
+    let frame = FrameHeader::parse(&head[frame_start..])?;
+    let audio_bytes = size.saturating_sub(frame_start as u64);
+
+    // VBR files carry a frame count in a Xing/Info or VBRI header; otherwise assume CBR
+    let xing = frame_start + frame.xing_offset();
+    let vbri = frame_start + 4 + 32;
+    let frame_count = match head.get(xing..xing + 4) {
+        Some(b"Xing") | Some(b"Info") if be_u32(head, xing + 4).unwrap_or(0) & 0x01 != 0 => be_u32(head, xing + 8),
+        _ if head.get(vbri..vbri + 4) == Some(b"VBRI") => be_u32(head, vbri + 14),
+        _ => None,
+    };
+
+    let duration = match frame_count {
+        Some(frames) => frames as f64 * frame.samples_per_frame() as f64 / frame.sample_rate as f64,
+        None => audio_bytes as f64 * 8.0 / (frame.bitrate_kbps as f64 * 1000.0),
+    };
+    let bitrate = match frame_count {
+        Some(_) if duration > 0.0 => (audio_bytes as f64 * 8.0 / duration).round() as u64,
+        _ => frame.bitrate_kbps as u64 * 1000,
+    };
+
+    let codec = match frame.layer {
+        1 => "MP1",
+        2 => "MP2",
+        _ => "MP3",
+    };
+
+    Some(TechnicalMetadata {
+        container: codec.to_string(),
+        audio_codec: Some(codec.to_string()),
+        duration_seconds: Some(duration),
+        bitrate: Some(bitrate),
+        sample_rate: Some(frame.sample_rate),
+        channels: Some(if frame.mono { 1 } else { 2 }),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        [&id[..], &(body.len() as u32).to_le_bytes(), body].concat()
+    }
+
+    fn wav(chunks: &[Vec<u8>]) -> Vec<u8> {
+        [b"RIFF\0\0\0\0WAVE".to_vec(), chunks.concat()].concat()
+    }
+
+    // PCM, stereo, 44.1 kHz, 16-bit
+    fn fmt_chunk() -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&1u16.to_le_bytes());
+        body.extend_from_slice(&2u16.to_le_bytes());
+        body.extend_from_slice(&44_100u32.to_le_bytes());
+        body.extend_from_slice(&176_400u32.to_le_bytes());
+        body.extend_from_slice(&4u16.to_le_bytes());
+        body.extend_from_slice(&16u16.to_le_bytes());
+        chunk(b"fmt ", &body)
+    }
+
+    #[test]
+    fn wav_reads_format_and_duration_from_data_chunk() {
+        let mut data = chunk(b"data", &[0; 16]);
+        // The data chunk claims two seconds of samples, most of them beyond the probed head
+        data[4..8].copy_from_slice(&352_800u32.to_le_bytes());
+        let head = wav(&[fmt_chunk(), data]);
+
+        let metadata = probe_wav(&head, 352_844).unwrap();
+        assert_eq!(metadata.audio_codec.as_deref(), Some("PCM"));
+        assert_eq!(metadata.channels, Some(2));
+        assert_eq!(metadata.sample_rate, Some(44_100));
+        assert_eq!(metadata.bits_per_sample, Some(16));
+        assert_eq!(metadata.duration_seconds, Some(2.0));
+        assert_eq!(metadata.bitrate, Some(1_411_200));
+    }
+
+    #[test]
+    fn wav_with_truncated_fmt_chunk_is_rejected() {
+        let head = wav(&[fmt_chunk()]);
+        assert!(probe_wav(&head[..21], 21).is_none());
+
+        // The format tag alone is enough to name the codec, but not to time the audio
+        let metadata = probe_wav(&head[..22], 22).unwrap();
+        assert_eq!(metadata.audio_codec.as_deref(), Some("PCM"));
+        assert_eq!((metadata.channels, metadata.duration_seconds), (None, None));
+    }
+
+    #[test]
+    fn wav_with_oversized_chunk_stops_scanning() {
+        let mut oversized = chunk(b"LIST", b"");
+        oversized[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        let head = wav(&[fmt_chunk(), oversized]);
+
+        let metadata = probe_wav(&head, head.len() as u64).unwrap();
+        assert_eq!(metadata.sample_rate, Some(44_100));
+        assert_eq!(metadata.duration_seconds, Some(0.0));
+    }
+
+    fn flac(streaminfo: &[u8]) -> Vec<u8> {
+        [&b"fLaC"[..], &[0x80, 0, 0, 34], streaminfo].concat()
+    }
+
+    #[test]
+    fn flac_reads_streaminfo() {
+        // 44.1 kHz, 2 channels, 16 bits, 441000 samples packed into 64 bits after the block sizes
+        let packed: u64 = (44_100 << 44) | (1 << 41) | (15 << 36) | 441_000;
+        let mut streaminfo = vec![0; 34];
+        streaminfo[10..18].copy_from_slice(&packed.to_be_bytes());
+
+        let metadata = probe_flac(&flac(&streaminfo), 1_000_000).unwrap();
+        assert_eq!(metadata.sample_rate, Some(44_100));
+        assert_eq!(metadata.channels, Some(2));
+        assert_eq!(metadata.bits_per_sample, Some(16));
+        assert_eq!(metadata.duration_seconds, Some(10.0));
+        assert_eq!(metadata.bitrate, Some(800_000));
+    }
+
+    #[test]
+    fn flac_with_truncated_streaminfo_is_rejected() {
+        assert!(probe_flac(&flac(&[0; 20]), 1_000).is_none());
+        assert!(probe_flac(b"fLaC", 4).is_none());
+    }
+
+    // MPEG-1 Layer III, 128 kbps, 44.1 kHz, stereo
+    const MP3_FRAME: [u8; 4] = [0xFF, 0xFB, 0x90, 0x00];
+
+    #[test]
+    fn mp3_estimates_cbr_duration_from_size() {
+        let head = [&MP3_FRAME[..], &[0; 412]].concat();
+
+        let metadata = probe_mp3(&head, 160_000).unwrap();
+        assert_eq!(metadata.audio_codec.as_deref(), Some("MP3"));
+        assert_eq!(metadata.sample_rate, Some(44_100));
+        assert_eq!(metadata.channels, Some(2));
+        assert_eq!(metadata.bitrate, Some(128_000));
+        assert_eq!(metadata.duration_seconds, Some(10.0));
+    }
+
+    #[test]
+    fn mp3_after_id3_tag_uses_xing_frame_count() {
+        let mut frame = [&MP3_FRAME[..], &[0; 412]].concat();
+        frame[36..40].copy_from_slice(b"Xing");
+        frame[40..44].copy_from_slice(&1u32.to_be_bytes());
+        frame[44..48].copy_from_slice(&383u32.to_be_bytes());
+        let head = [&b"ID3\x04\x00\x00\x00\x00\x00\x04"[..], &[0; 4], &frame].concat();
+
+        let metadata = probe_mp3(&head, 1_000).unwrap();
+        // 383 frames of 1152 samples at 44.1 kHz
+        assert!((metadata.duration_seconds.unwrap() - 10.005).abs() < 0.001);
+    }
+
+    #[test]
+    fn mp3_with_truncated_or_oversized_headers_is_rejected() {
+        assert!(probe_mp3(&MP3_FRAME[..2], 2).is_none());
+        assert!(probe_mp3(b"ID3\x04\x00", 5).is_none());
+        // A tag size past the end of the head leaves no frame to find
+        let oversized = [&b"ID3\x04\x00\x00\x7F\x7F\x7F\x7F"[..], &MP3_FRAME, &[0; 16]].concat();
+        assert!(probe_mp3(&oversized, 1 << 30).is_none());
+        // Reserved MPEG version
+        assert!(probe_mp3(&[0xFF, 0xEB, 0x90, 0x00, 0, 0], 6).is_none());
+    }
+}
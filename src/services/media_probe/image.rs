@@ -0,0 +1,176 @@
+// Still images: PNG (IHDR), JPEG (SOF marker), GIF (logical screen) and WebP (VP8/VP8L/VP8X)
+
+use super::{be_u16, be_u32, le_u16, le_u32};
+use crate::models::asset::TechnicalMetadata;
+
+fn image(container: &str, width: Option<u32>, height: Option<u32>) -> Option<TechnicalMetadata> {
+    Some(TechnicalMetadata {
+        container: container.to_string(),
+        width: width.filter(|w| *w > 0),
+        height: height.filter(|h| *h > 0),
+        ..Default::default()
+    })
+}
+
+pub(super) fn probe_png(head: &[u8]) -> Option<TechnicalMetadata> {
+    if head.get(12..16) != Some(&b"IHDR"[..]) {
+        return image("PNG", None, None);
+    }
+    let mut metadata = image("PNG", be_u32(head, 16), be_u32(head, 20))?;
+    metadata.bits_per_sample = head.get(24).map(|b| *b as u16);
+    Some(metadata)
+}
+
+pub(super) fn probe_jpeg(head: &[u8]) -> Option<TechnicalMetadata> {
+    let mut offset = 2usize;
+
+    while offset + 4 <= head.len() {
+        if head[offset] != 0xFF {
+            break;
+        }
+        let marker = head[offset + 1];
+        // Fill bytes and standalone markers carry no length
+        if marker == 0xFF {
+            offset += 1;
+            continue;
+        }
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            offset += 2;
+            continue;
+        }
+
+        let length = be_u16(head, offset + 2)? as usize;
+        // SOF0-SOF15, excluding DHT (C4), JPG (C8) and DAC (CC)
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let mut metadata = image(
+                "JPEG",
+                be_u16(head, offset + 7).map(u32::from),
+                be_u16(head, offset + 5).map(u32::from),
+            )?;
+            metadata.bits_per_sample = head.get(offset + 4).map(|b| *b as u16);
+            return Some(metadata);
+        }
+        if marker == 0xDA {
+            break; // Start of scan without a frame header
+        }
+        offset += 2 + length;
+    }
+
+    // Frame header beyond the probed head (very large EXIF/thumbnail segments)
+    image("JPEG", None, None)
+}
+
+pub(super) fn probe_gif(head: &[u8]) -> Option<TechnicalMetadata> {
+    image("GIF", le_u16(head, 6).map(u32::from), le_u16(head, 8).map(u32::from))
+}
+
+pub(super) fn probe_webp(head: &[u8]) -> Option<TechnicalMetadata> {
+    let le_u24 = |at: usize| -> Option<u32> {
+        let b = head.get(at..at + 3)?;
+        Some(b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16)
+    };
+
+    let (width, height) = match head.get(12..16)? {
+        // Lossy: frame tag (3 bytes), start code 9D 01 2A, then 14-bit dimensions
+        b"VP8 " if head.get(23..26) == Some(&[0x9D, 0x01, 0x2A][..]) => (
+            le_u16(head, 26).map(|w| (w & 0x3FFF) as u32),
+            le_u16(head, 28).map(|h| (h & 0x3FFF) as u32),
+        ),
+        // Lossless: signature byte then 14-bit width-1 and height-1
+        b"VP8L" if head.get(20) == Some(&0x2F) => {
+            let bits = le_u32(head, 21)?;
+            (Some((bits & 0x3FFF) + 1), Some(((bits >> 14) & 0x3FFF) + 1))
+        }
+        // Extended: 24-bit canvas width-1 and height-1
+        b"VP8X" => (le_u24(24).map(|w| w + 1), le_u24(27).map(|h| h + 1)),
+        _ => (None, None),
+    };
+
+    image("WEBP", width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn png_reads_ihdr() {
+        let head = [
+            &b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR"[..],
+            &1920u32.to_be_bytes(),
+            &1080u32.to_be_bytes(),
+            &[8, 2, 0, 0, 0],
+        ]
+        .concat();
+
+        let metadata = probe_png(&head).unwrap();
+        assert_eq!((metadata.width, metadata.height), (Some(1920), Some(1080)));
+        assert_eq!(metadata.bits_per_sample, Some(8));
+    }
+
+    #[test]
+    fn truncated_png_has_no_dimensions() {
+        let metadata = probe_png(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0").unwrap();
+        assert_eq!((metadata.width, metadata.height), (None, None));
+        assert!(probe_png(b"\x89PNG").unwrap().width.is_none());
+    }
+
+    #[test]
+    fn jpeg_reads_frame_header_after_other_segments() {
+        let head = [
+            &[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0, 0][..],
+            &[0xFF, 0xC0, 0x00, 0x11, 8, 0x02, 0xD0, 0x05, 0x00],
+            &[0; 12],
+        ]
+        .concat();
+
+        let metadata = probe_jpeg(&head).unwrap();
+        assert_eq!((metadata.width, metadata.height), (Some(1280), Some(720)));
+        assert_eq!(metadata.bits_per_sample, Some(8));
+    }
+
+    #[test]
+    fn truncated_or_oversized_jpeg_segments_have_no_dimensions() {
+        let truncated = probe_jpeg(&[0xFF, 0xD8, 0xFF, 0xC0, 0x00, 0x11, 8, 0x02]).unwrap();
+        assert_eq!((truncated.width, truncated.height), (None, None));
+
+        let oversized = probe_jpeg(&[0xFF, 0xD8, 0xFF, 0xE1, 0xFF, 0xFF, 0, 0, 0xFF, 0xC0]).unwrap();
+        assert_eq!((oversized.width, oversized.height), (None, None));
+    }
+
+    #[test]
+    fn gif_reads_logical_screen() {
+        let metadata = probe_gif(b"GIF89a\x40\x01\xF0\x00").unwrap();
+        assert_eq!((metadata.width, metadata.height), (Some(320), Some(240)));
+
+        let truncated = probe_gif(b"GIF89a\x40").unwrap();
+        assert_eq!((truncated.width, truncated.height), (None, None));
+    }
+
+    fn webp(chunk: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        [&b"RIFF\0\0\0\0WEBP"[..], chunk, &(body.len() as u32).to_le_bytes(), body].concat()
+    }
+
+    #[test]
+    fn webp_reads_extended_and_lossless_dimensions() {
+        // 24-bit width-1 and height-1 after four flag bytes
+        let extended = webp(b"VP8X", &[0, 0, 0, 0, 0x7F, 0x07, 0x00, 0x37, 0x04, 0x00]);
+        let metadata = probe_webp(&extended).unwrap();
+        assert_eq!((metadata.width, metadata.height), (Some(1920), Some(1080)));
+
+        // 14-bit width-1 then height-1 after the 0x2F signature
+        let bits: u32 = 99 | (49 << 14);
+        let lossless = webp(b"VP8L", &[&[0x2F][..], &bits.to_le_bytes()].concat());
+        let metadata = probe_webp(&lossless).unwrap();
+        assert_eq!((metadata.width, metadata.height), (Some(100), Some(50)));
+    }
+
+    #[test]
+    fn truncated_webp_is_rejected_or_has_no_dimensions() {
+        assert!(probe_webp(b"RIFF\0\0\0\0WEBP").is_none());
+        assert!(probe_webp(b"RIFF\0\0\0\0WEBPVP8L\0\0\0\0\x2F\x01").is_none());
+
+        let metadata = probe_webp(&webp(b"VP8X", &[0, 0, 0, 0, 0x7F])).unwrap();
+        assert_eq!((metadata.width, metadata.height), (None, None));
+    }
+}
@@ -0,0 +1,139 @@
+// Media probing
+// Reads container headers directly (pure Rust, no ffprobe) to record duration,
+// codecs, bitrate, resolution and audio layout in the asset's technical metadata.
+
+mod audio;
+mod image;
+mod mp4;
+mod pdf;
+
+use crate::models::asset::TechnicalMetadata;
+use crate::services::storage::StorageRegistry;
+use crate::utils::http_range::ByteRange;
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::io::AsyncReadExt;
+
+// Enough for every header we parse except MP4 `moov` and PDF bodies, which are fetched separately
+const HEAD_BYTES: usize = 256 * 1024;
+
+/// Random access to the bytes being probed
+#[async_trait]
+pub trait ByteSource: Send + Sync {
+    fn size(&self) -> u64;
+
+    /// Read up to `len` bytes at `offset`; shorter (or empty) at end of file
+    async fn read_at(&self, offset: u64, len: usize) -> Result<Vec<u8>>;
+}
+
+/// Object in a storage backend, read with ranged requests so large files are never fully fetched
+pub struct StorageSource<'a> {
+    storage: &'a StorageRegistry,
+    uri: &'a str,
+    size: u64,
+}
+
+impl<'a> StorageSource<'a> {
+    pub async fn open(storage: &'a StorageRegistry, uri: &'a str) -> Result<Self> {
+        let size = storage.stat(uri).await?.size;
+        Ok(Self { storage, uri, size })
+    }
+}
+
+#[async_trait]
+impl ByteSource for StorageSource<'_> {
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    async fn read_at(&self, offset: u64, len: usize) -> Result<Vec<u8>> {
+        if offset >= self.size || len == 0 {
+            return Ok(Vec::new());
+        }
+        let end = (offset + len as u64).min(self.size) - 1;
+        let mut reader = self.storage.stream(self.uri, Some(ByteRange { start: offset, end })).await?;
+        let mut data = Vec::with_capacity((end - offset + 1) as usize);
+        reader.read_to_end(&mut data).await?;
+        Ok(data)
+    }
+}
+
+/// Identify the container from its magic bytes and extract what its headers describe.
+/// Returns `None` for formats we do not recognise.
+pub async fn probe(source: &dyn ByteSource) -> Result<Option<TechnicalMetadata>> {
+    let head = source.read_at(0, HEAD_BYTES).await?;
+    let size = source.size();
+
+    let metadata = if head.get(4..8) == Some(b"ftyp") {
+        mp4::probe(source, &head).await?
+    } else if head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WAVE") {
+        audio::probe_wav(&head, size)
+    } else if head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WEBP") {
+        image::probe_webp(&head)
+    } else if head.starts_with(b"fLaC") {
+        audio::probe_flac(&head, size)
+    } else if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        image::probe_png(&head)
+    } else if head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        image::probe_jpeg(&head)
+    } else if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+        image::probe_gif(&head)
+    } else if head.starts_with(b"%PDF-") {
+        pdf::probe(source, &head).await?
+    } else {
+        // MP3 has no fixed magic (ID3 tag or a bare frame sync), so it is tried last
+        audio::probe_mp3(&head, size)
+    };
+
+    Ok(metadata.map(|mut m| {
+        if m.bitrate.is_none() {
+            m.bitrate = m.duration_seconds
+                .filter(|d| *d > 0.0)
+                .map(|d| (size as f64 * 8.0 / d).round() as u64);
+        }
+        m
+    }))
+}
+
+pub async fn probe_stored(storage: &StorageRegistry, uri: &str) -> Result<Option<TechnicalMetadata>> {
+    let source = StorageSource::open(storage, uri).await?;
+    probe(&source).await
+}
+
+fn be_u16(data: &[u8], at: usize) -> Option<u16> {
+    data.get(at..at + 2)?.try_into().ok().map(u16::from_be_bytes)
+}
+
+fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+    data.get(at..at + 4)?.try_into().ok().map(u32::from_be_bytes)
+}
+
+fn be_u64(data: &[u8], at: usize) -> Option<u64> {
+    data.get(at..at + 8)?.try_into().ok().map(u64::from_be_bytes)
+}
+
+fn le_u16(data: &[u8], at: usize) -> Option<u16> {
+    data.get(at..at + 2)?.try_into().ok().map(u16::from_le_bytes)
+}
+
+fn le_u32(data: &[u8], at: usize) -> Option<u32> {
+    data.get(at..at + 4)?.try_into().ok().map(u32::from_le_bytes)
+}
+
+/// In-memory bytes for the parsers' tests
+#[cfg(test)]
+pub(super) struct Bytes(pub Vec<u8>);
+
+#[cfg(test)]
+#[async_trait]
+impl ByteSource for Bytes {
+    fn size(&self) -> u64 {
+        self.0.len() as u64
+    }
+
+    async fn read_at(&self, offset: u64, len: usize) -> Result<Vec<u8>> {
+        let start = (offset as usize).min(self.0.len());
+        let end = start.saturating_add(len).min(self.0.len());
+        Ok(self.0[start..end].to_vec())
+    }
+}
@@ -0,0 +1,283 @@
+// ISO base media (MP4, MOV, M4A, 3GP)
+// Duration comes from `mvhd`; per-track codec, resolution, frame rate and audio layout from `trak` boxes.
+
+use super::{be_u16, be_u32, be_u64, ByteSource};
+use crate::models::asset::TechnicalMetadata;
+use anyhow::Result;
+
+// A `moov` larger than this is almost certainly not a real index
+const MAX_MOOV_BYTES: u64 = 64 * 1024 * 1024;
+
+pub(super) async fn probe(source: &dyn ByteSource, head: &[u8]) -> Result<Option<TechnicalMetadata>> {
+    let brand = head.get(8..12).unwrap_or_default();
+    let container = match brand {
+        b"qt  " => "MOV",
+        b"M4A " | b"M4B " => "M4A",
+        b if b.starts_with(b"3g") => "3GP",
+        _ => "MP4",
+    };
+
+    let mut metadata = TechnicalMetadata {
+        container: container.to_string(),
+        ..Default::default()
+    };
+
+    if let Some(moov) = find_moov(source).await? {
+        parse_moov(&moov, &mut metadata);
+    }
+
+    Ok(Some(metadata))
+}
+
+/// Walk top-level boxes to the `moov` box, which may sit after `mdat` at the end of the file
+async fn find_moov(source: &dyn ByteSource) -> Result<Option<Vec<u8>>> {
+    let size = source.size();
+    let mut offset = 0u64;
+
+    while offset.checked_add(8).is_some_and(|end| end <= size) {
+        let header = source.read_at(offset, 16).await?;
+        let (box_size, header_len) = match be_u32(&header, 0) {
+            Some(1) => match be_u64(&header, 8) {
+                Some(large) => (large, 16),
+                None => return Ok(None),
+            },
+            Some(0) => (size - offset, 8),
+            Some(small) => (small as u64, 8),
+            None => return Ok(None),
+        };
+        if box_size < header_len {
+            return Ok(None);
+        }
+        // Sizes are untrusted; a box claiming to run past u64 ends the walk
+        let Some(next) = offset.checked_add(box_size) else {
+            return Ok(None);
+        };
+
+        if header.get(4..8) == Some(b"moov") {
+            let body_len = box_size - header_len;
+            if body_len > MAX_MOOV_BYTES {
+                return Ok(None);
+            }
+            let body = source.read_at(offset + header_len, body_len as usize).await?;
+            return Ok(Some(body));
+        }
+
+        offset = next;
+    }
+
+    Ok(None)
+}
+
+/// Child boxes of a container box body as (type, body) pairs
+fn boxes(data: &[u8]) -> Vec<(&[u8], &[u8])> {
+    let mut children = Vec::new();
+    let mut offset = 0usize;
+
+    while offset + 8 <= data.len() {
+        let (size, header_len) = match be_u32(data, offset) {
+            Some(1) => match be_u64(data, offset + 8).and_then(|large| usize::try_from(large).ok()) {
+                Some(large) => (large, 16),
+                None => break,
+            },
+            Some(0) => (data.len() - offset, 8),
+            Some(small) => (small as usize, 8),
+            None => break,
+        };
+        if size < header_len {
+            break;
+        }
+        let Some(end) = offset.checked_add(size).filter(|end| *end <= data.len()) else {
+            break;
+        };
+        children.push((&data[offset + 4..offset + 8], &data[offset + header_len..end]));
+        offset = end;
+    }
+
+    children
+}
+
+fn child<'a>(data: &'a [u8], kind: &[u8]) -> Option<&'a [u8]> {
+    boxes(data).into_iter().find(|(k, _)| *k == kind).map(|(_, body)| body)
+}
+
+/// (timescale, duration) from an `mvhd` or `mdhd` full box
+fn timescale_and_duration(body: &[u8]) -> Option<(u32, u64)> {
+    match body.first()? {
+        1 => Some((be_u32(body, 20)?, be_u64(body, 24)?)),
+        _ => Some((be_u32(body, 12)?, be_u32(body, 16)? as u64)),
+    }
+}
+
+fn parse_moov(moov: &[u8], metadata: &mut TechnicalMetadata) {
+    if let Some((timescale, duration)) = child(moov, b"mvhd").and_then(timescale_and_duration) {
+        if timescale > 0 {
+            metadata.duration_seconds = Some(duration as f64 / timescale as f64);
+        }
+    }
+
+    for (kind, trak) in boxes(moov) {
+        if kind != b"trak" {
+            continue;
+        }
+        let Some(mdia) = child(trak, b"mdia") else { continue };
+        let handler = child(mdia, b"hdlr").and_then(|h| h.get(8..12)).unwrap_or_default();
+        let media_time = child(mdia, b"mdhd").and_then(timescale_and_duration);
+        let Some(stbl) = child(mdia, b"minf").and_then(|m| child(m, b"stbl")) else { continue };
+        // stsd: version/flags, entry count, then the first sample entry box
+        let Some(entry) = child(stbl, b"stsd").and_then(|s| s.get(8..)) else { continue };
+        let Some(format) = entry.get(4..8) else { continue };
+
+        match handler {
+            b"vide" if metadata.video_codec.is_none() => {
+                metadata.video_codec = Some(codec_name(format));
+                metadata.width = be_u16(entry, 32).map(u32::from).filter(|w| *w > 0);
+                metadata.height = be_u16(entry, 34).map(u32::from).filter(|h| *h > 0);
+                metadata.frame_rate = media_time.and_then(|(timescale, _)| {
+                    child(stbl, b"stts").and_then(|stts| frame_rate(stts, timescale))
+                });
+            }
+            b"soun" if metadata.audio_codec.is_none() => {
+                metadata.audio_codec = Some(codec_name(format));
+                metadata.channels = be_u16(entry, 24).filter(|c| *c > 0);
+                metadata.bits_per_sample = be_u16(entry, 26).filter(|b| *b > 0);
+                // 16.16 fixed point; the media timescale is the reliable rate when they disagree
+                metadata.sample_rate = media_time
+                    .map(|(timescale, _)| timescale)
+                    .filter(|t| *t > 0)
+                    .or_else(|| be_u32(entry, 32).map(|r| r >> 16));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Average frames per second from the decoding time-to-sample table
+fn frame_rate(stts: &[u8], timescale: u32) -> Option<f64> {
+    let entries = be_u32(stts, 4)? as usize;
+    let (mut samples, mut ticks) = (0u64, 0u64);
+    for i in 0..entries {
+        let count = be_u32(stts, 8 + i * 8)? as u64;
+        let delta = be_u32(stts, 12 + i * 8)? as u64;
+        samples = samples.saturating_add(count);
+        ticks = ticks.saturating_add(count * delta);
+    }
+    if ticks == 0 || timescale == 0 {
+        return None;
+    }
+    let fps = samples as f64 * timescale as f64 / ticks as f64;
+    Some((fps * 1000.0).round() / 1000.0)
+}
+
+fn codec_name(fourcc: &[u8]) -> String {
+    match fourcc {
+        b"avc1" | b"avc3" => "H.264",
+        b"hvc1" | b"hev1" => "H.265",
+        b"av01" => "AV1",
+        b"vp09" => "VP9",
+        b"vp08" => "VP8",
+        b"mp4v" => "MPEG-4 Visual",
+        b"apch" | b"apcn" | b"apcs" | b"apco" | b"ap4h" | b"ap4x" => "ProRes",
+        b"mp4a" => "AAC",
+        b"ac-3" => "AC-3",
+        b"ec-3" => "E-AC-3",
+        b"Opus" => "Opus",
+        b"fLaC" => "FLAC",
+        b"alac" => "ALAC",
+        b".mp3" => "MP3",
+        b"lpcm" | b"sowt" | b"twos" | b"in24" | b"in32" | b"fl32" => "PCM",
+        other => return String::from_utf8_lossy(other).trim().to_string(),
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::media_probe::Bytes;
+
+    fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut data = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(kind);
+        data.extend_from_slice(body);
+        data
+    }
+
+    fn large_box(kind: &[u8; 4], size: u64, body: &[u8]) -> Vec<u8> {
+        let mut data = 1u32.to_be_bytes().to_vec();
+        data.extend_from_slice(kind);
+        data.extend_from_slice(&size.to_be_bytes());
+        data.extend_from_slice(body);
+        data
+    }
+
+    #[test]
+    fn boxes_splits_children() {
+        let data = [mp4_box(b"free", b"ab"), mp4_box(b"mvhd", b"cdef")].concat();
+        let children = boxes(&data);
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0], (&b"free"[..], &b"ab"[..]));
+        assert_eq!(children[1], (&b"mvhd"[..], &b"cdef"[..]));
+    }
+
+    #[test]
+    fn boxes_stops_at_truncated_box() {
+        let mut data = mp4_box(b"free", b"ab");
+        let mut truncated = mp4_box(b"trak", &[0; 32]);
+        truncated.truncate(20);
+        data.extend(truncated);
+        let children = boxes(&data);
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].0, b"free");
+    }
+
+    #[test]
+    fn boxes_zero_size_extends_to_end() {
+        let mut data = mp4_box(b"free", b"ab");
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.extend_from_slice(b"mdat");
+        data.extend_from_slice(b"payload");
+        let children = boxes(&data);
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[1], (&b"mdat"[..], &b"payload"[..]));
+    }
+
+    #[test]
+    fn boxes_reads_largesize() {
+        let data = large_box(b"mdat", 20, b"abcd");
+        let children = boxes(&data);
+        assert_eq!(children, vec![(&b"mdat"[..], &b"abcd"[..])]);
+    }
+
+    #[test]
+    fn boxes_rejects_overflowing_sizes() {
+        assert!(boxes(&large_box(b"mdat", u64::MAX, b"abcd")).is_empty());
+        assert!(boxes(&large_box(b"mdat", u64::MAX - 4, b"abcd")).is_empty());
+        // Smaller than its own header
+        assert!(boxes(&[0, 0, 0, 4, b'f', b'r', b'e', b'e']).is_empty());
+    }
+
+    #[tokio::test]
+    async fn find_moov_after_mdat() {
+        let data = [mp4_box(b"ftyp", b"isom"), mp4_box(b"mdat", &[0; 64]), mp4_box(b"moov", b"index")].concat();
+        let moov = find_moov(&Bytes(data)).await.unwrap();
+        assert_eq!(moov.as_deref(), Some(&b"index"[..]));
+    }
+
+    #[tokio::test]
+    async fn find_moov_stops_on_overflowing_box() {
+        let data = [mp4_box(b"ftyp", b"isom"), large_box(b"mdat", u64::MAX, b"")].concat();
+        assert_eq!(find_moov(&Bytes(data)).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn find_moov_stops_on_undersized_box() {
+        let data = [mp4_box(b"ftyp", b"isom"), vec![0, 0, 0, 3], b"moov".to_vec()].concat();
+        assert_eq!(find_moov(&Bytes(data)).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn find_moov_rejects_oversized_moov() {
+        let data = large_box(b"moov", MAX_MOOV_BYTES + 17, b"");
+        assert_eq!(find_moov(&Bytes(data)).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,121 @@
+// PDF: page count from the page tree
+// The root /Pages node carries the total in /Count; files whose page tree sits in a
+// compressed object stream fall back to counting uncompressed /Type /Page objects.
+
+use super::ByteSource;
+use crate::models::asset::TechnicalMetadata;
+use anyhow::Result;
+
+// Scanned from the start and end of the file; the page tree root is almost always in one of them
+const SCAN_BYTES: u64 = 16 * 1024 * 1024;
+
+pub(super) async fn probe(source: &dyn ByteSource, head: &[u8]) -> Result<Option<TechnicalMetadata>> {
+    let size = source.size();
+    let mut data = source.read_at(0, SCAN_BYTES.min(size) as usize).await?;
+    if size > SCAN_BYTES {
+        let tail_start = size.saturating_sub(SCAN_BYTES).max(SCAN_BYTES);
+        data.extend(source.read_at(tail_start, (size - tail_start) as usize).await?);
+    }
+
+    let page_count = root_page_count(&data).or_else(|| count_page_objects(&data));
+
+    Ok(Some(TechnicalMetadata {
+        container: "PDF".to_string(),
+        // e.g. "%PDF-1.7"
+        format_version: head.get(5..8).map(|v| String::from_utf8_lossy(v).to_string()),
+        page_count,
+        ..Default::default()
+    }))
+}
+
+/// Largest /Count in a /Type /Pages dictionary - the root of the page tree
+fn root_page_count(data: &[u8]) -> Option<u32> {
+    find_all(data, b"/Count")
+        .filter(|&at| {
+            let window = &data[at.saturating_sub(512)..(at + 512).min(data.len())];
+            is_pages_type(window)
+        })
+        .filter_map(|at| parse_number(&data[at + b"/Count".len()..]))
+        .max()
+}
+
+fn is_pages_type(window: &[u8]) -> bool {
+    find_all(window, b"/Type").any(|at| {
+        let rest = skip_whitespace(&window[at + b"/Type".len()..]);
+        rest.starts_with(b"/Pages")
+    })
+}
+
+fn count_page_objects(data: &[u8]) -> Option<u32> {
+    let count = find_all(data, b"/Type")
+        .filter(|&at| {
+            let rest = skip_whitespace(&data[at + b"/Type".len()..]);
+            // "/Page" but not "/Pages"
+            rest.starts_with(b"/Page") && !rest.get(5).map(|b| b.is_ascii_alphanumeric()).unwrap_or(false)
+        })
+        .count();
+    (count > 0).then_some(count as u32)
+}
+
+fn find_all<'a>(data: &'a [u8], needle: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+    data.windows(needle.len())
+        .enumerate()
+        .filter(move |(_, window)| *window == needle)
+        .map(|(at, _)| at)
+}
+
+fn skip_whitespace(data: &[u8]) -> &[u8] {
+    let start = data.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(data.len());
+    &data[start..]
+}
+
+fn parse_number(data: &[u8]) -> Option<u32> {
+    let digits: String = skip_whitespace(data)
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .map(|b| *b as char)
+        .collect();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::media_probe::Bytes;
+
+    async fn probe_bytes(data: &[u8]) -> TechnicalMetadata {
+        probe(&Bytes(data.to_vec()), data).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn page_count_comes_from_the_page_tree_root() {
+        let data = b"%PDF-1.7\n\
+            1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj\n\
+            2 0 obj << /Type /Pages /Kids [3 0 R 4 0 R 5 0 R] /Count 3 >> endobj\n\
+            3 0 obj << /Type /Page /Parent 2 0 R >> endobj\n\
+            4 0 obj << /Type /Page /Parent 2 0 R >> endobj\n\
+            5 0 obj << /Type /Page /Parent 2 0 R >> endobj\n%%EOF";
+
+        let metadata = probe_bytes(data).await;
+        assert_eq!(metadata.format_version.as_deref(), Some("1.7"));
+        assert_eq!(metadata.page_count, Some(3));
+    }
+
+    #[tokio::test]
+    async fn pages_are_counted_without_a_readable_count() {
+        // The root's /Count overflows u32, so the /Type /Page objects are counted instead
+        let data = b"%PDF-1.4 << /Type /Pages /Count 99999999999 >> << /Type /Page >> << /Type/Page >>";
+        assert_eq!(probe_bytes(data).await.page_count, Some(2));
+    }
+
+    #[tokio::test]
+    async fn truncated_pdf_has_no_version_or_pages() {
+        let metadata = probe_bytes(b"%PDF-1").await;
+        assert_eq!(metadata.format_version, None);
+        assert_eq!(metadata.page_count, None);
+
+        // A dictionary cut off right after its keys
+        assert_eq!(probe_bytes(b"%PDF-1.7 << /Type /Pages /Count").await.page_count, None);
+        assert_eq!(probe_bytes(b"%PDF-1.7 << /Type").await.page_count, None);
+    }
+}
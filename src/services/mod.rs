@@ -6,7 +6,17 @@ pub mod graph_service;
 pub mod preprocessing_service;
 pub mod ai_processing;
 pub mod local_storage;
+pub mod storage;
+pub mod upload_sessions;
 pub mod google_oauth;
+pub mod oidc;
+pub mod rate_limiter;
+pub mod session_cleanup;
+pub mod sync_scheduler;
+pub mod job_worker;
+pub mod media_probe;
+pub mod content_sniffing;
+pub mod saved_search_service;
 
 pub use asset_service::*;
 pub use workflow_service::*;
@@ -14,4 +24,8 @@ pub use graph_service::*;
 pub use preprocessing_service::*;
 pub use ai_processing::*;
 pub use local_storage::*;
+pub use storage::*;
+pub use upload_sessions::*;
 pub use google_oauth::*;
+pub use sync_scheduler::*;
+pub use job_worker::*;
@@ -0,0 +1,228 @@
+// Rate limiter
+// I-FR-25: Rate limiting and access throttling
+//
+// Limits come from the startup configuration with overrides stored in platform_settings;
+// each instance reloads them periodically so an edit on one instance reaches all of them.
+// Counting is either in memory (sliding window, per instance) or in Postgres (fixed
+// one-minute windows shared by every instance).
+
+use crate::config::RateLimitConfig;
+use crate::db::DbPool;
+use crate::db::repositories::{rate_limit_repository::RateLimitRepository, settings_repository::SettingsRepository};
+use crate::models::user::UserRole;
+use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
+
+pub const WINDOW: Duration = Duration::from_secs(60);
+
+/// Outcome of counting one request
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Time until the window frees up capacity
+    pub reset: Duration,
+}
+
+enum Counter {
+    Memory(Mutex<HashMap<String, Vec<Instant>>>),
+    Postgres(DbPool),
+}
+
+pub struct RateLimiter {
+    defaults: RateLimitConfig,
+    config: RwLock<RateLimitConfig>,
+    counter: Counter,
+}
+
+impl RateLimiter {
+    /// Counts per instance
+    pub fn in_memory(defaults: RateLimitConfig) -> Self {
+        Self::with_counter(defaults, Counter::Memory(Mutex::new(HashMap::new())))
+    }
+
+    /// Counts in the database so several instances enforce one budget
+    pub fn shared(defaults: RateLimitConfig, db_pool: DbPool) -> Self {
+        Self::with_counter(defaults, Counter::Postgres(db_pool))
+    }
+
+    pub fn from_config(defaults: &RateLimitConfig, db_pool: DbPool) -> Result<Self> {
+        defaults.validate().map_err(|reason| anyhow::anyhow!("Invalid rate limit configuration: {}", reason))?;
+        match defaults.backend.as_str() {
+            "memory" => Ok(Self::in_memory(defaults.clone())),
+            "postgres" => Ok(Self::shared(defaults.clone(), db_pool)),
+            other => Err(anyhow::anyhow!("Unknown RATE_LIMIT_BACKEND {}", other)),
+        }
+    }
+
+    fn with_counter(defaults: RateLimitConfig, counter: Counter) -> Self {
+        Self {
+            config: RwLock::new(defaults.clone()),
+            defaults,
+            counter,
+        }
+    }
+
+    pub fn defaults(&self) -> &RateLimitConfig {
+        &self.defaults
+    }
+
+    pub async fn config(&self) -> RateLimitConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: RateLimitConfig) {
+        *self.config.write().await = config;
+    }
+
+    /// Apply the limits stored in the database
+    pub async fn reload(&self, db_pool: &DbPool) -> Result<()> {
+        let config = SettingsRepository::get_rate_limit_config(db_pool, &self.defaults).await?;
+        // Stored limits are validated when saved; keep the current ones if a bad row slipped in
+        config.validate().map_err(|reason| anyhow::anyhow!("Invalid stored rate limits: {}", reason))?;
+        self.set_config(config).await;
+        Ok(())
+    }
+
+    /// Requests per minute for a caller: the role's limit, lowered by the key's own quota if it has one
+    pub async fn limit_for(&self, role: Option<&UserRole>, key_limit: Option<u32>) -> u32 {
+        let config = self.config.read().await;
+        let roles = &config.per_role;
+        let role_limit = match role {
+            Some(UserRole::Admin) => roles.admin,
+            Some(UserRole::ContentManager) => roles.content_manager,
+            Some(UserRole::Editor) => roles.editor,
+            Some(UserRole::Developer) => roles.developer,
+            Some(UserRole::Viewer) => roles.viewer,
+            None => config.default_limit_per_minute,
+        };
+        key_limit.map(|limit| limit.min(role_limit).max(1)).unwrap_or(role_limit)
+    }
+
+    /// Count a request against `identifier`; fails open if the shared counter is unreachable
+    pub async fn check(&self, identifier: &str, limit: u32) -> RateLimitDecision {
+        match &self.counter {
+            Counter::Memory(requests) => {
+                let mut requests = requests.lock().unwrap();
+                let now = Instant::now();
+
+                // Clean old requests outside the window
+                let timestamps = requests.entry(identifier.to_string()).or_default();
+                timestamps.retain(|&time| now.duration_since(time) < WINDOW);
+
+                let allowed = timestamps.len() < limit as usize;
+                if allowed {
+                    timestamps.push(now);
+                }
+                let oldest = timestamps.first().copied().unwrap_or(now);
+                RateLimitDecision {
+                    allowed,
+                    limit,
+                    remaining: limit.saturating_sub(timestamps.len() as u32),
+                    reset: WINDOW.saturating_sub(now.duration_since(oldest)),
+                }
+            }
+            Counter::Postgres(db_pool) => {
+                let now = Utc::now();
+                let window_start = window_start(now);
+                let reset = (window_start + chrono::Duration::from_std(WINDOW).unwrap() - now)
+                    .to_std()
+                    .unwrap_or_default();
+                match RateLimitRepository::increment(db_pool, identifier, window_start).await {
+                    Ok(count) => RateLimitDecision {
+                        allowed: count as u32 <= limit,
+                        limit,
+                        remaining: limit.saturating_sub(count as u32),
+                        reset,
+                    },
+                    Err(e) => {
+                        error!("Shared rate limit counter unavailable, allowing request: {}", e);
+                        RateLimitDecision { allowed: true, limit, remaining: limit, reset }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Periodically pick up limits edited on other instances and drop finished windows
+    pub fn start_maintenance(self: Arc<Self>, db_pool: DbPool, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                if let Err(e) = self.reload(&db_pool).await {
+                    warn!("Failed to reload rate limits: {}", e);
+                }
+                match &self.counter {
+                    Counter::Memory(requests) => {
+                        let now = Instant::now();
+                        requests.lock().unwrap().retain(|_, timestamps| {
+                            timestamps.last().map(|&last| now.duration_since(last) < WINDOW).unwrap_or(false)
+                        });
+                    }
+                    Counter::Postgres(db_pool) => {
+                        let cutoff = window_start(Utc::now()) - chrono::Duration::from_std(WINDOW).unwrap();
+                        if let Err(e) = RateLimitRepository::purge_before(db_pool, cutoff).await {
+                            warn!("Failed to purge rate limit counters: {}", e);
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
+
+// Start of the fixed window containing `at`
+fn window_start(at: DateTime<Utc>) -> DateTime<Utc> {
+    let window = WINDOW.as_secs() as i64;
+    let timestamp = at.timestamp();
+    Utc.timestamp_opt(timestamp - timestamp.rem_euclid(window), 0).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RoleRateLimits;
+    use std::sync::Arc;
+
+    fn limits(default_limit_per_minute: u32, viewer: u32) -> RateLimitConfig {
+        RateLimitConfig {
+            default_limit_per_minute,
+            per_role: RoleRateLimits { admin: 1000, content_manager: 200, editor: 100, developer: 500, viewer },
+            backend: "memory".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn key_quotas_only_lower_the_role_limit() {
+        let limiter = RateLimiter::in_memory(limits(100, 50));
+        assert_eq!(limiter.limit_for(Some(&UserRole::Viewer), None).await, 50);
+        assert_eq!(limiter.limit_for(Some(&UserRole::Viewer), Some(10)).await, 10);
+        assert_eq!(limiter.limit_for(Some(&UserRole::Viewer), Some(5000)).await, 50);
+        assert_eq!(limiter.limit_for(Some(&UserRole::Viewer), Some(0)).await, 1);
+        assert_eq!(limiter.limit_for(None, Some(20)).await, 20);
+    }
+
+    #[tokio::test]
+    async fn zero_role_limit_does_not_panic() {
+        let limiter = RateLimiter::in_memory(limits(100, 50));
+        limiter.set_config(limits(0, 0)).await;
+        assert_eq!(limiter.limit_for(Some(&UserRole::Viewer), Some(10)).await, 1);
+        assert_eq!(limiter.limit_for(None, Some(10)).await, 1);
+    }
+
+    #[tokio::test]
+    async fn invalid_limits_are_refused_at_startup() {
+        let pool = Arc::new(sqlx::postgres::PgPoolOptions::new().connect_lazy("postgres://localhost/unused").unwrap());
+        let error = RateLimiter::from_config(&limits(0, 50), pool.clone()).err().unwrap();
+        assert!(error.to_string().contains("at least 1"), "{}", error);
+        assert!(RateLimiter::from_config(&limits(100, 0), pool.clone()).is_err());
+        assert!(RateLimiter::from_config(&limits(100, 50), pool).is_ok());
+    }
+}
@@ -0,0 +1,189 @@
+// Saved search evaluation
+// I-FR-22: Check each newly processed asset against every saved search, record new matches
+// and notify the search's webhook.
+
+use crate::db::DbPool;
+use crate::db::repositories::{
+    graph_repository::{GraphRepository, SearchFilter},
+    saved_search_repository::SavedSearchRepository,
+};
+use crate::models::saved_search::SavedSearch;
+use chrono::{DateTime, Utc};
+use crate::utils::public_url;
+use reqwest::{redirect::Policy, Client};
+use serde_json::json;
+use std::time::Duration;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Searches checked per query; keeps the UNION under Postgres' bind parameter limit
+const SEARCHES_PER_QUERY: usize = 200;
+
+/// Evaluate saved searches for an asset that finished processing, in the background so the
+/// job worker can move on. Failures are logged; they never fail the job that produced the asset.
+pub fn spawn_evaluation(db_pool: DbPool, asset_uuid: Uuid) {
+    tokio::spawn(async move {
+        evaluate_asset(&db_pool, asset_uuid).await;
+    });
+}
+
+async fn evaluate_asset(db_pool: &DbPool, asset_uuid: Uuid) {
+    let searches = match SavedSearchRepository::list_all(db_pool).await {
+        Ok(searches) => searches,
+        Err(e) => {
+            warn!("Failed to load saved searches for asset {}: {}", asset_uuid, e);
+            return;
+        }
+    };
+
+    for batch in searches.chunks(SEARCHES_PER_QUERY) {
+        if let Err(e) = evaluate_batch(db_pool, batch, asset_uuid).await {
+            warn!("Failed to evaluate saved searches for asset {}: {}", asset_uuid, e);
+        }
+    }
+}
+
+async fn evaluate_batch(db_pool: &DbPool, searches: &[SavedSearch], asset_uuid: Uuid) -> anyhow::Result<()> {
+    let criteria: Vec<(Uuid, String, SearchFilter)> = searches.iter()
+        .filter_map(|search| match serde_json::from_value(search.filters.clone()) {
+            Ok(filter) => Some((search.id, search.query.clone(), filter)),
+            Err(e) => {
+                warn!("Saved search {} has unreadable filters: {}", search.id, e);
+                None
+            }
+        })
+        .collect();
+
+    let matched = GraphRepository::matching_searches(db_pool, &criteria, asset_uuid).await?;
+    if matched.is_empty() {
+        return Ok(());
+    }
+    // Reprocessing an asset that already matched is not news
+    let recorded = SavedSearchRepository::record_matches(db_pool, &matched, asset_uuid).await?;
+
+    for (search_id, matched_at) in recorded {
+        let Some(search) = searches.iter().find(|s| s.id == search_id) else { continue };
+        info!("Asset {} matched saved search {} ({})", asset_uuid, search.id, search.name);
+        if let Some(url) = &search.webhook_url {
+            tokio::spawn(notify_webhook(url.clone(), search.clone(), asset_uuid, matched_at));
+        }
+    }
+    Ok(())
+}
+
+// Best effort: one attempt, logged on failure; the match stays fetchable either way
+async fn notify_webhook(url: String, search: SavedSearch, asset_uuid: Uuid, matched_at: DateTime<Utc>) {
+    // Checked again at send time: the host may have been re-pointed since the search was saved.
+    // The connection is pinned to the addresses checked here and redirects are not followed.
+    let target = match public_url::resolve_public(&url).await {
+        Ok(target) => target,
+        Err(e) => {
+            warn!("Webhook for saved search {} refused: {}", search.id, e);
+            return;
+        }
+    };
+    let mut builder = Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .redirect(Policy::none());
+    if let Some(domain) = &target.domain {
+        for addr in &target.addrs {
+            builder = builder.resolve(domain, *addr);
+        }
+    }
+    let client = match builder.build() {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("Webhook for saved search {} failed: {}", search.id, e);
+            return;
+        }
+    };
+
+    let payload = json!({
+        "event": "saved_search.match",
+        "saved_search_id": search.id,
+        "saved_search_name": search.name,
+        "asset_uuid": asset_uuid,
+        "matched_at": matched_at,
+        "matches_url": format!("/api/saved-searches/{}/matches", search.id),
+    });
+
+    match client.post(target.url).json(&payload).send().await {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => warn!("Webhook for saved search {} returned {}", search.id, response.status()),
+        Err(e) => warn!("Webhook for saved search {} failed: {}", search.id, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{db_pool, insert_asset, insert_user};
+    use sqlx::PgPool;
+
+    async fn save(pool: &DbPool, user_id: Uuid, name: &str, query: &str, filters: serde_json::Value) -> Uuid {
+        let now = Utc::now();
+        let search = SavedSearch {
+            id: Uuid::new_v4(),
+            user_id,
+            name: name.to_string(),
+            query: query.to_string(),
+            filters,
+            webhook_url: None,
+            last_checked_at: now,
+            created_at: now,
+            updated_at: None,
+        };
+        SavedSearchRepository::create(pool, &search).await.unwrap();
+        search.id
+    }
+
+    async fn matched_searches(pool: &DbPool, asset_uuid: Uuid) -> Vec<Uuid> {
+        let mut ids: Vec<Uuid> = sqlx::query_scalar("SELECT saved_search_id FROM saved_search_matches WHERE asset_uuid = $1")
+            .bind(asset_uuid)
+            .fetch_all(pool.as_ref())
+            .await
+            .unwrap();
+        ids.sort();
+        ids
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn records_matches_for_query_and_filters(pool: PgPool) {
+        let pool = db_pool(pool);
+        let user_id = insert_user(&pool, "searcher@example.com", "VIEWER").await;
+        let by_query = save(&pool, user_id, "Budget", "budget", json!({})).await;
+        let by_filter = save(&pool, user_id, "Budget videos", "budget", json!({"asset_type": ["Video"]})).await;
+        let wrong_type = save(&pool, user_id, "Budget audio", "budget", json!({"asset_type": ["Audio"]})).await;
+        let other_query = save(&pool, user_id, "Weather", "weather", json!({})).await;
+        let asset = insert_asset(&pool, "briefing.mp4", json!({"title": "City budget briefing"})).await;
+
+        evaluate_asset(&pool, asset).await;
+
+        let mut expected = vec![by_query, by_filter];
+        expected.sort();
+        let matched = matched_searches(&pool, asset).await;
+        assert_eq!(matched, expected);
+        assert!(!matched.contains(&wrong_type) && !matched.contains(&other_query));
+
+        // Re-evaluating a reprocessed asset records nothing new
+        evaluate_asset(&pool, asset).await;
+        assert_eq!(matched_searches(&pool, asset).await, expected);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn unreadable_filters_do_not_block_other_searches(pool: PgPool) {
+        let pool = db_pool(pool);
+        let user_id = insert_user(&pool, "searcher@example.com", "VIEWER").await;
+        save(&pool, user_id, "Broken", "budget", json!({"created_after": "not a date"})).await;
+        let good = save(&pool, user_id, "Budget", "budget", json!({})).await;
+        let asset = insert_asset(&pool, "report.mp4", json!({"title": "Budget report"})).await;
+
+        evaluate_asset(&pool, asset).await;
+
+        assert_eq!(matched_searches(&pool, asset).await, vec![good]);
+    }
+}
@@ -0,0 +1,33 @@
+// Expired session cleanup
+// I-FR-23: Revocation entries, sessions and abandoned sign-ins are only needed until they expire
+
+use crate::db::DbPool;
+use crate::db::repositories::oauth_state_repository::OAuthStateRepository;
+use crate::db::repositories::token_repository::TokenRepository;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+pub struct SessionCleanup;
+
+impl SessionCleanup {
+    pub fn start_cleanup(db_pool: DbPool, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                Self::purge_expired(&db_pool).await;
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+
+    async fn purge_expired(db_pool: &DbPool) {
+        match TokenRepository::purge_expired(db_pool).await {
+            Ok(0) => {}
+            Ok(removed) => info!("Purged {} expired sessions and revoked tokens", removed),
+            Err(e) => error!("Token revocation purge failed: {}", e),
+        }
+        if let Err(e) = OAuthStateRepository::purge_expired(db_pool).await {
+            error!("OAuth state purge failed: {}", e);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+// Storage backends
+// I-FR-02: File storage shared by every ingestion path
+//
+// Assets record their location as a URI (`file:///...`, `s3://bucket/key`) so each
+// one resolves to the backend that holds it, whatever the current default is.
+
+use crate::aws::s3::S3Service;
+use crate::config::AppConfig;
+use crate::services::local_storage::LocalStorageService;
+use crate::utils::http_range::ByteRange;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::io::AsyncRead;
+use tracing::info;
+
+pub type StorageReader = Pin<Box<dyn AsyncRead + Send>>;
+
+#[derive(Debug, Clone)]
+pub struct ObjectStat {
+    pub size: u64,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// URI scheme this backend owns (`file`, `s3`)
+    fn scheme(&self) -> &'static str;
+
+    /// Store `data` under `category` and return the URI to persist in `assets.file_path`
+    async fn put(&self, category: &str, filename: &str, data: Vec<u8>) -> Result<String>;
+
+    async fn get(&self, uri: &str) -> Result<Vec<u8>>;
+
+    async fn delete(&self, uri: &str) -> Result<()>;
+
+    async fn stat(&self, uri: &str) -> Result<ObjectStat>;
+
+    /// Open the object (or just `range` of it) for reading without buffering it in memory
+    async fn stream(&self, uri: &str, range: Option<ByteRange>) -> Result<StorageReader>;
+
+    /// Start an upload that receives its content chunk by chunk
+    async fn writer(&self, category: &str, filename: &str) -> Result<Box<dyn UploadWriter>>;
+}
+
+#[async_trait]
+pub trait UploadWriter: Send {
+    async fn write(&mut self, chunk: &[u8]) -> Result<()>;
+
+    /// Flush remaining data and return the URI of the stored object
+    async fn finish(self: Box<Self>) -> Result<String>;
+
+    /// Discard everything written so far
+    async fn abort(self: Box<Self>) -> Result<()>;
+}
+
+/// Scheme of a storage URI; paths stored before URIs were introduced count as `file`
+pub fn uri_scheme(uri: &str) -> &str {
+    uri.split_once("://").map(|(scheme, _)| scheme).unwrap_or("file")
+}
+
+pub struct StorageRegistry {
+    default: Arc<dyn StorageBackend>,
+    backends: HashMap<&'static str, Arc<dyn StorageBackend>>,
+    max_upload_bytes: u64,
+}
+
+impl StorageRegistry {
+    pub fn new(default: Arc<dyn StorageBackend>) -> Self {
+        let mut backends = HashMap::new();
+        backends.insert(default.scheme(), default.clone());
+        Self { default, backends, max_upload_bytes: u64::MAX }
+    }
+
+    pub fn with_max_upload_bytes(mut self, max_upload_bytes: u64) -> Self {
+        self.max_upload_bytes = max_upload_bytes;
+        self
+    }
+
+    /// Register an additional backend for reading existing assets
+    pub fn with_backend(mut self, backend: Arc<dyn StorageBackend>) -> Self {
+        self.backends.entry(backend.scheme()).or_insert(backend);
+        self
+    }
+
+    /// Build the registry once at startup; `storage.backend` picks where new files go
+    pub async fn from_config(config: &AppConfig) -> Result<Self> {
+        let local: Arc<dyn StorageBackend> = Arc::new(LocalStorageService::new(Some(config.storage.local_path.clone())));
+
+        let sdk_config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(aws_sdk_s3::config::Region::new(config.aws.region.clone()))
+            .load()
+            .await;
+        let s3: Arc<dyn StorageBackend> = Arc::new(S3Service::new(&sdk_config, config.aws.s3_bucket_staging.clone()));
+
+        let registry = match config.storage.backend.as_str() {
+            "local" => Self::new(local).with_backend(s3),
+            "s3" => Self::new(s3).with_backend(local),
+            other => anyhow::bail!("Unknown STORAGE_BACKEND '{}', expected 'local' or 's3'", other),
+        }
+        .with_max_upload_bytes(config.storage.max_upload_bytes);
+
+        info!("Storage backend: {}", registry.default.scheme());
+        Ok(registry)
+    }
+
+    /// Backend that receives newly stored files
+    pub fn default_backend(&self) -> &Arc<dyn StorageBackend> {
+        &self.default
+    }
+
+    /// Largest file accepted by the upload endpoints
+    pub fn max_upload_bytes(&self) -> u64 {
+        self.max_upload_bytes
+    }
+
+    /// Backend holding the object at `uri`
+    pub fn resolve(&self, uri: &str) -> Result<&Arc<dyn StorageBackend>> {
+        let scheme = uri_scheme(uri);
+        self.backends
+            .get(scheme)
+            .ok_or_else(|| anyhow::anyhow!("No storage backend registered for scheme '{}'", scheme))
+    }
+
+    pub async fn put(&self, category: &str, filename: &str, data: Vec<u8>) -> Result<String> {
+        self.default.put(category, filename, data).await
+    }
+
+    pub async fn writer(&self, category: &str, filename: &str) -> Result<Box<dyn UploadWriter>> {
+        self.default.writer(category, filename).await
+    }
+
+    pub async fn get(&self, uri: &str) -> Result<Vec<u8>> {
+        self.resolve(uri)?.get(uri).await
+    }
+
+    pub async fn delete(&self, uri: &str) -> Result<()> {
+        self.resolve(uri)?.delete(uri).await
+    }
+
+    pub async fn stat(&self, uri: &str) -> Result<ObjectStat> {
+        self.resolve(uri)?.stat(uri).await
+    }
+
+    pub async fn stream(&self, uri: &str, range: Option<ByteRange>) -> Result<StorageReader> {
+        self.resolve(uri)?.stream(uri, range).await
+    }
+}
@@ -0,0 +1,269 @@
+// Sync scheduler
+// I-FR-01: Sync on a configurable interval
+// I-FR-03: Asynchronous execution
+// I-FR-04: Parallel execution of controllers
+
+use crate::config::SyncConfig;
+use crate::controllers::base::Controller;
+use crate::controllers::{BrightcoveIngressController, LocalFileIngressController};
+use crate::db::DbPool;
+use crate::db::repositories::controller_repository::ControllerRepository;
+use crate::external::brightcove::BrightcoveClient;
+use crate::services::storage::StorageRegistry;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use futures_util::FutureExt;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+pub struct SyncScheduler {
+    db_pool: DbPool,
+    sync_config: SyncConfig,
+    storage: Arc<StorageRegistry>,
+    controllers: Vec<Arc<dyn Controller>>,
+    poll_interval: Duration,
+}
+
+impl SyncScheduler {
+    pub fn new(db_pool: DbPool, sync_config: SyncConfig, storage: Arc<StorageRegistry>) -> Self {
+        Self {
+            db_pool,
+            sync_config,
+            storage,
+            controllers: Vec::new(),
+            // How often controller_configs is re-read, so interval changes apply without a restart
+            poll_interval: Duration::from_secs(30),
+        }
+    }
+
+    pub fn register(mut self, controller: Arc<dyn Controller>) -> Self {
+        self.controllers.push(controller);
+        self
+    }
+
+    /// Register every controller whose credentials are present in the environment
+    pub fn with_env_controllers(mut self) -> Self {
+        let db_pool = self.db_pool.clone();
+        let storage = self.storage.clone();
+
+        if let (Ok(api_key), Ok(account_id)) = (
+            std::env::var("BRIGHTCOVE_API_KEY"),
+            std::env::var("BRIGHTCOVE_ACCOUNT_ID"),
+        ) {
+            let controller = match std::env::var("BRIGHTCOVE_CMS_URL") {
+                Ok(cms_url) => BrightcoveIngressController::with_client(
+                    BrightcoveClient::with_base_url(api_key, account_id, &cms_url),
+                    db_pool.clone(),
+                    storage.clone(),
+                ),
+                Err(_) => BrightcoveIngressController::new(api_key, account_id, db_pool.clone(), storage.clone()),
+            };
+            self = self.register(Arc::new(controller));
+        }
+
+        if let Ok(watch_directory) = std::env::var("LOCAL_INGRESS_DIR") {
+            self = self.register(Arc::new(LocalFileIngressController::new(watch_directory, db_pool.clone(), storage.clone())));
+        }
+
+        self
+    }
+
+    /// Spawn one sync loop per registered controller
+    pub async fn start(self) -> Result<Vec<JoinHandle<()>>> {
+        let mut handles = Vec::new();
+
+        for controller in self.controllers {
+            let default_interval = self.sync_config.interval_for(controller.name());
+            ControllerRepository::ensure_config(&self.db_pool, controller.name(), default_interval as i32).await?;
+
+            info!("Scheduling controller {} ({})", controller.name(), controller.version());
+            handles.push(tokio::spawn(run_controller_loop(
+                self.db_pool.clone(),
+                controller,
+                default_interval,
+                self.poll_interval,
+            )));
+        }
+
+        Ok(handles)
+    }
+}
+
+async fn run_controller_loop(
+    db_pool: DbPool,
+    controller: Arc<dyn Controller>,
+    default_interval_minutes: u64,
+    poll_interval: Duration,
+) {
+    // Tracks failed runs too, so a failing controller still waits a full interval
+    let mut last_attempt: Option<chrono::DateTime<Utc>> = None;
+
+    loop {
+        match ControllerRepository::get_config(&db_pool, controller.name()).await {
+            Ok(config) => {
+                let enabled = config.as_ref().and_then(|c| c.is_enabled).unwrap_or(true);
+                let interval_minutes = config.as_ref()
+                    .and_then(|c| c.sync_interval_minutes)
+                    .filter(|m| *m > 0)
+                    .map(|m| m as u64)
+                    .unwrap_or(default_interval_minutes);
+                let last_run = config.as_ref()
+                    .and_then(|c| c.last_sync_at)
+                    .into_iter()
+                    .chain(last_attempt)
+                    .max();
+
+                let due = last_run
+                    .map(|t| Utc::now() - t >= chrono::Duration::minutes(interval_minutes as i64))
+                    .unwrap_or(true);
+
+                if enabled && due {
+                    last_attempt = Some(Utc::now());
+                    if let Err(e) = run_once(&db_pool, controller.as_ref()).await {
+                        error!("Sync of {} failed: {}", controller.name(), e);
+                    }
+                }
+            }
+            Err(e) => {
+                error!("Failed to load config for {}: {}", controller.name(), e);
+            }
+        }
+
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+async fn run_once(db_pool: &DbPool, controller: &dyn Controller) -> Result<()> {
+    // Released below; a lock dropped on any other path closes its connection, which also frees it
+    let Some(lock) = ControllerRepository::try_lock(db_pool, controller.name()).await? else {
+        warn!("Sync of {} already running elsewhere, skipping", controller.name());
+        return Ok(());
+    };
+
+    let started_at = Utc::now();
+    // A panicking controller fails this run instead of ending its sync loop
+    let outcome = match AssertUnwindSafe(controller.sync()).catch_unwind().await {
+        Ok(outcome) => outcome,
+        Err(_) => Err(anyhow!("{} panicked during sync", controller.name())),
+    };
+
+    let result = match outcome {
+        Ok(result) => {
+            info!(
+                "Sync of {} finished: processed={} created={} updated={} skipped={} errors={}",
+                controller.name(),
+                result.assets_processed,
+                result.assets_created,
+                result.assets_updated,
+                result.assets_skipped,
+                result.errors.len(),
+            );
+            // Next run picks up changes made while this one was in flight
+            ControllerRepository::mark_synced(db_pool, controller.name(), started_at).await
+        }
+        Err(e) => Err(e),
+    };
+
+    lock.release().await?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controllers::base::SyncResult;
+    use crate::db::test_support::db_pool;
+    use crate::models::action_record::ActionRecord;
+    use crate::models::asset::Asset;
+    use async_trait::async_trait;
+    use sqlx::PgPool;
+
+    enum Outcome {
+        Succeed,
+        Fail,
+        Panic,
+    }
+
+    struct FakeController(Outcome);
+
+    #[async_trait]
+    impl Controller for FakeController {
+        fn name(&self) -> &str {
+            "FakeController"
+        }
+
+        fn version(&self) -> &str {
+            "v0"
+        }
+
+        async fn sync(&self) -> Result<SyncResult> {
+            match self.0 {
+                Outcome::Succeed => Ok(SyncResult {
+                    assets_processed: 0,
+                    assets_created: 0,
+                    assets_updated: 0,
+                    assets_skipped: 0,
+                    errors: Vec::new(),
+                }),
+                Outcome::Fail => Err(anyhow!("source unavailable")),
+                Outcome::Panic => panic!("controller bug"),
+            }
+        }
+
+        async fn check_duplicate(&self, _file_hash: &str) -> Result<Option<Asset>> {
+            Ok(None)
+        }
+
+        async fn log_action(&self, _action: ActionRecord) -> Result<()> {
+            Ok(())
+        }
+
+        async fn rollback(&self, _asset_uuid: uuid::Uuid, _version: i32) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    async fn lock_is_free(pool: &DbPool) -> bool {
+        match ControllerRepository::try_lock(pool, "FakeController").await.unwrap() {
+            Some(lock) => {
+                lock.release().await.unwrap();
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn lock_is_released_however_the_sync_ends(pool: PgPool) {
+        let pool = db_pool(pool);
+        ControllerRepository::ensure_config(&pool, "FakeController", 15).await.unwrap();
+
+        let error = run_once(&pool, &FakeController(Outcome::Panic)).await.unwrap_err();
+        assert!(error.to_string().contains("panicked"), "{}", error);
+        assert!(lock_is_free(&pool).await);
+
+        assert!(run_once(&pool, &FakeController(Outcome::Fail)).await.is_err());
+        assert!(lock_is_free(&pool).await);
+        assert!(ControllerRepository::get_last_sync_at(&pool, "FakeController").await.unwrap().is_none());
+
+        run_once(&pool, &FakeController(Outcome::Succeed)).await.unwrap();
+        assert!(lock_is_free(&pool).await);
+        assert!(ControllerRepository::get_last_sync_at(&pool, "FakeController").await.unwrap().is_some());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn skips_a_controller_synced_elsewhere(pool: PgPool) {
+        let pool = db_pool(pool);
+        ControllerRepository::ensure_config(&pool, "FakeController", 15).await.unwrap();
+
+        let held = ControllerRepository::try_lock(&pool, "FakeController").await.unwrap().unwrap();
+        run_once(&pool, &FakeController(Outcome::Succeed)).await.unwrap();
+        assert!(ControllerRepository::get_last_sync_at(&pool, "FakeController").await.unwrap().is_none());
+        held.release().await.unwrap();
+    }
+}
@@ -0,0 +1,246 @@
+// Resumable upload sessions
+// I-FR-31: Media upload and ingestion for large files over unreliable connections
+//
+// Each PATCH body is received into its own chunk file without holding any lock, then
+// appended to the session's staging file when the new offset is committed. Both live on
+// this instance's disk, so requests for one session must reach the same instance (or
+// UPLOAD_STAGING_PATH must be shared storage).
+
+use crate::config::StorageConfig;
+use crate::db::DbPool;
+use crate::db::repositories::upload_repository::UploadRepository;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+// Expired sessions removed per query during cleanup
+const EXPIRED_BATCH: i64 = 100;
+
+pub struct UploadSessionService {
+    staging_dir: PathBuf,
+    ttl: chrono::Duration,
+}
+
+impl UploadSessionService {
+    pub fn new(config: &StorageConfig) -> Result<Self> {
+        let staging_dir = PathBuf::from(&config.upload_staging_path);
+        std::fs::create_dir_all(&staging_dir)?;
+
+        Ok(Self {
+            staging_dir,
+            ttl: chrono::Duration::hours(config.upload_session_ttl_hours.max(1) as i64),
+        })
+    }
+
+    pub fn staging_path(&self, upload_id: Uuid) -> PathBuf {
+        self.staging_dir.join(format!("{}.part", upload_id))
+    }
+
+    /// A new, uniquely named file to receive one chunk of the upload into
+    pub fn chunk_file(&self, upload_id: Uuid) -> ChunkFile {
+        ChunkFile {
+            path: self.staging_dir.join(format!("{}.{}.chunk", upload_id, Uuid::new_v4())),
+        }
+    }
+
+    /// Append a received chunk to the staging file at `offset`, discarding anything past it that
+    /// an earlier failed append left behind. Callers hold the session's lock.
+    pub async fn append_chunk(&self, staging_path: &str, offset: u64, chunk: &ChunkFile) -> Result<()> {
+        let mut source = tokio::fs::File::open(chunk.path()).await?;
+        let mut file = tokio::fs::OpenOptions::new().write(true).open(staging_path).await?;
+        file.set_len(offset).await?;
+        file.seek(std::io::SeekFrom::Start(offset)).await?;
+        tokio::io::copy(&mut source, &mut file).await?;
+        file.flush().await?;
+        file.sync_data().await?;
+        Ok(())
+    }
+
+    /// Expiry for a session that has just been created or received data
+    pub fn next_expiry(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now() + self.ttl
+    }
+
+    /// Drop a session's staged bytes and its row
+    pub async fn remove(&self, db_pool: &DbPool, upload_id: Uuid, staging_path: &str) -> Result<()> {
+        remove_if_present(Path::new(staging_path)).await?;
+        // Chunks left by an instance that stopped while receiving them
+        let prefix = format!("{}.", upload_id);
+        let mut entries = tokio::fs::read_dir(&self.staging_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with(&prefix) && name.ends_with(".chunk") {
+                remove_if_present(&entry.path()).await?;
+            }
+        }
+        UploadRepository::delete(db_pool, upload_id).await
+    }
+
+    /// Periodically garbage-collect expired sessions and their staged files
+    pub fn start_cleanup(self: Arc<Self>, db_pool: DbPool, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match self.collect_expired(&db_pool).await {
+                    Ok(0) => {}
+                    Ok(removed) => info!("Removed {} expired upload sessions", removed),
+                    Err(e) => error!("Upload session cleanup failed: {}", e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+
+    async fn collect_expired(&self, db_pool: &DbPool) -> Result<usize> {
+        let mut removed = 0;
+        loop {
+            let expired = UploadRepository::list_expired(db_pool, EXPIRED_BATCH).await?;
+            let mut progressed = false;
+            for session in &expired {
+                // A request holding the lock may be about to extend the session; leave it for the next pass
+                let Some(lock) = UploadRepository::try_lock(db_pool, session.upload_id).await? else {
+                    continue;
+                };
+                let result = match UploadRepository::is_expired(db_pool, session.upload_id).await {
+                    Ok(true) => self.remove(db_pool, session.upload_id, &session.staging_path).await.map(|_| true),
+                    Ok(false) => Ok(false),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = lock.release().await {
+                    warn!("Failed to release the lock on upload session {}: {}", session.upload_id, e);
+                }
+
+                match result {
+                    Ok(true) => {
+                        removed += 1;
+                        progressed = true;
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        // Leave the row so the next pass retries; stop this pass to avoid spinning on it
+                        warn!("Failed to remove upload session {}: {}", session.upload_id, e);
+                        return Ok(removed);
+                    }
+                }
+            }
+            if !progressed || (expired.len() as i64) < EXPIRED_BATCH {
+                return Ok(removed);
+            }
+        }
+    }
+}
+
+async fn remove_if_present(path: &Path) -> Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// One PATCH body on disk; deleted once appended, or when the request ends without committing it
+pub struct ChunkFile {
+    path: PathBuf,
+}
+
+impl ChunkFile {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ChunkFile {
+    fn drop(&mut self) {
+        match std::fs::remove_file(&self.path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Failed to remove upload chunk {}: {}", self.path.display(), e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::db_pool;
+    use crate::models::upload::UploadSession;
+    use sqlx::PgPool;
+
+    fn service(dir: &std::path::Path) -> UploadSessionService {
+        UploadSessionService::new(&StorageConfig {
+            backend: "local".to_string(),
+            local_path: dir.join("storage").to_string_lossy().to_string(),
+            max_upload_bytes: 1024,
+            upload_staging_path: dir.to_string_lossy().to_string(),
+            upload_session_ttl_hours: 1,
+        }).unwrap()
+    }
+
+    async fn stage(pool: &DbPool, service: &UploadSessionService, expires_in: chrono::Duration) -> UploadSession {
+        let upload_id = Uuid::new_v4();
+        let staging_path = service.staging_path(upload_id);
+        std::fs::write(&staging_path, b"partial").unwrap();
+        let now = chrono::Utc::now();
+        let session = UploadSession {
+            upload_id,
+            filename: "clip.mp4".to_string(),
+            upload_length: 100,
+            upload_offset: 7,
+            metadata: serde_json::json!({}),
+            staging_path: staging_path.to_string_lossy().to_string(),
+            created_by: None,
+            created_at: now,
+            updated_at: now,
+            expires_at: now + expires_in,
+        };
+        UploadRepository::create(pool, &session).await.unwrap();
+        session
+    }
+
+    async fn exists(pool: &DbPool, session: &UploadSession) -> bool {
+        let row: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM upload_sessions WHERE upload_id = $1)")
+            .bind(session.upload_id)
+            .fetch_one(pool.as_ref())
+            .await
+            .unwrap();
+        row && std::path::Path::new(&session.staging_path).exists()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn collects_expired_sessions_not_in_use(pool: PgPool) {
+        let pool = db_pool(pool);
+        let dir = std::env::temp_dir().join(format!("upload-sessions-test-{}", Uuid::new_v4()));
+        let service = service(&dir);
+        let expired = stage(&pool, &service, chrono::Duration::minutes(-5)).await;
+        let in_use = stage(&pool, &service, chrono::Duration::minutes(-5)).await;
+        let active = stage(&pool, &service, chrono::Duration::minutes(30)).await;
+
+        let lock = UploadRepository::try_lock(&pool, in_use.upload_id).await.unwrap().unwrap();
+        assert_eq!(service.collect_expired(&pool).await.unwrap(), 1);
+        assert!(!exists(&pool, &expired).await);
+        assert!(exists(&pool, &in_use).await, "a session being written to is left for the next pass");
+        assert!(exists(&pool, &active).await);
+
+        // A lock dropped without release (e.g. a cancelled request) ends its connection and frees the session
+        drop(lock);
+        let mut removed = 0;
+        for _ in 0..50 {
+            removed = service.collect_expired(&pool).await.unwrap();
+            if removed > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(removed, 1);
+        assert!(!exists(&pool, &in_use).await);
+        assert!(exists(&pool, &active).await);
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
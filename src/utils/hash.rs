@@ -26,3 +26,23 @@ pub async fn calculate_file_hash_async(
     hasher.update(data);
     Ok(format!("{:x}", hasher.finalize()))
 }
+
+/// Incremental SHA-256 for content that arrives in chunks (streamed uploads)
+#[derive(Default)]
+pub struct StreamingHasher {
+    hasher: Sha256,
+}
+
+impl StreamingHasher {
+    pub fn new() -> Self {
+        Self { hasher: Sha256::new() }
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+    }
+
+    pub fn finalize(self) -> String {
+        format!("{:x}", self.hasher.finalize())
+    }
+}
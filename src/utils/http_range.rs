@@ -0,0 +1,152 @@
+// HTTP range and conditional request helpers
+// Used for seekable media playback from /api/media/:asset_id/download
+
+use chrono::{DateTime, Utc};
+
+/// Inclusive byte range within an object
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl ByteRange {
+    pub fn length(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeRequest {
+    /// No usable range; serve the whole object
+    Full,
+    Partial(ByteRange),
+    /// Range lies entirely past the end of the object (416)
+    Unsatisfiable,
+}
+
+/// Parse a `Range` header against an object of `size` bytes.
+/// Only single `bytes=` ranges are honoured; anything else falls back to the full object.
+pub fn parse_range_header(value: &str, size: u64) -> RangeRequest {
+    let spec = match value.trim().strip_prefix("bytes=") {
+        Some(spec) if !spec.contains(',') => spec.trim(),
+        _ => return RangeRequest::Full,
+    };
+    let (start, end) = match spec.split_once('-') {
+        Some(parts) => parts,
+        None => return RangeRequest::Full,
+    };
+
+    let range = match (start.trim(), end.trim()) {
+        // bytes=-N: the last N bytes
+        ("", suffix) => match suffix.parse::<u64>() {
+            Ok(0) => return RangeRequest::Unsatisfiable,
+            Ok(n) if size > 0 => ByteRange { start: size.saturating_sub(n), end: size - 1 },
+            Ok(_) => return RangeRequest::Unsatisfiable,
+            Err(_) => return RangeRequest::Full,
+        },
+        (start, end) => {
+            let start = match start.parse::<u64>() {
+                Ok(start) => start,
+                Err(_) => return RangeRequest::Full,
+            };
+            let end = if end.is_empty() {
+                size.saturating_sub(1)
+            } else {
+                match end.parse::<u64>() {
+                    Ok(end) if end >= start => end.min(size.saturating_sub(1)),
+                    _ => return RangeRequest::Full,
+                }
+            };
+            if start >= size {
+                return RangeRequest::Unsatisfiable;
+            }
+            ByteRange { start, end }
+        }
+    };
+
+    RangeRequest::Partial(range)
+}
+
+/// True when an `If-None-Match` / `If-Range` style header matches `etag` (weak comparison)
+pub fn etag_matches(header: &str, etag: &str) -> bool {
+    let strip = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = strip(etag);
+    header.split(',').any(|candidate| candidate.trim() == "*" || strip(candidate) == etag)
+}
+
+/// Format a timestamp as an HTTP-date (RFC 7231 IMF-fixdate)
+pub fn http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+pub fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partial(start: u64, end: u64) -> RangeRequest {
+        RangeRequest::Partial(ByteRange { start, end })
+    }
+
+    #[test]
+    fn parses_closed_and_open_ended_ranges() {
+        assert_eq!(parse_range_header("bytes=0-99", 1000), partial(0, 99));
+        assert_eq!(parse_range_header(" bytes= 10 - 19 ", 1000), partial(10, 19));
+        assert_eq!(parse_range_header("bytes=500-", 1000), partial(500, 999));
+        // An end past the object is clamped to its last byte
+        assert_eq!(parse_range_header("bytes=900-5000", 1000), partial(900, 999));
+        assert_eq!(ByteRange { start: 10, end: 19 }.length(), 10);
+    }
+
+    #[test]
+    fn parses_suffix_ranges() {
+        assert_eq!(parse_range_header("bytes=-100", 1000), partial(900, 999));
+        // A suffix longer than the object covers all of it
+        assert_eq!(parse_range_header("bytes=-5000", 1000), partial(0, 999));
+        assert_eq!(parse_range_header("bytes=-0", 1000), RangeRequest::Unsatisfiable);
+    }
+
+    #[test]
+    fn serves_the_full_object_for_multiple_or_malformed_ranges() {
+        for header in ["bytes=0-9,20-29", "bytes=-5, 0-1", "items=0-9", "bytes=abc", "bytes=9-0", "bytes=x-9", "bytes=-x"] {
+            assert_eq!(parse_range_header(header, 1000), RangeRequest::Full, "{}", header);
+        }
+    }
+
+    #[test]
+    fn ranges_past_the_end_are_unsatisfiable() {
+        assert_eq!(parse_range_header("bytes=1000-", 1000), RangeRequest::Unsatisfiable);
+        assert_eq!(parse_range_header("bytes=1000-1100", 1000), RangeRequest::Unsatisfiable);
+        assert_eq!(parse_range_header("bytes=999-", 1000), partial(999, 999));
+    }
+
+    #[test]
+    fn no_range_is_satisfiable_on_an_empty_object() {
+        for header in ["bytes=0-", "bytes=0-0", "bytes=-1"] {
+            assert_eq!(parse_range_header(header, 0), RangeRequest::Unsatisfiable, "{}", header);
+        }
+    }
+
+    #[test]
+    fn matches_etags_weakly() {
+        assert!(etag_matches("\"abc\"", "\"abc\""));
+        assert!(etag_matches("W/\"abc\"", "\"abc\""));
+        assert!(etag_matches("\"x\", \"abc\"", "W/\"abc\""));
+        assert!(etag_matches("*", "\"abc\""));
+        assert!(!etag_matches("\"abd\"", "\"abc\""));
+    }
+
+    #[test]
+    fn http_dates_round_trip() {
+        let time = DateTime::parse_from_rfc3339("2024-03-05T07:08:09Z").unwrap().with_timezone(&Utc);
+        assert_eq!(http_date(time), "Tue, 05 Mar 2024 07:08:09 GMT");
+        assert_eq!(parse_http_date(&http_date(time)), Some(time));
+        assert_eq!(parse_http_date("yesterday"), None);
+    }
+}
@@ -2,8 +2,20 @@
 // Generate and validate JWT tokens
 
 use jsonwebtoken::{encode, decode, EncodingKey, DecodingKey, Header, Validation, Algorithm};
-use crate::middleware::auth::Claims;
+use crate::middleware::auth::{Claims, TokenType};
 use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+pub const ACCESS_TOKEN_TTL_HOURS: i64 = 24;
+pub const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+
+/// A signed token and the identifiers needed to track or revoke it
+pub struct IssuedToken {
+    pub token: String,
+    pub jti: Uuid,
+    pub expires_at: DateTime<Utc>,
+}
 
 pub struct JWTService;
 
@@ -17,37 +29,48 @@ impl JWTService {
             })
     }
     
-    /// Generate JWT access token
-    pub fn generate_access_token(user_id: &str, email: &str, role: &str) -> Result<String> {
-        let secret = Self::get_secret();
-        let expiration = chrono::Utc::now() + chrono::Duration::hours(24);
-        
-        let claims = Claims {
-            user_id: user_id.to_string(),
-            email: email.to_string(),
-            role: role.to_string(),
-            exp: expiration.timestamp() as usize,
-        };
-        
-        let token = encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(secret.as_ref()),
-        )?;
-        
-        Ok(token)
+    /// Generate JWT access token; it never outlives its session
+    pub fn generate_access_token(
+        user_id: &str,
+        email: &str,
+        role: &str,
+        session_id: Uuid,
+        session_expires_at: DateTime<Utc>,
+    ) -> Result<IssuedToken> {
+        let expires_at = (Utc::now() + Duration::hours(ACCESS_TOKEN_TTL_HOURS)).min(session_expires_at);
+        Self::issue(TokenType::Access, user_id, email, role, session_id, expires_at)
     }
     
-    /// Generate JWT refresh token (longer expiry)
-    pub fn generate_refresh_token(user_id: &str, email: &str, role: &str) -> Result<String> {
+    /// Generate JWT refresh token (single use, expires with its session)
+    pub fn generate_refresh_token(
+        user_id: &str,
+        email: &str,
+        role: &str,
+        session_id: Uuid,
+        session_expires_at: DateTime<Utc>,
+    ) -> Result<IssuedToken> {
+        Self::issue(TokenType::Refresh, user_id, email, role, session_id, session_expires_at)
+    }
+
+    fn issue(
+        token_type: TokenType,
+        user_id: &str,
+        email: &str,
+        role: &str,
+        session_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<IssuedToken> {
         let secret = Self::get_secret();
-        let expiration = chrono::Utc::now() + chrono::Duration::days(30);
+        let jti = Uuid::new_v4();
         
         let claims = Claims {
             user_id: user_id.to_string(),
             email: email.to_string(),
             role: role.to_string(),
-            exp: expiration.timestamp() as usize,
+            exp: expires_at.timestamp() as usize,
+            token_type,
+            jti: jti.to_string(),
+            sid: session_id.to_string(),
         };
         
         let token = encode(
@@ -56,11 +79,11 @@ impl JWTService {
             &EncodingKey::from_secret(secret.as_ref()),
         )?;
         
-        Ok(token)
+        Ok(IssuedToken { token, jti, expires_at })
     }
     
-    /// Validate JWT token
-    pub fn validate_token(token: &str) -> Result<Claims> {
+    /// Validate JWT token; a refresh token is never accepted where an access token is expected
+    pub fn validate_token(token: &str, expected: TokenType) -> Result<Claims> {
         let secret = Self::get_secret();
         let decoding_key = DecodingKey::from_secret(secret.as_ref());
         let mut validation = Validation::new(Algorithm::HS256);
@@ -73,6 +96,9 @@ impl JWTService {
         if token_data.claims.exp < now {
             return Err(anyhow::anyhow!("Token expired"));
         }
+        if token_data.claims.token_type != expected {
+            return Err(anyhow::anyhow!("Expected {:?} token, got {:?}", expected, token_data.claims.token_type));
+        }
         
         Ok(token_data.claims)
     }
@@ -80,7 +106,6 @@ impl JWTService {
     /// Generate a secure random JWT secret (for setup)
     pub fn generate_secret() -> String {
         use sha2::{Digest, Sha256};
-        use uuid::Uuid;
         
         // Generate random secret
         let random_data = format!("{}-{}", Uuid::new_v4(), chrono::Utc::now().timestamp());
@@ -90,3 +115,37 @@ impl JWTService {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session_end() -> DateTime<Utc> {
+        Utc::now() + Duration::days(1)
+    }
+
+    #[test]
+    fn tokens_are_only_accepted_as_their_own_type() {
+        let session_id = Uuid::new_v4();
+        let access = JWTService::generate_access_token("u1", "a@example.com", "Viewer", session_id, session_end()).unwrap();
+        let refresh = JWTService::generate_refresh_token("u1", "a@example.com", "Viewer", session_id, session_end()).unwrap();
+
+        let claims = JWTService::validate_token(&access.token, TokenType::Access).unwrap();
+        assert_eq!(claims.jti, access.jti.to_string());
+        assert_eq!(claims.sid, session_id.to_string());
+        assert!(JWTService::validate_token(&refresh.token, TokenType::Refresh).is_ok());
+
+        assert!(JWTService::validate_token(&access.token, TokenType::Refresh).is_err());
+        assert!(JWTService::validate_token(&refresh.token, TokenType::Access).is_err());
+    }
+
+    #[test]
+    fn access_tokens_never_outlive_their_session() {
+        let session_expires_at = Utc::now() + Duration::minutes(5);
+        let access = JWTService::generate_access_token("u1", "a@example.com", "Viewer", Uuid::new_v4(), session_expires_at).unwrap();
+        assert_eq!(access.expires_at, session_expires_at);
+
+        let ended = JWTService::generate_access_token("u1", "a@example.com", "Viewer", Uuid::new_v4(), Utc::now() - Duration::minutes(5)).unwrap();
+        assert!(JWTService::validate_token(&ended.token, TokenType::Access).is_err());
+    }
+}
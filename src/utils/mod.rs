@@ -2,6 +2,8 @@
 // I-FR-02: Hash calculation for deduplication
 
 pub mod hash;
+pub mod http_range;
 pub mod jwt;
+pub mod public_url;
 
 pub use hash::*;
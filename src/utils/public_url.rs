@@ -0,0 +1,138 @@
+// Outbound URL checks
+// Webhook URLs are user-supplied, so requests to them must never reach the platform's own
+// network: loopback, private, link-local, unique-local and other non-routable addresses are refused.
+
+use anyhow::{anyhow, bail, Result};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use url::{Host, Url};
+
+/// An http(s) URL together with the public addresses its host resolved to.
+/// Connect to these addresses rather than resolving again, or a second lookup could be
+/// answered with an internal one.
+#[derive(Debug, Clone)]
+pub struct PublicTarget {
+    pub url: Url,
+    /// Host name to pin to `addrs`; None when the URL names an IP address directly
+    pub domain: Option<String>,
+    pub addrs: Vec<SocketAddr>,
+}
+
+/// Parse `url` and resolve its host, failing unless every address it resolves to is public
+pub async fn resolve_public(url: &str) -> Result<PublicTarget> {
+    let url = Url::parse(url)?;
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("scheme must be http or https");
+    }
+    let port = url.port_or_known_default().ok_or_else(|| anyhow!("URL has no port"))?;
+
+    let (domain, addrs) = match url.host() {
+        Some(Host::Ipv4(ip)) => (None, vec![SocketAddr::new(IpAddr::V4(ip), port)]),
+        Some(Host::Ipv6(ip)) => (None, vec![SocketAddr::new(IpAddr::V6(ip), port)]),
+        Some(Host::Domain(domain)) => {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((domain, port)).await?.collect();
+            (Some(domain.to_string()), addrs)
+        }
+        None => bail!("URL has no host"),
+    };
+
+    if addrs.is_empty() {
+        bail!("host did not resolve");
+    }
+    if let Some(addr) = addrs.iter().find(|addr| !is_public(addr.ip())) {
+        bail!("host resolves to non-public address {}", addr.ip());
+    }
+
+    Ok(PublicTarget { url, domain, addrs })
+}
+
+/// Whether `ip` is a globally routable unicast address
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_v4(mapped),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // Carrier-grade NAT 100.64.0.0/10
+        || (a == 100 && (64..128).contains(&b))
+        // Protocol assignments 192.0.0.0/24 and benchmarking 198.18.0.0/15
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (b == 18 || b == 19))
+        // Reserved 240.0.0.0/4
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || ip.is_unique_local()
+        || ip.is_unicast_link_local()
+        // Documentation 2001:db8::/32
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8)
+        // Deprecated site-local fec0::/10
+        || (first & 0xffc0) == 0xfec0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(ip: &str) -> bool {
+        is_public(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn rejects_internal_ipv4() {
+        for ip in [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254",
+            "0.0.0.0", "100.64.0.1", "255.255.255.255", "224.0.0.1", "192.0.0.8",
+        ] {
+            assert!(!public(ip), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn rejects_internal_ipv6() {
+        for ip in ["::1", "::", "fc00::1", "fd12:3456::1", "fe80::1", "ff02::1", "::ffff:127.0.0.1", "::ffff:10.0.0.1"] {
+            assert!(!public(ip), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn accepts_public_addresses() {
+        for ip in ["93.184.216.34", "8.8.8.8", "2606:4700:4700::1111", "::ffff:8.8.8.8"] {
+            assert!(public(ip), "{}", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn resolve_rejects_internal_hosts() {
+        assert!(resolve_public("http://127.0.0.1:8080/hook").await.is_err());
+        assert!(resolve_public("http://[::1]/hook").await.is_err());
+        assert!(resolve_public("http://169.254.169.254/latest/meta-data").await.is_err());
+        assert!(resolve_public("http://localhost/hook").await.is_err());
+        assert!(resolve_public("ftp://93.184.216.34/hook").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn resolve_keeps_public_ip_literal() {
+        let target = resolve_public("https://93.184.216.34/hook").await.unwrap();
+        assert_eq!(target.domain, None);
+        assert_eq!(target.addrs, vec!["93.184.216.34:443".parse().unwrap()]);
+    }
+}